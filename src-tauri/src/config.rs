@@ -12,6 +12,9 @@ pub struct RepositoryConfig {
     pub path: String,
     pub added_at: DateTime<Utc>,
     pub last_opened_at: Option<DateTime<Utc>>,
+    /// Branch name patterns (`*` wildcard) whose history must not be rewritten without confirmation
+    #[serde(default)]
+    pub protected_branches: Vec<String>,
 }
 
 /// Application configuration
@@ -74,6 +77,7 @@ impl AppConfig {
             path,
             added_at: Utc::now(),
             last_opened_at: None,
+            protected_branches: Vec::new(),
         };
         self.repositories.push(repo.clone());
         repo
@@ -94,6 +98,23 @@ impl AppConfig {
         }
     }
 
+    /// Replace the protected branch patterns of a repository
+    pub fn set_protected_branches(&mut self, id: &str, branches: Vec<String>) -> Option<RepositoryConfig> {
+        let repo = self.repositories.iter_mut().find(|r| r.id == id)?;
+        repo.protected_branches = branches.into_iter()
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty())
+            .collect();
+        Some(repo.clone())
+    }
+
+    /// Protected branch patterns of the repository at `path`
+    pub fn protected_branches(&self, path: &str) -> Vec<String> {
+        self.find_by_path(path)
+            .map(|r| r.protected_branches.clone())
+            .unwrap_or_default()
+    }
+
     /// Find repository by path
     pub fn find_by_path(&self, path: &str) -> Option<&RepositoryConfig> {
         self.repositories.iter().find(|r| r.path == path)
@@ -1,6 +1,58 @@
+use std::collections::HashMap;
+
 use git2::{Repository, StatusOptions};
 use crate::models::*;
 
+pub mod blame;
+pub mod branch;
+pub mod cleanup;
+pub mod clone;
+pub mod credentials;
+pub mod file_history;
+pub mod graph;
+pub mod history;
+pub mod interactive_rebase;
+pub mod merge;
+pub mod pickaxe;
+pub mod protection;
+pub mod rebase;
+pub mod reflog;
+pub mod remote;
+pub mod reset;
+pub mod revision;
+pub mod sequencer;
+pub mod stash;
+pub mod sync;
+#[cfg(test)]
+pub(crate) mod test_repo;
+pub mod tree;
+
+/// 打开仓库（供子模块复用）
+pub(crate) fn open(path: &str) -> Result<Repository, String> {
+    Repository::open(path).map_err(|e| format!("无法打开仓库: {}", e))
+}
+
+/// 获取 HEAD 指向的提交
+pub(crate) fn head_commit(repo: &Repository) -> Result<git2::Commit<'_>, String> {
+    repo.head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("无法获取 HEAD: {}", e))
+}
+
+/// 获取配置中的用户签名
+pub(crate) fn signature(repo: &Repository) -> Result<git2::Signature<'static>, String> {
+    repo.signature().map_err(|e| format!("无法获取签名: {}", e))
+}
+
+/// 对象的缩写 id，长度遵循 core.abbrev 并保证在仓库中唯一
+pub(crate) fn short_id(object: &git2::Object) -> String {
+    let id = object.id().to_string();
+    // 缩写失败时（理论上不会发生）退回到固定长度
+    object.short_id().ok()
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| id[..7].to_string())
+}
+
 /// 打开 Git 仓库
 pub fn open_repository(path: &str) -> Result<RepoInfo, String> {
     let repo = Repository::open(path)
@@ -13,12 +65,17 @@ pub fn open_repository(path: &str) -> Result<RepoInfo, String> {
         .to_string();
 
     let current_branch = get_current_branch(&repo)?;
+    let head_ref = repo.head().ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.name().map(|s| s.to_string()))
+        .unwrap_or_default();
 
     Ok(RepoInfo {
         path: path.to_string(),
         name,
         current_branch,
         is_bare: repo.is_bare(),
+        upstream: branch::upstream_status(&repo, &head_ref),
     })
 }
 
@@ -34,6 +91,95 @@ fn get_current_branch(repo: &Repository) -> Result<String, String> {
     }
 }
 
+/// 提交的基本信息
+pub(crate) fn commit_info(commit: &git2::Commit) -> CommitInfo {
+    let id = commit.id().to_string();
+    let short_id = short_id(commit.as_object());
+    // 非 UTF-8 编码的说明按有损方式解码，原编码见 encoding
+    let message = String::from_utf8_lossy(commit.message_bytes()).to_string();
+    let trailers = git2::message_trailers_strs(&message)
+        .map(|trailers| {
+            trailers.iter()
+                .map(|(key, value)| CommitTrailer {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let author = commit.author();
+    let committer = commit.committer();
+
+    CommitInfo {
+        id,
+        short_id,
+        message,
+        summary: commit.summary_bytes()
+            .map(|s| String::from_utf8_lossy(s).to_string())
+            .unwrap_or_default(),
+        body: commit.body_bytes()
+            .map(|s| String::from_utf8_lossy(s).to_string())
+            .unwrap_or_default(),
+        encoding: commit.message_encoding().map(|s| s.to_string()),
+        author_name: String::from_utf8_lossy(author.name_bytes()).to_string(),
+        author_email: String::from_utf8_lossy(author.email_bytes()).to_string(),
+        author_timestamp: author.when().seconds(),
+        author_offset: author.when().offset_minutes(),
+        committer_name: String::from_utf8_lossy(committer.name_bytes()).to_string(),
+        committer_email: String::from_utf8_lossy(committer.email_bytes()).to_string(),
+        timestamp: commit.time().seconds(),
+        committer_offset: committer.when().offset_minutes(),
+        parent_ids: commit.parent_ids().map(|id| id.to_string()).collect(),
+        trailers,
+        refs: Vec::new(),
+    }
+}
+
+/// 按提交分组的引用标注：HEAD、本地分支、远程跟踪分支和标签（附注标签解析到其指向的提交）
+pub(crate) fn ref_labels(repo: &Repository) -> HashMap<git2::Oid, Vec<RefLabel>> {
+    let mut labels: HashMap<git2::Oid, Vec<RefLabel>> = HashMap::new();
+    let head = repo.head().ok();
+    let current = head.as_ref()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.name().map(|s| s.to_string()));
+
+    if let Some(id) = head.as_ref().and_then(|head| head.target()) {
+        labels.entry(id).or_default().push(RefLabel {
+            name: "HEAD".to_string(),
+            full_name: "HEAD".to_string(),
+            kind: "head".to_string(),
+            is_current: current.is_none(),
+        });
+    }
+
+    let Ok(references) = repo.references() else { return labels };
+    for reference in references.flatten() {
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        let Some(full_name) = reference.name() else { continue };
+        let kind = if reference.is_branch() {
+            "branch"
+        } else if reference.is_remote() {
+            "remote"
+        } else if reference.is_tag() {
+            "tag"
+        } else {
+            continue;
+        };
+        let Ok(commit) = reference.peel_to_commit() else { continue };
+
+        labels.entry(commit.id()).or_default().push(RefLabel {
+            name: reference.shorthand().unwrap_or(full_name).to_string(),
+            full_name: full_name.to_string(),
+            kind: kind.to_string(),
+            is_current: current.as_deref() == Some(full_name),
+        });
+    }
+
+    labels
+}
+
 /// 获取提交历史
 pub fn get_commit_history(
     path: &str,
@@ -52,35 +198,16 @@ pub fn get_commit_history(
     revwalk.push_head()
         .map_err(|e| format!("无法推送 HEAD: {}", e))?;
 
+    let labels = ref_labels(&repo);
     let commits: Vec<CommitInfo> = revwalk
         .skip(offset)
         .take(limit)
         .filter_map(|oid_result| {
             let oid = oid_result.ok()?;
             let commit = repo.find_commit(oid).ok()?;
-
-            let parent_ids: Vec<String> = commit.parent_ids()
-                .map(|id| id.to_string())
-                .collect();
-
-            // 提前提取所有需要的字符串，避免生命周期问题
-            let id = commit.id().to_string();
-            let short_id = id[..7].to_string();
-            let message = commit.message().unwrap_or("").to_string();
-            let author = commit.author();
-            let author_name = author.name().unwrap_or("").to_string();
-            let author_email = author.email().unwrap_or("").to_string();
-            let timestamp = commit.time().seconds();
-
-            Some(CommitInfo {
-                id,
-                short_id,
-                message,
-                author_name,
-                author_email,
-                timestamp,
-                parent_ids,
-            })
+            let mut info = commit_info(&commit);
+            info.refs = labels.get(&oid).cloned().unwrap_or_default();
+            Some(info)
         })
         .collect();
 
@@ -100,6 +227,7 @@ pub fn get_file_status(path: &str) -> Result<FileStatusResponse, String> {
 
     let mut unstaged = Vec::new();
     let mut staged = Vec::new();
+    let mut conflicted = Vec::new();
 
     for entry in statuses.iter() {
         let file_path = entry.path().unwrap_or("").to_string();
@@ -114,6 +242,12 @@ pub fn get_file_status(path: &str) -> Result<FileStatusResponse, String> {
             status_code: status_code.clone(),
         };
 
+        // 冲突的文件单独列出
+        if status.contains(git2::Status::CONFLICTED) {
+            conflicted.push(file_info);
+            continue;
+        }
+
         // 已暂存的文件
         if status.intersects(
             git2::Status::INDEX_NEW
@@ -133,12 +267,139 @@ pub fn get_file_status(path: &str) -> Result<FileStatusResponse, String> {
         }
     }
 
-    Ok(FileStatusResponse { unstaged, staged })
+    Ok(FileStatusResponse {
+        unstaged,
+        staged,
+        conflicted,
+        state: format_repo_state(repo.state()).to_string(),
+        rebase: rebase::read_progress(&repo),
+    })
+}
+
+/// 格式化仓库当前进行中的操作
+pub(crate) fn format_repo_state(state: git2::RepositoryState) -> &'static str {
+    use git2::RepositoryState::*;
+    match state {
+        Clean => "clean",
+        Merge => "merge",
+        Revert | RevertSequence => "revert",
+        CherryPick | CherryPickSequence => "cherry_pick",
+        Bisect => "bisect",
+        Rebase | RebaseInteractive | RebaseMerge => "rebase",
+        ApplyMailbox | ApplyMailboxOrRebase => "apply_mailbox",
+    }
+}
+
+/// 把合并结果写入索引和工作区；有冲突时写入冲突标记并返回冲突文件，否则返回结果树
+///
+/// `force` 用于工作区已与索引一致、但与 HEAD 不同的场景（如不提交的连续拣选）。
+pub(crate) fn checkout_merge_index(
+    repo: &Repository,
+    merged: &mut git2::Index,
+    force: bool,
+) -> Result<Result<git2::Oid, Vec<String>>, String> {
+    let strategy = |checkout: &mut git2::build::CheckoutBuilder| {
+        if force {
+            checkout.force();
+        } else {
+            checkout.safe();
+        }
+    };
+
+    if merged.has_conflicts() {
+        let conflicts = merge::conflicted_paths(merged)?;
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        strategy(&mut checkout);
+        checkout.allow_conflicts(true).conflict_style_merge(true);
+        repo.checkout_index(Some(merged), Some(&mut checkout))
+            .map_err(|e| format!("检出失败: {}", e))?;
+        return Ok(Err(conflicts));
+    }
+
+    let tree_id = merged.write_tree_to(repo)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let tree = repo.find_tree(tree_id)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    strategy(&mut checkout);
+    repo.checkout_tree(tree.as_object(), Some(&mut checkout))
+        .map_err(|e| format!("检出失败: {}", e))?;
+    Ok(Ok(tree_id))
+}
+
+/// 已跟踪文件是否有未提交的修改（不含未跟踪文件）
+pub(crate) fn has_local_changes(repo: &Repository) -> Result<bool, String> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false);
+    let statuses = repo.statuses(Some(&mut opts))
+        .map_err(|e| format!("获取状态失败: {}", e))?;
+    Ok(!statuses.is_empty())
+}
+
+/// 确认仓库没有进行中的合并、变基等操作
+pub(crate) fn ensure_clean_state(repo: &Repository) -> Result<(), String> {
+    match repo.state() {
+        git2::RepositoryState::Clean => Ok(()),
+        state => Err(format!(
+            "仓库正在进行其他操作（{}），请先完成或放弃",
+            format_repo_state(state)
+        )),
+    }
+}
+
+/// 放弃合并类操作写入索引和工作区的变更，保留无关的本地修改（类似 `git reset --merge`）
+pub(crate) fn reset_merge(repo: &Repository) -> Result<(), String> {
+    let head = head_commit(repo)?;
+    let head_tree = head.tree()
+        .map_err(|e| format!("无法获取树: {}", e))?;
+
+    let statuses = repo.statuses(None)
+        .map_err(|e| format!("获取状态失败: {}", e))?;
+    let paths: Vec<String> = statuses.iter()
+        .filter(|entry| entry.status().intersects(
+            git2::Status::INDEX_NEW
+            | git2::Status::INDEX_MODIFIED
+            | git2::Status::INDEX_DELETED
+            | git2::Status::INDEX_RENAMED
+            | git2::Status::INDEX_TYPECHANGE
+            | git2::Status::CONFLICTED
+        ))
+        .filter_map(|entry| entry.path().map(|p| p.to_string()))
+        .collect();
+
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    repo.reset_default(Some(head.as_object()), paths.iter())
+        .map_err(|e| format!("重置索引失败: {}", e))?;
+
+    let workdir = repo.workdir()
+        .ok_or_else(|| "裸仓库没有工作区".to_string())?;
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.force();
+    let mut has_checkout = false;
+    for file_path in &paths {
+        if head_tree.get_path(std::path::Path::new(file_path)).is_ok() {
+            checkout.path(file_path);
+            has_checkout = true;
+        } else {
+            let _ = std::fs::remove_file(workdir.join(file_path));
+        }
+    }
+    if has_checkout {
+        repo.checkout_head(Some(&mut checkout))
+            .map_err(|e| format!("恢复工作区失败: {}", e))?;
+    }
+
+    Ok(())
 }
 
 /// 格式化 Git 状态为可读文本
 fn format_status(status: git2::Status) -> String {
-    if status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW) {
+    if status.contains(git2::Status::CONFLICTED) {
+        "冲突".to_string()
+    } else if status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW) {
         "新建".to_string()
     } else if status.contains(git2::Status::WT_MODIFIED) || status.contains(git2::Status::INDEX_MODIFIED) {
         "修改".to_string()
@@ -153,7 +414,9 @@ fn format_status(status: git2::Status) -> String {
 
 /// 格式化状态代码
 fn format_status_code(status: git2::Status) -> String {
-    if status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW) {
+    if status.contains(git2::Status::CONFLICTED) {
+        "U".to_string()
+    } else if status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW) {
         "A".to_string()
     } else if status.contains(git2::Status::WT_MODIFIED) || status.contains(git2::Status::INDEX_MODIFIED) {
         "M".to_string()
@@ -183,11 +446,19 @@ pub fn get_file_diff(path: &str, file_path: &str) -> Result<DiffResponse, String
     let diff = repo.diff_tree_to_workdir(Some(&tree), Some(&mut diff_opts))
         .map_err(|e| format!("创建 diff 失败: {}", e))?;
 
+    Ok(DiffResponse {
+        file_path: file_path.to_string(),
+        hunks: diff_hunks(&diff)?,
+    })
+}
+
+/// 将 diff 按 hunk 整理为行列表
+pub(crate) fn diff_hunks(diff: &git2::Diff) -> Result<Vec<DiffHunk>, String> {
     let mut hunks = Vec::new();
 
     diff.print(git2::DiffFormat::Patch, |_delta, hunk, line| {
-        if let Some(hunk) = hunk {
-            // 创建新的 hunk
+        // 每一行都会带上所属 hunk，只在 hunk 头处创建新的 hunk
+        if let (Some(hunk), 'H') = (hunk, line.origin()) {
             let diff_hunk = DiffHunk {
                 old_start: hunk.old_start(),
                 old_lines: hunk.old_lines(),
@@ -196,9 +467,10 @@ pub fn get_file_diff(path: &str, file_path: &str) -> Result<DiffResponse, String
                 lines: Vec::new(),
             };
             hunks.push(diff_hunk);
+            return true;
         }
 
-        if !hunks.is_empty() {
+        if !hunks.is_empty() && !matches!(line.origin(), 'F' | 'H') {
             let line_type = match line.origin() {
                 '+' => "add",
                 '-' => "delete",
@@ -220,8 +492,109 @@ pub fn get_file_diff(path: &str, file_path: &str) -> Result<DiffResponse, String
         true
     }).map_err(|e| format!("打印 diff 失败: {}", e))?;
 
-    Ok(DiffResponse {
-        file_path: file_path.to_string(),
-        hunks,
-    })
+    Ok(hunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    const MESSAGE: &str = "Subject line\ncontinued\n\nBody text.\n\n\
+        Signed-off-by: A <a@example.com>\nCo-authored-by: B <b@example.com>\n";
+
+    fn latest(repo: &TempRepo) -> CommitInfo {
+        get_commit_history(repo.path(), 1, 0).unwrap().remove(0)
+    }
+
+    #[test]
+    fn commit_info_reads_message_parts() {
+        let repo = TempRepo::new("commit-info");
+        repo.write("f.txt", "1\n");
+        repo.git(&["add", "-A"]);
+        repo.git_with_env(&["commit", "-q", "-m", MESSAGE], &[
+            ("GIT_AUTHOR_NAME", "Author"),
+            ("GIT_AUTHOR_DATE", "1700000000 +0530"),
+            ("GIT_COMMITTER_NAME", "Committer"),
+            ("GIT_COMMITTER_DATE", "1700000100 -0800"),
+        ]);
+
+        let info = latest(&repo);
+        // 多行标题按 git 的规则合并为一行
+        assert_eq!(info.summary, "Subject line continued");
+        assert!(info.body.starts_with("Body text."));
+        assert_eq!(info.message, MESSAGE);
+        let trailers: Vec<_> = info.trailers.iter().map(|t| (t.key.as_str(), t.value.as_str())).collect();
+        assert_eq!(trailers, vec![
+            ("Signed-off-by", "A <a@example.com>"),
+            ("Co-authored-by", "B <b@example.com>"),
+        ]);
+        assert_eq!((info.author_name.as_str(), info.committer_name.as_str()), ("Author", "Committer"));
+        assert_eq!((info.author_timestamp, info.author_offset), (1700000000, 330));
+        assert_eq!((info.timestamp, info.committer_offset), (1700000100, -480));
+        assert!(info.parent_ids.is_empty());
+        assert_eq!(info.encoding, None);
+    }
+
+    #[test]
+    fn commit_info_without_trailers() {
+        let repo = TempRepo::new("commit-info-plain");
+        repo.commit("first");
+        repo.git(&["commit", "-q", "--allow-empty", "-m", "Second\n\nSigned-off-by: A <a@example.com>\n\nNot a trailer block."]);
+
+        let info = latest(&repo);
+        assert!(info.trailers.is_empty());
+        assert_eq!(info.parent_ids, vec![repo.rev_parse("HEAD~1")]);
+    }
+
+    fn label_kinds(repo: &TempRepo, id: &str) -> Vec<(String, String, bool)> {
+        let repository = Repository::open(repo.path()).unwrap();
+        let mut labels: Vec<_> = ref_labels(&repository)
+            .remove(&git2::Oid::from_str(id).unwrap())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|label| (label.name, label.kind, label.is_current))
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    #[test]
+    fn ref_labels_mark_kinds_and_current_branch() {
+        let repo = TempRepo::new("ref-labels");
+        let id = repo.commit("first");
+        repo.git(&["branch", "other"]);
+        repo.git(&["tag", "-a", "v1", "-m", "v1"]);
+        repo.git(&["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        repo.git(&["tag", "-a", "tree", "-m", "tree", "HEAD^{tree}"]);
+
+        let label = |name: &str, kind: &str, is_current| (name.to_string(), kind.to_string(), is_current);
+        assert_eq!(label_kinds(&repo, &id), vec![
+            label("HEAD", "head", false),
+            label("main", "branch", true),
+            label("origin/main", "remote", false),
+            label("other", "branch", false),
+            label("v1", "tag", false),
+        ]);
+
+        // 分离 HEAD 时 HEAD 本身是当前位置
+        repo.git(&["checkout", "-q", "--detach"]);
+        assert_eq!(label_kinds(&repo, &id)[..2], [
+            label("HEAD", "head", true),
+            label("main", "branch", false),
+        ]);
+    }
+
+    #[test]
+    fn short_id_follows_core_abbrev() {
+        let repo = TempRepo::new("commit-info-abbrev");
+        let id = repo.commit("first");
+
+        let info = latest(&repo);
+        assert_eq!(info.id, id);
+        assert_eq!(info.short_id, id[..7]);
+
+        repo.git(&["config", "core.abbrev", "10"]);
+        assert_eq!(latest(&repo).short_id, id[..10]);
+    }
 }
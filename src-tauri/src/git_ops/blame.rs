@@ -0,0 +1,503 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use git2::{Blame, BlameOptions, Commit, DiffOptions, Oid, Patch, Repository, Tree};
+
+use super::file_history::renamed_from;
+use super::open;
+use crate::models::*;
+
+/// 仓库根目录下约定的忽略提交列表，未配置 `blame.ignoreRevsFile` 时使用
+const IGNORE_REVS_FILE: &str = ".git-blame-ignore-revs";
+
+/// 一行追溯到被忽略的提交时，沿父提交继续追溯的最多次数
+const MAX_IGNORE_DEPTH: usize = 64;
+
+/// 逐行追溯文件内容的来源（`git blame`）
+///
+/// `rev` 为空时追溯工作区中的文件，尚未提交的行没有提交 id。
+/// 来自被忽略提交的行会沿该提交的第一个父提交继续追溯，与 `git blame --ignore-rev` 相同；
+/// 父提交中找不到对应行（如该提交新增的行）时仍归属于被忽略的提交。
+pub fn blame_file(
+    path: &str,
+    file_path: &str,
+    rev: Option<&str>,
+    options: &BlameFileOptions,
+) -> Result<BlameResult, String> {
+    let repo = open(path)?;
+    let file_path = file_path.trim_matches('/');
+    let ignored = ignored_revs(&repo, options)?;
+    let mut blamer = Blamer {
+        repo: &repo,
+        ignore_whitespace: options.ignore_whitespace,
+        blames: HashMap::new(),
+        line_maps: HashMap::new(),
+    };
+
+    let mut origins = match rev {
+        Some(rev) => {
+            let commit = repo.revparse_single(rev)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|e| format!("无法解析版本 {}: {}", rev, e))?;
+            blamer.blame_at(commit.id(), file_path)?.to_vec()
+        }
+        None => blamer.blame_workdir(file_path)?,
+    };
+    if !ignored.is_empty() {
+        for origin in origins.iter_mut() {
+            for _ in 0..MAX_IGNORE_DEPTH {
+                if !ignored.contains(&origin.commit) {
+                    break;
+                }
+                match blamer.in_parent(origin)? {
+                    Some(parent) => *origin = parent,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    let mut ignored_revs: Vec<String> = ignored.iter().map(|id| id.to_string()).collect();
+    ignored_revs.sort();
+    Ok(BlameResult {
+        path: file_path.to_string(),
+        rev: rev.map(|rev| rev.to_string()),
+        ranges: ranges(&repo, &origins)?,
+        ignored_revs,
+    })
+}
+
+/// 一行内容的来源：引入它的提交、该提交中的路径和行号（从 1 开始）
+#[derive(Clone)]
+struct Origin {
+    commit: Oid,
+    path: String,
+    line: usize,
+    boundary: bool,
+}
+
+struct Blamer<'r> {
+    repo: &'r Repository,
+    ignore_whitespace: bool,
+    // (提交, 路径) -> 该版本中每一行的来源
+    blames: HashMap<(Oid, String), Vec<Origin>>,
+    // (提交, 路径) -> 该版本的行在父提交中的位置，父提交中没有该文件时为 None
+    line_maps: HashMap<(Oid, String), Option<LineMap>>,
+}
+
+/// 父提交中的路径，以及按行号（下标）对应的父提交中的行号
+struct LineMap {
+    parent_path: String,
+    lines: Vec<Option<usize>>,
+}
+
+impl Blamer<'_> {
+    fn options(&self) -> BlameOptions {
+        let mut options = BlameOptions::new();
+        options.ignore_whitespace(self.ignore_whitespace);
+        options
+    }
+
+    fn blame_at(&mut self, commit: Oid, file_path: &str) -> Result<&[Origin], String> {
+        let key = (commit, file_path.to_string());
+        if !self.blames.contains_key(&key) {
+            let mut options = self.options();
+            options.newest_commit(commit);
+            let blame = self.repo.blame_file(Path::new(file_path), Some(&mut options))
+                .map_err(|e| format!("追溯 {} 失败: {}", file_path, e))?;
+            self.blames.insert(key.clone(), origins(&blame, file_path));
+        }
+        Ok(&self.blames[&key])
+    }
+
+    /// 工作区中的文件：先追溯 HEAD 中的版本，再与工作区内容比较，修改过的行标记为未提交
+    fn blame_workdir(&self, file_path: &str) -> Result<Vec<Origin>, String> {
+        let workdir = self.repo.workdir()
+            .ok_or_else(|| "裸仓库没有工作区".to_string())?;
+        let content = std::fs::read(workdir.join(file_path))
+            .map_err(|e| format!("读取文件 {} 失败: {}", file_path, e))?;
+
+        let blame = self.repo.blame_file(Path::new(file_path), Some(&mut self.options()))
+            .map_err(|e| format!("追溯 {} 失败: {}", file_path, e))?;
+        let blame = blame.blame_buffer(&content)
+            .map_err(|e| format!("追溯 {} 失败: {}", file_path, e))?;
+        Ok(origins(&blame, file_path))
+    }
+
+    /// 被忽略提交中的一行在第一个父提交中的来源，找不到对应行时返回 None
+    fn in_parent(&mut self, origin: &Origin) -> Result<Option<Origin>, String> {
+        let commit = self.repo.find_commit(origin.commit)
+            .map_err(|e| format!("无法读取提交 {}: {}", origin.commit, e))?;
+        let Ok(parent) = commit.parent(0) else { return Ok(None) };
+
+        let key = (origin.commit, origin.path.clone());
+        if !self.line_maps.contains_key(&key) {
+            let map = self.line_map(&commit, &parent, &origin.path)?;
+            self.line_maps.insert(key.clone(), map);
+        }
+        let Some(map) = &self.line_maps[&key] else { return Ok(None) };
+        let Some(line) = map.lines.get(origin.line).copied().flatten() else { return Ok(None) };
+
+        let parent_path = map.parent_path.clone();
+        let blame = self.blame_at(parent.id(), &parent_path)?;
+        Ok(blame.get(line - 1).cloned())
+    }
+
+    fn line_map(
+        &self,
+        commit: &Commit,
+        parent: &Commit,
+        file_path: &str,
+    ) -> Result<Option<LineMap>, String> {
+        let tree = commit.tree()
+            .map_err(|e| format!("无法获取树: {}", e))?;
+        let parent_tree = parent.tree()
+            .map_err(|e| format!("无法获取树: {}", e))?;
+        let parent_path = match parent_tree.get_path(Path::new(file_path)) {
+            Ok(_) => file_path.to_string(),
+            Err(_) => match renamed_from(self.repo, &parent_tree, &tree, file_path)? {
+                Some(old_path) => old_path,
+                None => return Ok(None),
+            },
+        };
+
+        let new_blob = blob_at(self.repo, &tree, file_path)?;
+        let old_blob = blob_at(self.repo, &parent_tree, &parent_path)?;
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.context_lines(0).ignore_whitespace(self.ignore_whitespace);
+        let patch = Patch::from_blobs(
+            &old_blob,
+            Some(Path::new(&parent_path)),
+            &new_blob,
+            Some(Path::new(file_path)),
+            Some(&mut diff_opts),
+        ).map_err(|e| format!("比较 {} 失败: {}", file_path, e))?;
+
+        let lines = map_lines(&patch, line_count(new_blob.content()))?;
+        Ok(Some(LineMap { parent_path, lines }))
+    }
+}
+
+fn origins(blame: &Blame, file_path: &str) -> Vec<Origin> {
+    let mut origins = Vec::new();
+    for hunk in blame.iter() {
+        let commit = hunk.final_commit_id();
+        let path = hunk.path()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| file_path.to_string());
+        // 未提交的行没有原始版本，使用其在工作区中的行号
+        let start = match commit.is_zero() {
+            true => hunk.final_start_line(),
+            false => hunk.orig_start_line(),
+        };
+        for offset in 0..hunk.lines_in_hunk() {
+            origins.push(Origin {
+                commit,
+                path: path.clone(),
+                line: start + offset,
+                boundary: hunk.is_boundary(),
+            });
+        }
+    }
+    origins
+}
+
+/// 按 diff 把新版本的行号映射到旧版本（下标为新版本行号）
+///
+/// 修改块中第 k 个新增的行对应第 k 个删除的行，多出来的新增行没有对应行。
+fn map_lines(patch: &Patch, new_len: usize) -> Result<Vec<Option<usize>>, String> {
+    let mut map = vec![None; new_len + 1];
+    let mut next_new = 1;
+    let mut offset = 0isize;
+    // 修改块之间未改变的行按行号差对应
+    let unchanged = |map: &mut [Option<usize>], from: usize, to: usize, offset: isize| {
+        for (line, slot) in map.iter_mut().enumerate().take(to).skip(from) {
+            *slot = line.checked_add_signed(offset);
+        }
+    };
+
+    for index in 0..patch.num_hunks() {
+        let (hunk, count) = patch.hunk(index)
+            .map_err(|e| format!("读取 diff 失败: {}", e))?;
+        // 只有删除（或只有新增）的块，起始行指向块之前的一行
+        let first = |start: u32, lines: u32| start as usize + usize::from(lines == 0);
+        let new_first = first(hunk.new_start(), hunk.new_lines());
+        let old_first = first(hunk.old_start(), hunk.old_lines());
+
+        unchanged(&mut map, next_new, new_first, offset);
+        let mut deleted = Vec::new();
+        let mut added = 0;
+        for line_index in 0..count {
+            let line = patch.line_in_hunk(index, line_index)
+                .map_err(|e| format!("读取 diff 失败: {}", e))?;
+            match (line.origin(), line.old_lineno(), line.new_lineno()) {
+                ('-', Some(old), _) => {
+                    if added > 0 {
+                        deleted.clear();
+                        added = 0;
+                    }
+                    deleted.push(old as usize);
+                }
+                ('+', _, Some(new)) => {
+                    if let Some(slot) = map.get_mut(new as usize) {
+                        *slot = deleted.get(added).copied();
+                    }
+                    added += 1;
+                }
+                _ => {}
+            }
+        }
+
+        next_new = new_first + hunk.new_lines() as usize;
+        offset = (old_first + hunk.old_lines() as usize) as isize - next_new as isize;
+    }
+    unchanged(&mut map, next_new, new_len + 1, offset);
+    Ok(map)
+}
+
+fn line_count(content: &[u8]) -> usize {
+    let newlines = content.iter().filter(|&&b| b == b'\n').count();
+    newlines + usize::from(content.last().is_some_and(|&b| b != b'\n'))
+}
+
+fn blob_at<'r>(repo: &'r Repository, tree: &Tree, file_path: &str) -> Result<git2::Blob<'r>, String> {
+    tree.get_path(Path::new(file_path))
+        .and_then(|entry| entry.to_object(repo))
+        .and_then(|object| object.peel_to_blob())
+        .map_err(|e| format!("无法读取文件 {}: {}", file_path, e))
+}
+
+/// 把逐行的来源合并为连续的区间，并附上提交信息
+fn ranges(repo: &Repository, origins: &[Origin]) -> Result<Vec<BlameRange>, String> {
+    let mut commits: HashMap<Oid, BlameRange> = HashMap::new();
+    let mut ranges: Vec<BlameRange> = Vec::new();
+
+    for (index, origin) in origins.iter().enumerate() {
+        let commit_id = (!origin.commit.is_zero()).then(|| origin.commit.to_string());
+        if let Some(last) = ranges.last_mut() {
+            if last.commit_id == commit_id
+                && last.orig_path == origin.path
+                && last.orig_start_line + last.lines == origin.line
+                && last.boundary == origin.boundary
+            {
+                last.lines += 1;
+                continue;
+            }
+        }
+
+        if let Entry::Vacant(entry) = commits.entry(origin.commit) {
+            entry.insert(commit_range(repo, origin.commit)?);
+        }
+        let mut range = commits[&origin.commit].clone();
+        range.start_line = index + 1;
+        range.lines = 1;
+        range.orig_path = origin.path.clone();
+        range.orig_start_line = origin.line;
+        range.boundary = origin.boundary;
+        ranges.push(range);
+    }
+    Ok(ranges)
+}
+
+/// 提交的作者、说明和父提交；未提交的行以 HEAD 作为之前的版本
+fn commit_range(repo: &Repository, id: Oid) -> Result<BlameRange, String> {
+    let mut range = BlameRange {
+        start_line: 0,
+        lines: 0,
+        commit_id: None,
+        author_name: String::new(),
+        author_email: String::new(),
+        timestamp: 0,
+        summary: String::new(),
+        orig_path: String::new(),
+        orig_start_line: 0,
+        parent_id: None,
+        boundary: false,
+    };
+    if id.is_zero() {
+        range.parent_id = repo.head().ok()
+            .and_then(|head| head.target())
+            .map(|head| head.to_string());
+        return Ok(range);
+    }
+
+    let commit = repo.find_commit(id)
+        .map_err(|e| format!("无法读取提交 {}: {}", id, e))?;
+    let author = commit.author();
+    range.commit_id = Some(id.to_string());
+    range.author_name = author.name().unwrap_or("").to_string();
+    range.author_email = author.email().unwrap_or("").to_string();
+    range.timestamp = author.when().seconds();
+    range.summary = commit.summary().unwrap_or("").to_string();
+    range.parent_id = commit.parent_id(0).ok().map(|parent| parent.to_string());
+    Ok(range)
+}
+
+/// 要忽略的提交：`options.ignore_revs` 加上忽略列表文件中的提交
+///
+/// 列表文件中无法解析的提交（如历史被改写后已不存在）直接跳过。
+fn ignored_revs(repo: &Repository, options: &BlameFileOptions) -> Result<HashSet<Oid>, String> {
+    let mut ignored = HashSet::new();
+    for spec in &options.ignore_revs {
+        let commit = repo.revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| format!("无法解析版本 {}: {}", spec, e))?;
+        ignored.insert(commit.id());
+    }
+    if options.skip_ignore_revs_file {
+        return Ok(ignored);
+    }
+
+    for file in ignore_revs_files(repo) {
+        let Ok(content) = std::fs::read_to_string(&file) else { continue };
+        let specs = content.lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty());
+        for spec in specs {
+            if let Ok(commit) = repo.revparse_single(spec).and_then(|object| object.peel_to_commit()) {
+                ignored.insert(commit.id());
+            }
+        }
+    }
+    Ok(ignored)
+}
+
+/// `blame.ignoreRevsFile` 配置的文件（相对仓库根目录，空值清除之前的配置），
+/// 未配置时使用存在的 `.git-blame-ignore-revs`
+fn ignore_revs_files(repo: &Repository) -> Vec<PathBuf> {
+    let Some(workdir) = repo.workdir() else { return Vec::new() };
+
+    let mut files = Vec::new();
+    let mut configured = false;
+    if let Ok(config) = repo.config() {
+        if let Ok(mut entries) = config.multivar("blame.ignoreRevsFile", None) {
+            while let Some(Ok(entry)) = entries.next() {
+                configured = true;
+                match entry.value() {
+                    Some(value) if !value.is_empty() => files.push(workdir.join(value)),
+                    _ => files.clear(),
+                }
+            }
+        }
+    }
+    if !configured {
+        let default = workdir.join(IGNORE_REVS_FILE);
+        if default.is_file() {
+            files.push(default);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// 新版本每一行（从第 1 行起）对应的旧版本行号
+    fn mapped(old: &str, new: &str) -> Vec<Option<usize>> {
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.context_lines(0);
+        let patch = Patch::from_buffers(old.as_bytes(), None, new.as_bytes(), None, Some(&mut diff_opts)).unwrap();
+        map_lines(&patch, line_count(new.as_bytes())).unwrap()[1..].to_vec()
+    }
+
+    #[test]
+    fn maps_lines_through_hunks() {
+        assert_eq!(mapped("a\nb\nc\n", "a\nb\nc\n"), vec![Some(1), Some(2), Some(3)]);
+        // 修改的行按顺序对应，多出的新增行没有对应行
+        assert_eq!(mapped("a\nb\nc\n", "a\nB\nc\nd\n"), vec![Some(1), Some(2), Some(3), None]);
+        assert_eq!(mapped("a\nb\nc\nd\n", "a\nX\nd\n"), vec![Some(1), Some(2), Some(4)]);
+        // 只有删除或只有新增的块之后按行号差对应
+        assert_eq!(mapped("a\nb\nc\n", "a\nc\n"), vec![Some(1), Some(3)]);
+        assert_eq!(mapped("a\n", "x\na\n"), vec![None, Some(1)]);
+        assert_eq!(mapped("a\nb\n", "a\nb\nc"), vec![Some(1), Some(2), None]);
+    }
+
+    fn summaries(result: &BlameResult) -> Vec<(usize, usize, &str)> {
+        result.ranges.iter()
+            .map(|range| (range.start_line, range.lines, range.summary.as_str()))
+            .collect()
+    }
+
+    fn without_ignore_file() -> BlameFileOptions {
+        BlameFileOptions { skip_ignore_revs_file: true, ..Default::default() }
+    }
+
+    /// a.txt 经过两次修改后在格式化提交中改名为 b.txt 并缩进第 3 行，最后追加一行
+    fn formatted_history() -> (TempRepo, String) {
+        let repo = TempRepo::new("blame");
+        repo.write("a.txt", "one\ntwo\nthree\nfour\n");
+        repo.commit("c1");
+        repo.write("a.txt", "one\nTWO\nthree\nfour\nfive\n");
+        repo.commit("c2");
+        repo.git(&["mv", "a.txt", "b.txt"]);
+        repo.write("b.txt", "one\nTWO\n    three\nfour\nfive\n");
+        let format = repo.commit("format");
+        repo.write("b.txt", "one\nTWO\n    three\nfour\nfive\nsix\n");
+        repo.commit("c4");
+        (repo, format)
+    }
+
+    #[test]
+    fn blames_committed_lines() {
+        let (repo, _) = formatted_history();
+
+        let result = blame_file(repo.path(), "b.txt", Some("HEAD"), &without_ignore_file()).unwrap();
+        assert_eq!(summaries(&result), vec![
+            (1, 1, "c1"), (2, 1, "c2"), (3, 1, "format"), (4, 1, "c1"), (5, 1, "c2"), (6, 1, "c4"),
+        ]);
+        assert_eq!(result.ranges[0].orig_path, "a.txt");
+        assert!(result.ignored_revs.is_empty());
+
+        let whitespace = BlameFileOptions { ignore_whitespace: true, ..without_ignore_file() };
+        let result = blame_file(repo.path(), "b.txt", Some("HEAD"), &whitespace).unwrap();
+        assert_eq!(summaries(&result)[2], (3, 2, "c1"));
+    }
+
+    #[test]
+    fn ignored_revisions_pass_lines_to_parent() {
+        let (repo, format) = formatted_history();
+
+        let options = BlameFileOptions { ignore_revs: vec![format.clone()], ..without_ignore_file() };
+        let result = blame_file(repo.path(), "b.txt", Some("HEAD"), &options).unwrap();
+        assert_eq!(summaries(&result), vec![
+            (1, 1, "c1"), (2, 1, "c2"), (3, 2, "c1"), (5, 1, "c2"), (6, 1, "c4"),
+        ]);
+        assert_eq!(result.ranges[2].orig_path, "a.txt");
+        assert_eq!(result.ranges[2].orig_start_line, 3);
+        assert_eq!(result.ignored_revs, vec![format.clone()]);
+
+        // 默认读取仓库根目录下的忽略列表，跳过注释
+        repo.write(IGNORE_REVS_FILE, &format!("# formatting\n{}\n", format));
+        let from_file = blame_file(repo.path(), "b.txt", Some("HEAD"), &Default::default()).unwrap();
+        assert_eq!(summaries(&from_file), summaries(&result));
+        let skipped = blame_file(repo.path(), "b.txt", Some("HEAD"), &without_ignore_file()).unwrap();
+        assert_eq!(skipped.ranges.len(), 6);
+
+        // blame.ignoreRevsFile 设为空表示不使用忽略列表
+        repo.git(&["config", "blame.ignoreRevsFile", ""]);
+        let disabled = blame_file(repo.path(), "b.txt", Some("HEAD"), &Default::default()).unwrap();
+        assert_eq!(disabled.ranges.len(), 6);
+    }
+
+    #[test]
+    fn blames_working_copy() {
+        let (repo, _) = formatted_history();
+        repo.write("b.txt", "one\nTWO\n    three\nfour\nFIVE\nsix\nseven\n");
+
+        let result = blame_file(repo.path(), "b.txt", None, &without_ignore_file()).unwrap();
+        let uncommitted: Vec<_> = result.ranges.iter()
+            .filter(|range| range.commit_id.is_none())
+            .map(|range| (range.start_line, range.lines))
+            .collect();
+        assert_eq!(uncommitted, vec![(5, 1), (7, 1)]);
+
+        // 从某一行的父提交继续查看之前的版本
+        let c4 = result.ranges.iter().find(|range| range.summary == "c4").unwrap();
+        let parent = c4.parent_id.as_deref().unwrap();
+        let before = blame_file(repo.path(), &c4.orig_path, Some(parent), &without_ignore_file()).unwrap();
+        assert_eq!(before.ranges.iter().map(|range| range.lines).sum::<usize>(), 5);
+    }
+}
@@ -0,0 +1,200 @@
+use git2::{BranchType, Repository};
+
+use super::open;
+use crate::models::*;
+
+/// 列出本地分支和远程跟踪分支，本地分支附带上游分支的跟踪状态
+pub fn list_branches(path: &str) -> Result<Vec<BranchInfo>, String> {
+    let repo = open(path)?;
+    let branches = repo.branches(None)
+        .map_err(|e| format!("读取分支失败: {}", e))?;
+
+    let mut result = Vec::new();
+    for item in branches {
+        let (branch, kind) = item.map_err(|e| format!("读取分支失败: {}", e))?;
+        let reference = branch.get();
+        let Some(full_name) = reference.name() else { continue };
+        // 跳过 refs/remotes/origin/HEAD 这类符号引用
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        let is_remote = kind == BranchType::Remote;
+
+        result.push(BranchInfo {
+            name: reference.shorthand().unwrap_or(full_name).to_string(),
+            full_name: full_name.to_string(),
+            is_remote,
+            is_head: branch.is_head(),
+            commit_id: reference.target().map(|id| id.to_string()),
+            upstream: (!is_remote).then(|| upstream_status(&repo, full_name)),
+        });
+    }
+
+    // 本地分支在前，各自按名称排序
+    result.sort_by(|a, b| a.is_remote.cmp(&b.is_remote).then_with(|| a.name.cmp(&b.name)));
+    Ok(result)
+}
+
+/// 设置或修改分支的上游分支（`git branch --set-upstream-to=<upstream> <branch>`）
+///
+/// `upstream` 可以是远程跟踪分支（如 `origin/main`）或本地分支，必须已经存在。
+pub fn set_upstream(path: &str, branch: &str, upstream: &str) -> Result<BranchInfo, String> {
+    let repo = open(path)?;
+    let mut local = find_local_branch(&repo, branch)?;
+    let upstream = upstream.trim()
+        .trim_start_matches("refs/remotes/")
+        .trim_start_matches("refs/heads/");
+    if upstream.is_empty() {
+        return Err("上游分支不能为空".to_string());
+    }
+    local.set_upstream(Some(upstream))
+        .map_err(|e| format!("设置上游分支 {} 失败: {}", upstream, e))?;
+
+    branch_info(&repo, branch)
+}
+
+/// 取消分支的上游分支（`git branch --unset-upstream <branch>`）
+pub fn unset_upstream(path: &str, branch: &str) -> Result<BranchInfo, String> {
+    let repo = open(path)?;
+    let mut local = find_local_branch(&repo, branch)?;
+    let full_name = format!("refs/heads/{}", branch.trim_start_matches("refs/heads/"));
+    // 上游已被删除（gone）时 libgit2 仍能读到配置，只有完全未配置时才无需处理
+    if repo.branch_upstream_name(&full_name).is_ok() {
+        local.set_upstream(None)
+            .map_err(|e| format!("取消上游分支失败: {}", e))?;
+    }
+
+    branch_info(&repo, branch)
+}
+
+/// 本地分支与上游分支的跟踪状态
+pub(crate) fn upstream_status(repo: &Repository, local_ref: &str) -> UpstreamStatus {
+    let mut status = UpstreamStatus {
+        status: "none".to_string(),
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+    };
+    let Some(upstream_ref) = repo.branch_upstream_name(local_ref).ok()
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+    else {
+        return status;
+    };
+    status.upstream = Some(
+        upstream_ref.strip_prefix("refs/remotes/")
+            .or_else(|| upstream_ref.strip_prefix("refs/heads/"))
+            .unwrap_or(&upstream_ref)
+            .to_string(),
+    );
+
+    // 配置了上游但远程跟踪分支已不存在（通常是远程分支被删除并 prune）
+    let (Ok(local_id), Ok(upstream_id)) = (repo.refname_to_id(local_ref), repo.refname_to_id(&upstream_ref)) else {
+        status.status = "gone".to_string();
+        return status;
+    };
+    status.status = "tracking".to_string();
+    if let Ok((ahead, behind)) = repo.graph_ahead_behind(local_id, upstream_id) {
+        status.ahead = ahead;
+        status.behind = behind;
+    }
+    status
+}
+
+fn find_local_branch<'r>(repo: &'r Repository, name: &str) -> Result<git2::Branch<'r>, String> {
+    let name = name.trim_start_matches("refs/heads/");
+    repo.find_branch(name, BranchType::Local)
+        .map_err(|e| format!("无法找到分支 {}: {}", name, e))
+}
+
+fn branch_info(repo: &Repository, name: &str) -> Result<BranchInfo, String> {
+    let branch = find_local_branch(repo, name)?;
+    let reference = branch.get();
+    let full_name = reference.name().unwrap_or("").to_string();
+
+    Ok(BranchInfo {
+        name: reference.shorthand().unwrap_or(name).to_string(),
+        upstream: Some(upstream_status(repo, &full_name)),
+        full_name,
+        is_remote: false,
+        is_head: branch.is_head(),
+        commit_id: reference.target().map(|id| id.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// 已推送 main 的克隆，另有本地分支 feat 领先 main 一个提交
+    fn clone_with_feature() -> (TempRepo, TempRepo) {
+        let remote = TempRepo::bare("branch-remote");
+        let repo = TempRepo::clone_of(&remote, "branch-local");
+        repo.write("a.txt", "a\n");
+        repo.commit("initial");
+        repo.git(&["push", "-q", "-u", "origin", "main"]);
+        repo.git(&["checkout", "-q", "-b", "feat"]);
+        repo.write("b.txt", "b\n");
+        repo.commit("feature");
+        (remote, repo)
+    }
+
+    #[test]
+    fn sets_and_unsets_upstream() {
+        let (_remote, repo) = clone_with_feature();
+
+        let info = set_upstream(repo.path(), "feat", "origin/main").unwrap();
+        let upstream = info.upstream.unwrap();
+        assert_eq!(upstream.status, "tracking");
+        assert_eq!(upstream.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((upstream.ahead, upstream.behind), (1, 0));
+        assert_eq!(repo.git(&["rev-parse", "--abbrev-ref", "feat@{upstream}"]), "origin/main");
+
+        // 上游也可以是本地分支，并接受完整引用名
+        let info = set_upstream(repo.path(), "refs/heads/feat", "refs/heads/main").unwrap();
+        assert_eq!(info.upstream.unwrap().upstream.as_deref(), Some("main"));
+
+        let info = unset_upstream(repo.path(), "feat").unwrap();
+        assert_eq!(info.upstream.unwrap().status, "none");
+        // 未配置上游时再次取消不报错
+        unset_upstream(repo.path(), "feat").unwrap();
+
+        assert!(set_upstream(repo.path(), "feat", "origin/missing").is_err());
+        assert!(set_upstream(repo.path(), "feat", " ").is_err());
+        assert!(set_upstream(repo.path(), "missing", "origin/main").is_err());
+    }
+
+    #[test]
+    fn reports_gone_upstream() {
+        let (_remote, repo) = clone_with_feature();
+        repo.git(&["push", "-q", "-u", "origin", "feat"]);
+        repo.git(&["push", "-q", "origin", ":feat"]);
+
+        let branches = list_branches(repo.path()).unwrap();
+        let feat = branches.iter().find(|b| b.name == "feat").unwrap();
+        assert!(feat.is_head);
+        let upstream = feat.upstream.as_ref().unwrap();
+        assert_eq!(upstream.status, "gone");
+        assert_eq!(upstream.upstream.as_deref(), Some("origin/feat"));
+
+        // 已删除的上游也能取消
+        let info = unset_upstream(repo.path(), "feat").unwrap();
+        assert_eq!(info.upstream.unwrap().status, "none");
+    }
+
+    #[test]
+    fn lists_local_branches_before_remote() {
+        let (_remote, repo) = clone_with_feature();
+        repo.git(&["fetch", "-q"]);
+
+        let names: Vec<_> = list_branches(repo.path()).unwrap()
+            .into_iter()
+            .map(|b| (b.name, b.is_remote, b.upstream.is_some()))
+            .collect();
+        assert_eq!(names, vec![
+            ("feat".to_string(), false, true),
+            ("main".to_string(), false, true),
+            ("origin/main".to_string(), true, false),
+        ]);
+    }
+}
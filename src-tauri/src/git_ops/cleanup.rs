@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, HashSet};
+
+use git2::{BranchType, ErrorCode, Oid, Repository};
+
+use super::branch::upstream_status;
+use super::{open, signature};
+use super::sync::{remote_tracking_refs, RemoteHandlers};
+use crate::models::*;
+
+/// 恢复日志所在的引用，每删除一个分支在其引用日志中追加一条记录
+///
+/// 记录在 `gc.reflogExpireUnreachable`（默认 30 天）内使已删除分支的提交不会被回收。
+const RECOVERY_REF: &str = "refs/prism/deleted-branches";
+const DELETED_MESSAGE: &str = "branch cleanup: deleted ";
+
+/// 查找可以清理的分支：已完全合并到 `base` 的本地分支、上游已删除（gone）的本地分支，
+/// 以及 `check_remotes` 为 true 时远程上已不存在的远程跟踪分支
+///
+/// 当前分支和 `base` 本身不会列出。无法连接的远程记录在 `skipped_remotes` 中，不影响其它结果。
+pub fn scan(
+    path: &str,
+    base: &str,
+    check_remotes: bool,
+    handlers: &RemoteHandlers,
+) -> Result<CleanupScan, RemoteError> {
+    let repo = open(path)?;
+    let base_id = repo.revparse_single(base)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("无法解析版本 {}: {}", base, e))?
+        .id();
+    let base_ref = repo.resolve_reference_from_short_name(base).ok()
+        .and_then(|reference| reference.name().map(|s| s.to_string()));
+
+    let mut reasons: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let branches = repo.branches(Some(BranchType::Local))
+        .map_err(|e| format!("读取分支失败: {}", e))?;
+    for item in branches {
+        let (branch, _) = item.map_err(|e| format!("读取分支失败: {}", e))?;
+        let reference = branch.get();
+        let (Some(name), Some(id)) = (reference.name(), reference.target()) else { continue };
+        if branch.is_head() || base_ref.as_deref() == Some(name) {
+            continue;
+        }
+
+        let mut found = Vec::new();
+        if id == base_id || repo.graph_descendant_of(base_id, id).unwrap_or(false) {
+            found.push("merged".to_string());
+        }
+        if upstream_status(&repo, name).status == "gone" {
+            found.push("gone".to_string());
+        }
+        if !found.is_empty() {
+            reasons.insert(name.to_string(), found);
+        }
+    }
+
+    let mut skipped_remotes = Vec::new();
+    if check_remotes {
+        let remotes = repo.remotes()
+            .map_err(|e| format!("读取远程仓库失败: {}", e))?;
+        for remote in remotes.iter().flatten() {
+            match remote_tracking_refs(&repo, remote, handlers) {
+                Ok(existing) => {
+                    for name in stale_tracking_refs(&repo, remote, &existing)? {
+                        reasons.insert(name, vec!["stale_remote".to_string()]);
+                    }
+                }
+                Err(_) => skipped_remotes.push(remote.to_string()),
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (full_name, reasons) in reasons {
+        let commit = repo.find_reference(&full_name)
+            .and_then(|reference| reference.peel_to_commit())
+            .map_err(|e| format!("读取分支 {} 失败: {}", full_name, e))?;
+        let is_remote = full_name.starts_with("refs/remotes/");
+        let name = full_name.strip_prefix("refs/heads/")
+            .or_else(|| full_name.strip_prefix("refs/remotes/"))
+            .unwrap_or(&full_name)
+            .to_string();
+
+        candidates.push(CleanupCandidate {
+            name,
+            full_name: full_name.clone(),
+            is_remote,
+            reasons,
+            commit_id: commit.id().to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            author_name: commit.author().name().unwrap_or("").to_string(),
+            timestamp: commit.time().seconds(),
+        });
+    }
+    candidates.sort_by(|a, b| a.is_remote.cmp(&b.is_remote).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(CleanupScan {
+        base: base.to_string(),
+        candidates,
+        skipped_remotes,
+    })
+}
+
+/// 批量删除本地分支或远程跟踪分支（完整引用名），删除前把分支指向的提交记入恢复日志
+///
+/// 单个分支删除失败不影响其它分支，失败原因在 `failures` 中返回。
+pub fn delete_branches(path: &str, names: &[String]) -> Result<CleanupResult, String> {
+    let repo = open(path)?;
+
+    let mut deleted = Vec::new();
+    let mut failures = Vec::new();
+    for name in names {
+        match delete_branch(&repo, name) {
+            Ok(()) => deleted.push(name.clone()),
+            Err(message) => failures.push(CleanupFailure {
+                full_name: name.clone(),
+                message,
+            }),
+        }
+    }
+
+    Ok(CleanupResult { deleted, failures })
+}
+
+/// 读取恢复日志，最近删除的在前
+pub fn list_deleted(path: &str) -> Result<Vec<DeletedBranch>, String> {
+    let repo = open(path)?;
+    let reflog = match repo.reflog(RECOVERY_REF) {
+        Ok(reflog) => reflog,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("读取恢复日志失败: {}", e)),
+    };
+
+    let entries = reflog.iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let full_name = entry.message()?.strip_prefix(DELETED_MESSAGE)?.to_string();
+            Some(DeletedBranch {
+                index,
+                full_name,
+                commit_id: entry.id_new().to_string(),
+                timestamp: entry.committer().when().seconds(),
+            })
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// 按恢复日志中第 `index` 条记录重新创建分支，`name` 为空时使用原来的完整引用名
+pub fn restore_deleted(path: &str, index: usize, name: Option<&str>) -> Result<String, String> {
+    let repo = open(path)?;
+    let entry = list_deleted(path)?
+        .into_iter()
+        .find(|entry| entry.index == index)
+        .ok_or_else(|| format!("恢复日志中没有第 {} 条记录", index))?;
+    let full_name = match name.map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(name) if name.starts_with("refs/") => name.to_string(),
+        Some(name) => format!("refs/heads/{}", name),
+        None => entry.full_name,
+    };
+    let id = Oid::from_str(&entry.commit_id)
+        .map_err(|e| format!("无效的提交 {}: {}", entry.commit_id, e))?;
+
+    repo.reference(&full_name, id, false, "branch cleanup: restored")
+        .map_err(|e| match e.code() {
+            ErrorCode::Exists => format!("分支 {} 已存在", full_name),
+            _ => format!("恢复分支 {} 失败: {}", full_name, e),
+        })?;
+
+    Ok(full_name)
+}
+
+fn delete_branch(repo: &Repository, full_name: &str) -> Result<(), String> {
+    let (short, kind) = if let Some(short) = full_name.strip_prefix("refs/heads/") {
+        (short, BranchType::Local)
+    } else if let Some(short) = full_name.strip_prefix("refs/remotes/") {
+        (short, BranchType::Remote)
+    } else {
+        return Err(format!("{} 不是分支", full_name));
+    };
+
+    let mut branch = repo.find_branch(short, kind)
+        .map_err(|e| format!("无法找到分支 {}: {}", short, e))?;
+    if branch.is_head() {
+        return Err(format!("不能删除当前所在的分支 {}", short));
+    }
+    let id = branch.get().target()
+        .ok_or_else(|| format!("分支 {} 没有指向提交", short))?;
+
+    record_deleted(repo, full_name, id)?;
+    branch.delete()
+        .map_err(|e| format!("删除分支 {} 失败: {}", short, e))
+}
+
+/// 将分支指向的提交记入恢复日志
+///
+/// 引用目标不变或未开启引用日志时 libgit2 不会写入记录，此时手动追加。
+fn record_deleted(repo: &Repository, full_name: &str, id: Oid) -> Result<(), String> {
+    let message = format!("{}{}", DELETED_MESSAGE, full_name);
+    let logged = repo.reflog(RECOVERY_REF).map(|reflog| reflog.len()).unwrap_or(0);
+    repo.reference(RECOVERY_REF, id, true, &message)
+        .map_err(|e| format!("写入恢复日志失败: {}", e))?;
+
+    let mut reflog = repo.reflog(RECOVERY_REF)
+        .map_err(|e| format!("写入恢复日志失败: {}", e))?;
+    if reflog.len() == logged {
+        let signature = signature(repo)?;
+        reflog.append(id, &signature, Some(&message))
+            .and_then(|_| reflog.write())
+            .map_err(|e| format!("写入恢复日志失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 指定远程下已不在 `existing` 中的远程跟踪分支（不含 `refs/remotes/<remote>/HEAD`）
+fn stale_tracking_refs(repo: &Repository, remote: &str, existing: &[String]) -> Result<Vec<String>, String> {
+    let existing: HashSet<&str> = existing.iter().map(|s| s.as_str()).collect();
+    let prefix = format!("refs/remotes/{}/", remote);
+    let references = repo.references_glob(&format!("{}*", prefix))
+        .map_err(|e| format!("读取远程跟踪分支失败: {}", e))?;
+
+    let mut stale = Vec::new();
+    for reference in references.flatten() {
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        if let Some(name) = reference.name() {
+            if !existing.contains(name) {
+                stale.push(name.to_string());
+            }
+        }
+    }
+    Ok(stale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    fn quiet(_: RemoteProgress) {}
+
+    fn no_credentials(_: CredentialPrompt) -> Option<CredentialAnswer> {
+        None
+    }
+
+    fn handlers() -> RemoteHandlers<'static> {
+        RemoteHandlers { progress: &quiet, prompt: &no_credentials, cancel: None }
+    }
+
+    /// 构造已合并（merged）、上游已删除（gone）、远程已删除（stale）和仍在开发（wip）的分支
+    fn repo_with_candidates() -> (TempRepo, TempRepo) {
+        let remote = TempRepo::bare("cleanup-remote");
+        let repo = TempRepo::clone_of(&remote, "cleanup-local");
+        repo.write("a.txt", "a\n");
+        repo.commit("initial");
+        repo.git(&["branch", "merged"]);
+        repo.git(&["checkout", "-q", "-b", "wip"]);
+        repo.write("w.txt", "w\n");
+        repo.commit("wip work");
+        repo.git(&["checkout", "-q", "-b", "gone"]);
+        repo.write("g.txt", "g\n");
+        repo.commit("gone work");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.git(&["push", "-q", "-u", "origin", "main", "wip", "gone", "main:stale"]);
+
+        // 远程删除 gone 和 stale，本地只清除 gone 的远程跟踪分支
+        remote.git(&["branch", "-D", "gone", "stale"]);
+        repo.git(&["update-ref", "-d", "refs/remotes/origin/gone"]);
+        (remote, repo)
+    }
+
+    #[test]
+    fn scan_finds_merged_gone_and_stale_branches() {
+        let (_remote, repo) = repo_with_candidates();
+
+        let result = scan(repo.path(), "main", true, &handlers()).unwrap();
+        let found: Vec<_> = result.candidates.iter()
+            .map(|c| (c.full_name.as_str(), c.reasons.join(",")))
+            .collect();
+        assert_eq!(found, vec![
+            ("refs/heads/gone", "gone".to_string()),
+            ("refs/heads/merged", "merged".to_string()),
+            ("refs/remotes/origin/stale", "stale_remote".to_string()),
+        ]);
+        assert!(result.skipped_remotes.is_empty());
+        assert_eq!(result.candidates[0].summary, "gone work");
+
+        let local_only = scan(repo.path(), "main", false, &handlers()).unwrap();
+        assert_eq!(local_only.candidates.len(), 2);
+    }
+
+    #[test]
+    fn scan_skips_unreachable_remotes() {
+        let (_remote, repo) = repo_with_candidates();
+        repo.git(&["remote", "add", "broken", "file:///nonexistent/prism-cleanup.git"]);
+
+        let result = scan(repo.path(), "main", true, &handlers()).unwrap();
+        assert_eq!(result.skipped_remotes, vec!["broken".to_string()]);
+        assert_eq!(result.candidates.len(), 3);
+    }
+
+    #[test]
+    fn deleted_branches_can_be_restored() {
+        let (_remote, repo) = repo_with_candidates();
+        let gone_id = repo.rev_parse("gone");
+
+        let names = vec![
+            "refs/heads/gone".to_string(),
+            "refs/remotes/origin/stale".to_string(),
+            "refs/heads/main".to_string(),
+            "refs/tags/v1".to_string(),
+        ];
+        let result = delete_branches(repo.path(), &names).unwrap();
+        assert_eq!(result.deleted, names[..2].to_vec());
+        let failed: Vec<_> = result.failures.iter().map(|f| f.full_name.as_str()).collect();
+        assert_eq!(failed, vec!["refs/heads/main", "refs/tags/v1"]);
+        assert!(!repo.try_git(&["rev-parse", "--verify", "-q", "gone"]));
+        // 删除本地分支时一并删除其上游配置
+        assert!(!repo.try_git(&["config", "--get", "branch.gone.merge"]));
+
+        let deleted = list_deleted(repo.path()).unwrap();
+        let names: Vec<_> = deleted.iter().map(|d| d.full_name.as_str()).collect();
+        assert_eq!(names, vec!["refs/remotes/origin/stale", "refs/heads/gone"]);
+        let gone = &deleted[1];
+        assert_eq!(gone.commit_id, gone_id);
+
+        assert_eq!(restore_deleted(repo.path(), gone.index, None).unwrap(), "refs/heads/gone");
+        assert_eq!(repo.rev_parse("gone"), gone_id);
+        assert!(restore_deleted(repo.path(), gone.index, None).is_err());
+        assert_eq!(restore_deleted(repo.path(), gone.index, Some("again")).unwrap(), "refs/heads/again");
+        assert!(restore_deleted(repo.path(), 99, None).is_err());
+
+        // 恢复日志使已删除分支的提交不会被 gc 回收
+        repo.git(&["branch", "-q", "-D", "gone", "again"]);
+        repo.git(&["gc", "-q", "--prune=now"]);
+        assert_eq!(repo.git(&["cat-file", "-t", &gone_id]), "commit");
+    }
+}
@@ -0,0 +1,259 @@
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{CheckoutNotificationType, Config, FetchOptions, Repository, SubmoduleUpdateOptions};
+
+use super::credentials::{parse_ssh_url, resolve_ssh_url, CredentialProvider, SshHost};
+use super::sync::{callbacks, due, RemoteHandlers};
+use crate::models::*;
+
+/// 克隆仓库到 `destination`，返回仓库路径
+///
+/// 目标目录必须不存在或为空；克隆失败或被取消时清理本次写入的内容。
+/// libgit2 的本地传输不支持浅克隆，来源为本地路径或 file:// 时指定 `depth` 会返回错误。
+pub fn clone_repository(
+    url: &str,
+    destination: &str,
+    options: &CloneOptions,
+    handlers: &RemoteHandlers,
+) -> Result<String, RemoteError> {
+    if options.depth.is_some_and(|depth| depth > 0) && is_local(url) {
+        return Err("本地路径或 file:// 地址不支持浅克隆，请去掉克隆深度".to_string().into());
+    }
+
+    let dest = Path::new(destination);
+    let existed = dest.exists();
+    if existed {
+        let empty = fs::read_dir(dest)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if !empty {
+            return Err(format!("目标目录 {} 已存在且不为空", destination).into());
+        }
+    }
+
+    match run_clone(url, dest, options, handlers) {
+        Ok(_) => {
+            let path = fs::canonicalize(dest).unwrap_or_else(|_| dest.to_path_buf());
+            Ok(path.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            if existed {
+                remove_contents(dest);
+            } else {
+                let _ = fs::remove_dir_all(dest);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn run_clone(
+    url: &str,
+    dest: &Path,
+    options: &CloneOptions,
+    handlers: &RemoteHandlers,
+) -> Result<Repository, RemoteError> {
+    let (connect_url, ssh_host) = resolve_ssh_url(url)
+        .unwrap_or_else(|| (url.to_string(), SshHost::default()));
+    let mut provider = CredentialProvider::new(handlers.prompt, Config::open_default().ok(), ssh_host);
+
+    let result = {
+        let mut fetch = FetchOptions::new();
+        fetch.remote_callbacks(callbacks(handlers, &mut provider));
+        if let Some(depth) = options.depth.filter(|depth| *depth > 0) {
+            fetch.depth(depth as i32);
+        }
+
+        let mut builder = RepoBuilder::new();
+        builder.bare(options.bare).fetch_options(fetch);
+        if let Some(branch) = options.branch.as_deref().filter(|b| !b.is_empty()) {
+            builder.branch(branch);
+        }
+        if !options.bare {
+            builder.with_checkout(checkout_progress(handlers));
+        }
+        builder.clone(&connect_url, dest)
+    };
+    let repo = result.map_err(|e| handlers.remote_error(&provider, e, "克隆失败"))?;
+    // 检出写入文件的阶段无法中止，期间收到的取消在完成后处理，由调用方清理目标目录
+    handlers.check_cancelled()?;
+
+    // 通过 ssh 主机别名解析后的地址连接时，仍在配置中保留用户输入的地址
+    if connect_url != url {
+        repo.remote_set_url("origin", url)
+            .map_err(|e| format!("设置远程仓库地址失败: {}", e))?;
+    }
+
+    if options.recursive && !options.bare {
+        update_submodules(&repo, handlers)?;
+    }
+
+    Ok(repo)
+}
+
+/// 初始化并检出全部子模块，包括嵌套的子模块（`git submodule update --init --recursive`）
+fn update_submodules(repo: &Repository, handlers: &RemoteHandlers) -> Result<(), RemoteError> {
+    let submodules = repo.submodules()
+        .map_err(|e| format!("读取子模块失败: {}", e))?;
+
+    for mut submodule in submodules {
+        let name = submodule.name().unwrap_or("").to_string();
+        let ssh_host = submodule.url()
+            .and_then(resolve_ssh_url)
+            .map(|(_, ssh_host)| ssh_host)
+            .unwrap_or_default();
+        let mut provider = CredentialProvider::new(handlers.prompt, Config::open_default().ok(), ssh_host);
+
+        let result = {
+            let mut fetch = FetchOptions::new();
+            fetch.remote_callbacks(callbacks(handlers, &mut provider));
+            let mut opts = SubmoduleUpdateOptions::new();
+            opts.fetch(fetch);
+            submodule.update(true, Some(&mut opts))
+        };
+        result.map_err(|e| {
+            handlers.remote_error(&provider, e, &format!("更新子模块 {} 失败", name))
+        })?;
+
+        let sub_repo = submodule.open()
+            .map_err(|e| format!("无法打开子模块 {}: {}", name, e))?;
+        update_submodules(&sub_repo, handlers)?;
+    }
+
+    Ok(())
+}
+
+/// 检出的进度回调，并在 libgit2 逐个检查文件时响应取消（notify 回调返回 false 即中止检出）
+fn checkout_progress<'a>(handlers: &'a RemoteHandlers) -> CheckoutBuilder<'a> {
+    let progress = handlers.progress;
+    let mut last: Option<Instant> = None;
+    let mut checkout = CheckoutBuilder::new();
+    checkout.progress(move |_, completed, total| {
+        if completed == total || due(&mut last) {
+            progress(RemoteProgress::Checkout { completed, total });
+        }
+    });
+    checkout.notify_on(CheckoutNotificationType::UPDATED | CheckoutNotificationType::CONFLICT);
+    checkout.notify(move |_, _, _, _, _| !handlers.is_cancelled());
+    checkout
+}
+
+fn is_local(url: &str) -> bool {
+    url.starts_with("file://") || (!url.contains("://") && parse_ssh_url(url).is_none())
+}
+
+fn remove_contents(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let _ = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    fn quiet(_: RemoteProgress) {}
+
+    fn no_credentials(_: CredentialPrompt) -> Option<CredentialAnswer> {
+        None
+    }
+
+    fn source() -> TempRepo {
+        let source = TempRepo::new("clone-source");
+        source.write("a.txt", "main\n");
+        source.commit("initial");
+        source.git(&["branch", "dev"]);
+        source.write("a.txt", "changed\n");
+        source.commit("second");
+        source
+    }
+
+    fn clone_into(url: &str, dest: &Path, options: &CloneOptions, handlers: &RemoteHandlers) -> Result<String, RemoteError> {
+        clone_repository(url, dest.to_str().unwrap(), options, handlers)
+    }
+
+    #[test]
+    fn clones_local_branch() {
+        let source = source();
+        let target = TempRepo::empty_dir("clone-target");
+        let dest = target.dir().join("work");
+        let handlers = RemoteHandlers { progress: &quiet, prompt: &no_credentials, cancel: None };
+        let options = CloneOptions { branch: Some("dev".to_string()), ..Default::default() };
+
+        clone_into(source.path(), &dest, &options, &handlers).unwrap();
+        let repo = Repository::open(&dest).unwrap();
+        assert_eq!(repo.head().unwrap().shorthand(), Some("dev"));
+        assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "main\n");
+    }
+
+    #[test]
+    fn clones_bare_repository() {
+        let source = source();
+        let target = TempRepo::empty_dir("clone-target");
+        let dest = target.dir().join("work.git");
+        let handlers = RemoteHandlers { progress: &quiet, prompt: &no_credentials, cancel: None };
+        let options = CloneOptions { bare: true, ..Default::default() };
+
+        clone_into(&source.url(), &dest, &options, &handlers).unwrap();
+        let repo = Repository::open(&dest).unwrap();
+        assert!(repo.is_bare());
+        assert!(!dest.join("a.txt").exists());
+        assert_eq!(repo.head().unwrap().target().unwrap().to_string(), source.rev_parse("HEAD"));
+    }
+
+    #[test]
+    fn rejects_depth_for_local_sources() {
+        let source = source();
+        let target = TempRepo::empty_dir("clone-target");
+        let dest = target.dir().join("work");
+        let handlers = RemoteHandlers { progress: &quiet, prompt: &no_credentials, cancel: None };
+        let options = CloneOptions { depth: Some(1), ..Default::default() };
+
+        assert!(clone_into(source.path(), &dest, &options, &handlers).is_err());
+        assert!(clone_into(&source.url(), &dest, &options, &handlers).is_err());
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn cancelled_transfer_removes_destination() {
+        let source = source();
+        let target = TempRepo::empty_dir("clone-target");
+        let dest = target.dir().join("work");
+        let cancel = AtomicBool::new(true);
+        let handlers = RemoteHandlers { progress: &quiet, prompt: &no_credentials, cancel: Some(&cancel) };
+
+        let error = clone_into(&source.url(), &dest, &CloneOptions::default(), &handlers).unwrap_err();
+        assert_eq!(error.kind, "cancelled");
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn cancelled_checkout_removes_destination() {
+        let source = source();
+        let target = TempRepo::empty_dir("clone-target");
+        let dest = target.dir().join("work");
+        let cancel = AtomicBool::new(false);
+        let progress = |progress: RemoteProgress| {
+            if let RemoteProgress::Checkout { .. } = progress {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        };
+        let handlers = RemoteHandlers { progress: &progress, prompt: &no_credentials, cancel: Some(&cancel) };
+
+        let error = clone_into(&source.url(), &dest, &CloneOptions::default(), &handlers).unwrap_err();
+        assert_eq!(error.kind, "cancelled");
+        assert!(!dest.exists());
+    }
+}
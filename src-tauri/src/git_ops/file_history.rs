@@ -0,0 +1,246 @@
+use std::path::Path;
+
+use git2::{Commit, Delta, DiffFindOptions, DiffOptions, Oid, Repository, Tree};
+
+use super::{commit_info, diff_hunks, open, ref_labels};
+use crate::models::*;
+
+/// 列出修改过 `file_path` 的提交（`git log --follow`），从 `rev`（默认 HEAD）开始按拓扑和时间顺序
+///
+/// 文件在某个提交中由其它路径重命名而来时，更早的提交改为追踪原来的路径。
+/// 合并提交只有在文件与所有父提交都不同时才列出。
+pub fn file_history(
+    path: &str,
+    file_path: &str,
+    rev: Option<&str>,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<FileHistoryEntry>, String> {
+    let repo = open(path)?;
+    let rev = rev.unwrap_or("HEAD");
+    let start = repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("无法解析版本 {}: {}", rev, e))?;
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("无法创建 revwalk: {}", e))?;
+    // 追踪的路径在重命名处改变，子提交必须先于父提交处理
+    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)
+        .map_err(|e| format!("设置排序失败: {}", e))?;
+    revwalk.push(start.id())
+        .map_err(|e| format!("无法推送起点: {}", e))?;
+
+    let labels = ref_labels(&repo);
+    let mut current = file_path.trim_matches('/').to_string();
+    let mut skipped = 0;
+    let mut entries = Vec::new();
+    for oid in revwalk {
+        if entries.len() >= limit {
+            break;
+        }
+        let oid = oid.map_err(|e| format!("遍历提交失败: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("无法读取提交 {}: {}", oid, e))?;
+        let Some((status, old_path)) = change_of(&repo, &commit, &current)? else { continue };
+
+        let entry_path = current.clone();
+        if let Some(old_path) = &old_path {
+            current = old_path.clone();
+        }
+        if skipped < offset {
+            skipped += 1;
+            continue;
+        }
+
+        let mut info = commit_info(&commit);
+        info.refs = labels.get(&oid).cloned().unwrap_or_default();
+        entries.push(FileHistoryEntry {
+            commit: info,
+            path: entry_path,
+            old_path,
+            status: status.to_string(),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// 提交中单个文件相对第一个父提交的 diff
+///
+/// `old_path` 传文件历史中返回的重命名前路径，diff 会按重命名比较两个路径的内容。
+pub fn commit_file_diff(
+    path: &str,
+    commit_id: &str,
+    file_path: &str,
+    old_path: Option<&str>,
+) -> Result<DiffResponse, String> {
+    let repo = open(path)?;
+    let commit = repo.revparse_single(commit_id)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("无法解析版本 {}: {}", commit_id, e))?;
+    let tree = commit.tree()
+        .map_err(|e| format!("无法获取树: {}", e))?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().map_err(|e| format!("无法获取树: {}", e))?),
+        Err(_) => None,
+    };
+
+    let mut diff_opts = DiffOptions::new();
+    diff_opts.pathspec(file_path);
+    if let Some(old_path) = old_path {
+        diff_opts.pathspec(old_path);
+    }
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))
+        .map_err(|e| format!("创建 diff 失败: {}", e))?;
+    if old_path.is_some() {
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+            .map_err(|e| format!("检测重命名失败: {}", e))?;
+    }
+
+    Ok(DiffResponse {
+        file_path: file_path.to_string(),
+        hunks: diff_hunks(&diff)?,
+    })
+}
+
+/// 提交对 `file_path` 的修改：状态和重命名前的路径，未修改时返回 None
+fn change_of(
+    repo: &Repository,
+    commit: &Commit,
+    file_path: &str,
+) -> Result<Option<(&'static str, Option<String>)>, String> {
+    let tree = commit.tree()
+        .map_err(|e| format!("无法获取树: {}", e))?;
+    let parent_trees = commit.parents()
+        .map(|parent| parent.tree())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("无法获取树: {}", e))?;
+
+    let new_id = entry_id(&tree, file_path);
+    let old_ids: Vec<Option<Oid>> = parent_trees.iter()
+        .map(|parent| entry_id(parent, file_path))
+        .collect();
+    // 与任一父提交相同说明文件的这个版本来自该父提交
+    if old_ids.contains(&new_id) {
+        return Ok(None);
+    }
+
+    let change = match (old_ids.first().copied().flatten(), new_id) {
+        (None, None) => None,
+        (Some(_), None) => Some(("deleted", None)),
+        (Some(_), Some(_)) => Some(("modified", None)),
+        (None, Some(_)) => match parent_trees.first() {
+            Some(parent) => match renamed_from(repo, parent, &tree, file_path)? {
+                Some(old_path) => Some(("renamed", Some(old_path))),
+                None => Some(("added", None)),
+            },
+            None => Some(("added", None)),
+        },
+    };
+    Ok(change)
+}
+
+/// 在两棵树之间检测重命名，返回重命名为 `file_path` 的原路径
+pub(super) fn renamed_from(repo: &Repository, old: &Tree, new: &Tree, file_path: &str) -> Result<Option<String>, String> {
+    let mut diff = repo.diff_tree_to_tree(Some(old), Some(new), None)
+        .map_err(|e| format!("创建 diff 失败: {}", e))?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .map_err(|e| format!("检测重命名失败: {}", e))?;
+
+    let old_path = diff.deltas()
+        .find(|delta| delta.status() == Delta::Renamed && delta.new_file().path() == Some(Path::new(file_path)))
+        .and_then(|delta| delta.old_file().path().map(|p| p.to_string_lossy().to_string()));
+    Ok(old_path)
+}
+
+fn entry_id(tree: &Tree, file_path: &str) -> Option<Oid> {
+    tree.get_path(Path::new(file_path)).ok().map(|entry| entry.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// old.txt 修改后重命名为 dir/new.txt 再修改，中间夹一个不相关的提交
+    fn renamed_file() -> (TempRepo, String) {
+        let repo = TempRepo::new("file-history");
+        let body: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let edited = body.replace("line 3", "LINE 3");
+        repo.write("old.txt", &body);
+        repo.write("other.txt", "o\n");
+        repo.commit("add old");
+        repo.write("old.txt", &edited);
+        repo.commit("edit old");
+        repo.write("other.txt", "o2\n");
+        repo.commit("unrelated");
+        repo.write("dir/.keep", "");
+        repo.git(&["mv", "old.txt", "dir/new.txt"]);
+        repo.commit("rename");
+        repo.write("dir/new.txt", &edited.replace("line 9", "LINE 9"));
+        repo.commit("edit new");
+        (repo, edited)
+    }
+
+    fn summary(entries: &[FileHistoryEntry]) -> Vec<(&str, &str, &str, Option<&str>)> {
+        entries.iter()
+            .map(|e| (e.commit.summary.as_str(), e.path.as_str(), e.status.as_str(), e.old_path.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn follows_renames() {
+        let (repo, _) = renamed_file();
+
+        let history = file_history(repo.path(), "dir/new.txt", None, 100, 0).unwrap();
+        assert_eq!(summary(&history), vec![
+            ("edit new", "dir/new.txt", "modified", None),
+            ("rename", "dir/new.txt", "renamed", Some("old.txt")),
+            ("edit old", "old.txt", "modified", None),
+            ("add old", "old.txt", "added", None),
+        ]);
+
+        // 分页跳过的条目同样要跟随重命名
+        let page = file_history(repo.path(), "/dir/new.txt", None, 2, 2).unwrap();
+        assert_eq!(summary(&page), vec![
+            ("edit old", "old.txt", "modified", None),
+            ("add old", "old.txt", "added", None),
+        ]);
+
+        let from_rename = file_history(repo.path(), "dir/new.txt", Some("HEAD~1"), 100, 0).unwrap();
+        assert_eq!(from_rename.len(), 3);
+        assert!(file_history(repo.path(), "dir/new.txt", Some("nope"), 100, 0).is_err());
+    }
+
+    #[test]
+    fn lists_deletion() {
+        let (repo, _) = renamed_file();
+        repo.git(&["rm", "-q", "dir/new.txt"]);
+        repo.commit("remove");
+
+        let history = file_history(repo.path(), "dir/new.txt", None, 100, 0).unwrap();
+        assert_eq!(history.len(), 5);
+        assert_eq!((history[0].commit.summary.as_str(), history[0].status.as_str()), ("remove", "deleted"));
+    }
+
+    #[test]
+    fn diffs_across_rename() {
+        let (repo, edited) = renamed_file();
+        let history = file_history(repo.path(), "dir/new.txt", None, 100, 0).unwrap();
+
+        // 纯重命名按新旧路径配对后没有内容差异
+        let rename = commit_file_diff(repo.path(), &history[1].commit.id, "dir/new.txt", Some("old.txt")).unwrap();
+        assert!(rename.hunks.is_empty());
+
+        let edit = commit_file_diff(repo.path(), &history[2].commit.id, "old.txt", None).unwrap();
+        assert_eq!(edit.hunks.len(), 1);
+        assert!(edit.hunks[0].lines.iter().any(|l| l.line_type == "add" && l.content == "LINE 3\n"));
+
+        let added = commit_file_diff(repo.path(), &history[3].commit.id, "old.txt", None).unwrap();
+        let added_lines: String = added.hunks[0].lines.iter()
+            .filter(|l| l.line_type == "add")
+            .map(|l| l.content.as_str())
+            .collect();
+        assert_eq!(added_lines, edited.replace("LINE 3", "line 3"));
+    }
+}
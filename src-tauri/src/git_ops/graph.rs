@@ -0,0 +1,226 @@
+use super::get_commit_history;
+use crate::models::*;
+
+/// 获取一页提交及其图布局
+///
+/// `state` 传入上一页返回的状态，第一页传 None；按顺序翻页时只需布局新的一页。
+pub fn get_commit_graph(
+    path: &str,
+    limit: usize,
+    offset: usize,
+    state: Option<GraphState>,
+) -> Result<CommitGraphPage, String> {
+    let commits = get_commit_history(path, limit, offset)?;
+    let mut state = state.unwrap_or_default();
+    let rows = layout_rows(&mut state, &commits);
+
+    Ok(CommitGraphPage {
+        commits,
+        rows,
+        state,
+    })
+}
+
+/// 按顺序为一批提交布局，`state` 更新为最后一行之后的状态
+pub fn layout_rows(state: &mut GraphState, commits: &[CommitInfo]) -> Vec<GraphRow> {
+    commits.iter()
+        .map(|commit| state.layout(&commit.id, &commit.parent_ids))
+        .collect()
+}
+
+impl GraphState {
+    /// 为下一个提交分配列并生成本行的线段
+    ///
+    /// 提交放在第一条等待它的列上，没有等待它的列时（分支顶端）占用最左侧的空列。
+    /// 第一个父提交沿用提交所在的列和颜色，其余父提交优先汇入已在等待它的列，否则新开一列。
+    pub fn layout(&mut self, id: &str, parent_ids: &[String]) -> GraphRow {
+        let waiting: Vec<usize> = self.lanes.iter()
+            .enumerate()
+            .filter(|(_, lane)| lane.as_ref().is_some_and(|lane| lane.commit_id == id))
+            .map(|(column, _)| column)
+            .collect();
+        let (column, color) = match waiting.first() {
+            Some(&column) => (column, self.lanes[column].as_ref().map_or(0, |lane| lane.color)),
+            None => (self.free_column(), self.new_color()),
+        };
+
+        let mut edges = Vec::new();
+        for (from, lane) in self.lanes.iter().enumerate() {
+            let Some(lane) = lane else { continue };
+            let (kind, to) = match lane.commit_id == id {
+                true if from == column => ("in", column),
+                true => ("fork", column),
+                false => ("pass", from),
+            };
+            edges.push(edge(kind, from, to, lane.color));
+        }
+        for &column in &waiting {
+            self.lanes[column] = None;
+        }
+
+        if let Some(first) = parent_ids.first() {
+            self.occupy(column, first, color);
+            edges.push(edge("out", column, column, color));
+        }
+        for parent in parent_ids.iter().skip(1) {
+            let existing = self.lanes.iter()
+                .position(|lane| lane.as_ref().is_some_and(|lane| lane.commit_id == *parent));
+            let (to, lane_color) = match existing {
+                Some(to) => (to, self.lanes[to].as_ref().map_or(0, |lane| lane.color)),
+                None => {
+                    let to = self.free_column();
+                    let lane_color = self.new_color();
+                    self.occupy(to, parent, lane_color);
+                    (to, lane_color)
+                }
+            };
+            edges.push(edge("merge", column, to, lane_color));
+        }
+
+        let width = edges.iter()
+            .map(|edge| edge.from.max(edge.to) + 1)
+            .chain(std::iter::once(column + 1))
+            .max()
+            .unwrap_or(1);
+        while matches!(self.lanes.last(), Some(None)) {
+            self.lanes.pop();
+        }
+
+        GraphRow {
+            id: id.to_string(),
+            column,
+            color,
+            width,
+            edges,
+        }
+    }
+
+    fn free_column(&self) -> usize {
+        self.lanes.iter()
+            .position(|lane| lane.is_none())
+            .unwrap_or(self.lanes.len())
+    }
+
+    fn new_color(&mut self) -> usize {
+        let color = self.next_color;
+        self.next_color += 1;
+        color
+    }
+
+    fn occupy(&mut self, column: usize, commit_id: &str, color: usize) {
+        if column >= self.lanes.len() {
+            self.lanes.resize(column + 1, None);
+        }
+        self.lanes[column] = Some(GraphLane {
+            commit_id: commit_id.to_string(),
+            color,
+        });
+    }
+}
+
+fn edge(kind: &str, from: usize, to: usize, color: usize) -> GraphEdge {
+    GraphEdge {
+        kind: kind.to_string(),
+        from,
+        to,
+        color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(id: &str, parents: &[&str]) -> CommitInfo {
+        CommitInfo {
+            id: id.to_string(),
+            parent_ids: parents.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn kinds(row: &GraphRow) -> Vec<(&str, usize, usize)> {
+        row.edges.iter().map(|e| (e.kind.as_str(), e.from, e.to)).collect()
+    }
+
+    #[test]
+    fn lays_out_merge_and_fork() {
+        let commits = vec![
+            commit("m", &["a", "b"]),
+            commit("b", &["c"]),
+            commit("a", &["c"]),
+            commit("c", &[]),
+        ];
+        let mut state = GraphState::default();
+        let rows = layout_rows(&mut state, &commits);
+
+        assert_eq!(kinds(&rows[0]), vec![("out", 0, 0), ("merge", 0, 1)]);
+        assert_eq!((rows[1].column, rows[1].color), (1, 1));
+        assert_eq!(kinds(&rows[1]), vec![("pass", 0, 0), ("in", 1, 1), ("out", 1, 1)]);
+        assert_eq!(kinds(&rows[2]), vec![("in", 0, 0), ("pass", 1, 1), ("out", 0, 0)]);
+        assert_eq!(kinds(&rows[3]), vec![("in", 0, 0), ("fork", 1, 0)]);
+        assert_eq!(rows[3].width, 2);
+        assert!(state.lanes.is_empty());
+    }
+
+    #[test]
+    fn octopus_merge_opens_a_lane_per_parent() {
+        let commits = vec![
+            commit("m", &["a", "b", "c"]),
+            commit("c", &["r"]),
+        ];
+        let mut state = GraphState::default();
+        let rows = layout_rows(&mut state, &commits);
+
+        assert_eq!(kinds(&rows[0]), vec![("out", 0, 0), ("merge", 0, 1), ("merge", 0, 2)]);
+        assert_eq!(rows[0].edges.iter().map(|e| e.color).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(rows[0].width, 3);
+        assert_eq!((rows[1].column, rows[1].color), (2, 2));
+        assert_eq!(kinds(&rows[1]), vec![("pass", 0, 0), ("pass", 1, 1), ("in", 2, 2), ("out", 2, 2)]);
+        let lanes: Vec<_> = state.lanes.iter()
+            .map(|lane| lane.as_ref().map(|lane| lane.commit_id.as_str()))
+            .collect();
+        assert_eq!(lanes, vec![Some("a"), Some("b"), Some("r")]);
+    }
+
+    #[test]
+    fn merge_joins_lane_already_waiting_for_parent() {
+        let commits = vec![
+            commit("x", &["b"]),
+            commit("m", &["a", "b"]),
+            commit("b", &[]),
+        ];
+        let mut state = GraphState::default();
+        let rows = layout_rows(&mut state, &commits);
+
+        // 第二个父提交已有列在等待，合并线汇入该列并沿用其颜色，不再新开一列
+        assert_eq!((rows[1].column, rows[1].color), (1, 1));
+        assert_eq!(kinds(&rows[1]), vec![("pass", 0, 0), ("out", 1, 1), ("merge", 1, 0)]);
+        assert_eq!(rows[1].edges[2].color, 0);
+        assert_eq!(rows[1].width, 2);
+        assert_eq!(kinds(&rows[2]), vec![("in", 0, 0), ("pass", 1, 1)]);
+        assert_eq!(state.lanes.len(), 2);
+    }
+
+    #[test]
+    fn layout_continues_across_pages() {
+        let commits = vec![
+            commit("d", &["b"]),
+            commit("c", &["a"]),
+            commit("b", &["a"]),
+            commit("a", &[]),
+        ];
+        let mut whole = GraphState::default();
+        let expected = layout_rows(&mut whole, &commits);
+
+        let mut state = GraphState::default();
+        let mut rows = layout_rows(&mut state, &commits[..1]);
+        // 状态经过前端往返后继续布局
+        let mut state: GraphState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        rows.extend(layout_rows(&mut state, &commits[1..]));
+
+        assert_eq!(rows, expected);
+        assert_eq!(rows[1].column, 1);
+        assert_eq!(kinds(&rows[3]), vec![("in", 0, 0), ("fork", 1, 0)]);
+    }
+}
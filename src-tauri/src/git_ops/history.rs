@@ -0,0 +1,808 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use git2::{Commit, Oid, Repository, Revwalk, Signature};
+use regex::{Regex, RegexBuilder};
+
+use super::{commit_info, open, ref_labels};
+use crate::models::*;
+
+/// 后台遍历积攒到这么多匹配的提交，或距上次发布超过该时间时发布给等待的翻页请求
+const PUBLISH_BATCH: usize = 256;
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(20);
+
+pub struct WalkPage {
+    pub commits: Vec<CommitInfo>,
+    pub rows: Vec<GraphRow>,
+    pub next: Option<usize>,
+    pub total: usize,
+    pub complete: bool,
+}
+
+/// 常驻的提交遍历会话
+///
+/// 遍历和过滤在后台线程中进行，匹配的提交 id 不断追加到共享的进度中；翻页时只等到本页的提交
+/// 已找到即可返回，不必等整个历史遍历完。同时保存每个已返回游标处的提交图布局状态，
+/// 翻回之前的页也能继续布局。
+pub struct HistoryWalk {
+    repo: Repository,
+    shared: Arc<Shared>,
+    labels: HashMap<Oid, Vec<RefLabel>>,
+    parents: ParentRewriter,
+    graph_states: BTreeMap<usize, GraphState>,
+}
+
+#[derive(Default)]
+struct Progress {
+    oids: Vec<Oid>,
+    done: bool,
+    error: Option<String>,
+}
+
+/// 会话与后台遍历线程共享的状态
+#[derive(Default)]
+struct Shared {
+    progress: Mutex<Progress>,
+    changed: Condvar,
+    cancelled: AtomicBool,
+}
+
+impl Shared {
+    fn publish(&self, batch: &mut Vec<Oid>, done: bool, error: Option<String>) {
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.oids.append(batch);
+            progress.done = done;
+            progress.error = error;
+        }
+        self.changed.notify_all();
+    }
+}
+
+impl HistoryWalk {
+    /// 从 `options` 指定的起点按拓扑和时间顺序遍历，只保留满足过滤条件的提交
+    ///
+    /// 起点或过滤条件无效时直接返回错误，遍历本身在后台线程中继续。
+    pub fn start(path: &str, options: &HistoryOptions) -> Result<Self, String> {
+        let repo = open(path)?;
+        let labels = ref_labels(&repo);
+        let filter = HistoryFilter::new(options, &labels)?;
+        let shared = Arc::new(Shared::default());
+
+        let (ready_tx, ready_rx) = mpsc::channel();
+        {
+            let path = path.to_string();
+            let options = options.clone();
+            let filter = filter.clone();
+            let shared = shared.clone();
+            thread::spawn(move || walk_in_background(&path, &options, &filter, &shared, ready_tx));
+        }
+        ready_rx.recv()
+            .map_err(|_| "遍历线程意外退出".to_string())??;
+
+        let mut graph_states = BTreeMap::new();
+        graph_states.insert(0, GraphState::default());
+        Ok(HistoryWalk {
+            repo,
+            shared,
+            labels,
+            parents: ParentRewriter::new(filter, options.first_parent),
+            graph_states,
+        })
+    }
+
+    /// 读取从 `offset` 开始的一页提交及其图布局，返回下一页的起始位置，已到末尾时为 None
+    ///
+    /// `offset` 必须是之前返回过的位置（或 0）。后台遍历尚未找到足够的提交时会等待。
+    pub fn page(&mut self, offset: usize, limit: usize) -> Result<WalkPage, String> {
+        let mut state = self.graph_states.get(&offset)
+            .cloned()
+            .ok_or_else(|| format!("游标位置 {} 无效", offset))?;
+
+        let wanted = offset.saturating_add(limit);
+        let (oids, total, complete) = {
+            let mut progress = self.shared.progress.lock().map_err(|e| e.to_string())?;
+            while !progress.done && progress.oids.len() < wanted {
+                progress = self.shared.changed.wait(progress).map_err(|e| e.to_string())?;
+            }
+            if let Some(error) = &progress.error {
+                return Err(error.clone());
+            }
+            let end = wanted.min(progress.oids.len());
+            (progress.oids[offset.min(end)..end].to_vec(), progress.oids.len(), progress.done)
+        };
+
+        let mut commits = Vec::with_capacity(oids.len());
+        let mut rows = Vec::with_capacity(oids.len());
+        for oid in &oids {
+            let commit = self.repo.find_commit(*oid)
+                .map_err(|e| format!("无法读取提交 {}: {}", oid, e))?;
+            let parent_ids: Vec<String> = self.parents.rewrite(&self.repo, &commit)?
+                .iter()
+                .map(|id| id.to_string())
+                .collect();
+            let mut info = commit_info(&commit);
+            info.refs = self.labels.get(oid).cloned().unwrap_or_default();
+            rows.push(state.layout(&info.id, &parent_ids));
+            commits.push(info);
+        }
+
+        let end = offset + oids.len();
+        let next = (end < total || !complete).then_some(end);
+        if let Some(next) = next {
+            self.graph_states.insert(next, state);
+        }
+        Ok(WalkPage { commits, rows, next, total, complete })
+    }
+
+    /// 提交在本次遍历结果中的位置，遍历未完成时等待到找到或遍历结束
+    pub fn position(&self, id: Oid) -> Result<Option<usize>, String> {
+        let mut searched = 0;
+        let mut progress = self.shared.progress.lock().map_err(|e| e.to_string())?;
+        loop {
+            if let Some(index) = progress.oids[searched..].iter().position(|oid| *oid == id) {
+                return Ok(Some(searched + index));
+            }
+            if let Some(error) = &progress.error {
+                return Err(error.clone());
+            }
+            if progress.done {
+                return Ok(None);
+            }
+            searched = progress.oids.len();
+            progress = self.shared.changed.wait(progress).map_err(|e| e.to_string())?;
+        }
+    }
+}
+
+impl Drop for HistoryWalk {
+    fn drop(&mut self) {
+        self.shared.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 后台遍历：先通过 `ready` 报告起点是否有效，再逐个过滤提交并分批发布
+fn walk_in_background(
+    path: &str,
+    options: &HistoryOptions,
+    filter: &HistoryFilter,
+    shared: &Shared,
+    ready: mpsc::Sender<Result<(), String>>,
+) {
+    let repo = match open(path) {
+        Ok(repo) => repo,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let revwalk = match new_revwalk(&repo, options) {
+        Ok(revwalk) => revwalk,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+
+    let mut batch = Vec::new();
+    let mut published = Instant::now();
+    let mut error = None;
+    for item in revwalk {
+        if shared.cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+        match item.and_then(|oid| repo.find_commit(oid)) {
+            Ok(commit) if filter.matches(&commit) => batch.push(commit.id()),
+            Ok(_) => {}
+            Err(e) => {
+                error = Some(format!("遍历提交失败: {}", e));
+                break;
+            }
+        }
+        if !batch.is_empty() && (batch.len() >= PUBLISH_BATCH || published.elapsed() >= PUBLISH_INTERVAL) {
+            shared.publish(&mut batch, false, None);
+            published = Instant::now();
+        }
+    }
+    shared.publish(&mut batch, true, error);
+}
+
+fn new_revwalk<'r>(repo: &'r Repository, options: &HistoryOptions) -> Result<Revwalk<'r>, String> {
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("无法创建 revwalk: {}", e))?;
+    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)
+        .map_err(|e| format!("设置排序失败: {}", e))?;
+    if options.first_parent {
+        revwalk.simplify_first_parent()
+            .map_err(|e| format!("设置遍历方式失败: {}", e))?;
+    }
+    push_starts(repo, &mut revwalk, options)?;
+    Ok(revwalk)
+}
+
+/// 编译后的过滤条件，时间比较使用提交者时间（与 `git log --since/--until` 一致）
+#[derive(Clone)]
+struct HistoryFilter {
+    author: Option<Regex>,
+    committer: Option<Regex>,
+    message: Option<Regex>,
+    since: Option<i64>,
+    until: Option<i64>,
+    merges: Option<MergeFilter>,
+    decorated: Option<HashSet<Oid>>,
+}
+
+impl HistoryFilter {
+    fn new(options: &HistoryOptions, labels: &HashMap<Oid, Vec<RefLabel>>) -> Result<Self, String> {
+        Ok(HistoryFilter {
+            author: pattern(options.author.as_deref(), "作者")?,
+            committer: pattern(options.committer.as_deref(), "提交者")?,
+            message: pattern(options.message.as_deref(), "提交说明")?,
+            since: options.since,
+            until: options.until,
+            merges: options.merges,
+            decorated: options.simplify_by_decoration.then(|| labels.keys().copied().collect()),
+        })
+    }
+
+    /// 是否有会排除提交的条件；没有时提交图不需要改写父提交
+    fn is_active(&self) -> bool {
+        self.author.is_some()
+            || self.committer.is_some()
+            || self.message.is_some()
+            || self.since.is_some()
+            || self.until.is_some()
+            || self.merges.is_some()
+            || self.decorated.is_some()
+    }
+
+    fn matches(&self, commit: &Commit) -> bool {
+        let time = commit.time().seconds();
+        if self.is_before_since(commit) || self.until.is_some_and(|until| time > until) {
+            return false;
+        }
+        let is_merge = commit.parent_count() > 1;
+        match self.merges {
+            Some(MergeFilter::NoMerges) if is_merge => return false,
+            Some(MergeFilter::MergesOnly) if !is_merge => return false,
+            _ => {}
+        }
+        if self.decorated.as_ref().is_some_and(|decorated| !decorated.contains(&commit.id())) {
+            return false;
+        }
+
+        if let Some(message) = &self.message {
+            if !message.is_match(&String::from_utf8_lossy(commit.message_bytes())) {
+                return false;
+            }
+        }
+        person_matches(self.author.as_ref(), &commit.author())
+            && person_matches(self.committer.as_ref(), &commit.committer())
+    }
+
+    fn is_before_since(&self, commit: &Commit) -> bool {
+        self.since.is_some_and(|since| commit.time().seconds() < since)
+    }
+}
+
+/// 不区分大小写的正则，空白的条件视为未设置
+fn pattern(value: Option<&str>, what: &str) -> Result<Option<Regex>, String> {
+    value.map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            RegexBuilder::new(value)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| format!("无效的{}过滤条件 {}: {}", what, value, e))
+        })
+        .transpose()
+}
+
+/// 按 `姓名 <邮箱>` 匹配，与 `git log --author` 相同
+fn person_matches(pattern: Option<&Regex>, signature: &Signature) -> bool {
+    let Some(pattern) = pattern else { return true };
+    let person = format!(
+        "{} <{}>",
+        String::from_utf8_lossy(signature.name_bytes()),
+        String::from_utf8_lossy(signature.email_bytes()),
+    );
+    pattern.is_match(&person)
+}
+
+/// 提交图中使用的父提交
+///
+/// 过滤后原来的父提交可能不在结果中，此时沿着它向下找到最近的被保留的祖先作为父提交
+/// （与 `git log --graph` 改写父提交的方式相同），这样提交图中不会留下没有终点的列。
+/// 只看第一父提交时其余父提交不参与布局。
+struct ParentRewriter {
+    filter: HistoryFilter,
+    first_parent: bool,
+    included: HashMap<Oid, bool>,
+    // 未被保留的提交 -> 它（含自身以下）最近的被保留祖先
+    nearest: HashMap<Oid, Vec<Oid>>,
+}
+
+impl ParentRewriter {
+    fn new(filter: HistoryFilter, first_parent: bool) -> Self {
+        ParentRewriter {
+            filter,
+            first_parent,
+            included: HashMap::new(),
+            nearest: HashMap::new(),
+        }
+    }
+
+    fn rewrite(&mut self, repo: &Repository, commit: &Commit) -> Result<Vec<Oid>, String> {
+        let parents = self.walked_parents(commit);
+        if !self.filter.is_active() {
+            return Ok(parents);
+        }
+
+        let mut rewritten = Vec::new();
+        for parent in parents {
+            for id in self.nearest_included(repo, parent)? {
+                if !rewritten.contains(&id) {
+                    rewritten.push(id);
+                }
+            }
+        }
+        Ok(rewritten)
+    }
+
+    fn walked_parents(&self, commit: &Commit) -> Vec<Oid> {
+        let parents = commit.parent_ids();
+        match self.first_parent {
+            true => parents.take(1).collect(),
+            false => parents.collect(),
+        }
+    }
+
+    fn is_included(&mut self, repo: &Repository, id: Oid) -> Result<bool, String> {
+        if let Some(&included) = self.included.get(&id) {
+            return Ok(included);
+        }
+        let included = self.filter.matches(&find_commit(repo, id)?);
+        self.included.insert(id, included);
+        Ok(included)
+    }
+
+    /// `start` 本身或其下最近的被保留的提交，历史可能很长，用显式栈代替递归
+    fn nearest_included(&mut self, repo: &Repository, start: Oid) -> Result<Vec<Oid>, String> {
+        if self.is_included(repo, start)? {
+            return Ok(vec![start]);
+        }
+
+        let mut stack = vec![(start, false)];
+        while let Some((id, expanded)) = stack.pop() {
+            if self.nearest.contains_key(&id) {
+                continue;
+            }
+            let commit = find_commit(repo, id)?;
+            // 早于 since 的提交之下不会再有满足时间条件的祖先
+            let parents = match self.filter.is_before_since(&commit) {
+                true => Vec::new(),
+                false => self.walked_parents(&commit),
+            };
+
+            if !expanded {
+                stack.push((id, true));
+                for parent in parents {
+                    if !self.is_included(repo, parent)? && !self.nearest.contains_key(&parent) {
+                        stack.push((parent, false));
+                    }
+                }
+                continue;
+            }
+
+            let mut found = Vec::new();
+            for parent in parents {
+                let ids = match self.is_included(repo, parent)? {
+                    true => vec![parent],
+                    false => self.nearest.get(&parent).cloned().unwrap_or_default(),
+                };
+                for id in ids {
+                    if !found.contains(&id) {
+                        found.push(id);
+                    }
+                }
+            }
+            self.nearest.insert(id, found);
+        }
+
+        Ok(self.nearest.get(&start).cloned().unwrap_or_default())
+    }
+}
+
+fn find_commit(repo: &Repository, id: Oid) -> Result<Commit<'_>, String> {
+    repo.find_commit(id)
+        .map_err(|e| format!("无法读取提交 {}: {}", id, e))
+}
+
+/// 添加遍历起点：HEAD（未指定 `refs` 时）、选中的分支、远程跟踪分支、标签和指定的引用
+fn push_starts(repo: &Repository, revwalk: &mut Revwalk, options: &HistoryOptions) -> Result<(), String> {
+    if options.refs.is_empty() {
+        // 空仓库没有 HEAD，但仍可能有其它引用
+        if let Err(e) = revwalk.push_head() {
+            if !(options.branches || options.remotes || options.tags) {
+                return Err(format!("无法推送 HEAD: {}", e));
+            }
+        }
+    }
+
+    // 通配推送时会忽略不指向提交的引用（如指向树的标签）
+    let globs = [
+        (options.branches, "refs/heads"),
+        (options.remotes, "refs/remotes"),
+        (options.tags, "refs/tags"),
+    ];
+    for (_, glob) in globs.iter().filter(|(enabled, _)| *enabled) {
+        revwalk.push_glob(glob)
+            .map_err(|e| format!("无法添加遍历起点 {}: {}", glob, e))?;
+    }
+
+    for spec in &options.refs {
+        let commit = repo.revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| format!("无法解析版本 {}: {}", spec, e))?;
+        revwalk.push(commit.id())
+            .map_err(|e| format!("无法添加遍历起点 {}: {}", spec, e))?;
+    }
+    Ok(())
+}
+
+/// 游标由会话 id 和下一页的起始位置组成
+pub fn encode_cursor(session: &str, offset: usize) -> String {
+    format!("{}:{}", session, offset)
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<(&str, usize), String> {
+    cursor.rsplit_once(':')
+        .and_then(|(session, offset)| Some((session, offset.parse().ok()?)))
+        .ok_or_else(|| format!("无效的游标: {}", cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    fn ids(page: &WalkPage) -> Vec<String> {
+        page.commits.iter().map(|commit| commit.id.clone()).collect()
+    }
+
+    #[test]
+    fn pages_through_history_in_order() {
+        let repo = TempRepo::new("history-pages");
+        let mut commits: Vec<String> = (1..=5).map(|i| repo.commit(&format!("c{}", i))).collect();
+        commits.reverse();
+        let mut walk = HistoryWalk::start(repo.path(), &HistoryOptions::default()).unwrap();
+
+        let first = walk.page(0, 2).unwrap();
+        assert_eq!(ids(&first), commits[..2]);
+        assert_eq!(first.next, Some(2));
+
+        let second = walk.page(2, 2).unwrap();
+        assert_eq!(ids(&second), commits[2..4]);
+        assert_eq!(second.next, Some(4));
+
+        let last = walk.page(4, 2).unwrap();
+        assert_eq!(ids(&last), commits[4..]);
+        assert_eq!((last.next, last.total, last.complete), (None, 5, true));
+
+        // 已返回过的游标可以重复读取
+        assert_eq!(ids(&walk.page(2, 2).unwrap()), commits[2..4]);
+    }
+
+    #[test]
+    fn rejects_offsets_never_returned() {
+        let repo = TempRepo::new("history-offset");
+        for i in 1..=3 {
+            repo.commit(&format!("c{}", i));
+        }
+        let mut walk = HistoryWalk::start(repo.path(), &HistoryOptions::default()).unwrap();
+
+        assert!(walk.page(1, 2).is_err());
+        assert_eq!(walk.page(0, 2).unwrap().next, Some(2));
+        assert!(walk.page(1, 2).is_err());
+        assert_eq!(walk.page(2, 2).unwrap().next, None);
+        // 最后一页之后没有下一页，末尾位置也不是有效的游标
+        assert!(walk.page(3, 2).is_err());
+    }
+
+    #[test]
+    fn paged_graph_matches_single_page_layout() {
+        let repo = TempRepo::new("history-graph");
+        repo.commit("a");
+        repo.git(&["checkout", "-q", "-b", "feature"]);
+        repo.commit("b");
+        repo.commit("b2");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.commit("c");
+        repo.git(&["merge", "-q", "--no-ff", "-m", "m", "feature"]);
+        repo.commit("d");
+
+        let whole = HistoryWalk::start(repo.path(), &HistoryOptions::default()).unwrap()
+            .page(0, 100)
+            .unwrap();
+        assert_eq!(whole.total, 6);
+
+        let mut walk = HistoryWalk::start(repo.path(), &HistoryOptions::default()).unwrap();
+        let (mut commits, mut rows) = (Vec::new(), Vec::new());
+        let mut next = Some(0);
+        while let Some(offset) = next {
+            let page = walk.page(offset, 2).unwrap();
+            commits.extend(ids(&page));
+            rows.extend(page.rows);
+            next = page.next;
+        }
+
+        assert_eq!(commits, ids(&whole));
+        assert_eq!(rows, whole.rows);
+    }
+
+    #[test]
+    fn dropping_walk_cancels_background_thread() {
+        let repo = TempRepo::new("history-drop");
+        repo.commit("c1");
+        let walk = HistoryWalk::start(repo.path(), &HistoryOptions::default()).unwrap();
+        let shared = walk.shared.clone();
+        drop(walk);
+        assert!(shared.cancelled.load(Ordering::Relaxed));
+
+        // 已取消时遍历线程报告起点有效后直接退出，不再发布结果
+        let options = HistoryOptions::default();
+        let filter = HistoryFilter::new(&options, &HashMap::new()).unwrap();
+        let shared = Shared::default();
+        shared.cancelled.store(true, Ordering::Relaxed);
+        let (ready_tx, ready_rx) = mpsc::channel();
+        walk_in_background(repo.path(), &options, &filter, &shared, ready_tx);
+
+        assert!(ready_rx.recv().unwrap().is_ok());
+        let progress = shared.progress.lock().unwrap();
+        assert!(progress.oids.is_empty() && !progress.done);
+    }
+
+    /// 按给定起点遍历的全部提交
+    fn walk_ids(repo: &TempRepo, options: &HistoryOptions) -> Result<Vec<String>, String> {
+        Ok(ids(&HistoryWalk::start(repo.path(), options)?.page(0, 100)?))
+    }
+
+    #[test]
+    fn explicit_refs_replace_head() {
+        let repo = TempRepo::new("history-refs");
+        let base = repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "other"]);
+        let other = repo.commit("other");
+        repo.git(&["checkout", "-q", "main"]);
+        let head = repo.commit("head");
+
+        assert_eq!(walk_ids(&repo, &HistoryOptions::default()).unwrap(), vec![head.clone(), base.clone()]);
+        let options = HistoryOptions { refs: vec!["other".to_string()], ..Default::default() };
+        assert_eq!(walk_ids(&repo, &options).unwrap(), vec![other, base]);
+
+        let options = HistoryOptions { refs: vec!["missing".to_string()], ..Default::default() };
+        assert!(walk_ids(&repo, &options).is_err());
+    }
+
+    #[test]
+    fn globs_start_without_head() {
+        let repo = TempRepo::new("history-empty");
+        assert!(walk_ids(&repo, &HistoryOptions::default()).is_err());
+        let branches = HistoryOptions { branches: true, ..Default::default() };
+        assert_eq!(walk_ids(&repo, &branches).unwrap(), Vec::<String>::new());
+
+        // HEAD 指向尚未创建的分支时仍能从标签开始遍历
+        let tagged = repo.commit("tagged");
+        repo.git(&["tag", "v1"]);
+        repo.git(&["checkout", "-q", "--orphan", "unborn"]);
+        assert!(walk_ids(&repo, &HistoryOptions::default()).is_err());
+        let tags = HistoryOptions { tags: true, ..Default::default() };
+        assert_eq!(walk_ids(&repo, &tags).unwrap(), vec![tagged]);
+    }
+
+    #[test]
+    fn tag_glob_skips_tags_on_trees() {
+        let repo = TempRepo::new("history-tree-tag");
+        let id = repo.commit("first");
+        repo.git(&["tag", "-a", "tree", "-m", "tree", "HEAD^{tree}"]);
+
+        let tags = HistoryOptions { tags: true, ..Default::default() };
+        assert_eq!(walk_ids(&repo, &tags).unwrap(), vec![id]);
+        let options = HistoryOptions { refs: vec!["tree".to_string()], ..Default::default() };
+        assert!(walk_ids(&repo, &options).is_err());
+    }
+
+    const ALICE: (&str, &str) = ("Alice", "alice@example.com");
+    const BOB: (&str, &str) = ("Bob", "bob@corp.org");
+    const T0: i64 = 1_700_000_000;
+
+    /// 以 `author` 的身份在 `T0 + offset` 执行产生提交的命令，提交者固定为 Tester
+    fn run_as(repo: &TempRepo, args: &[&str], author: (&str, &str), offset: i64) -> Oid {
+        let date = format!("{} +0000", T0 + offset);
+        repo.git_with_env(args, &[
+            ("GIT_AUTHOR_NAME", author.0),
+            ("GIT_AUTHOR_EMAIL", author.1),
+            ("GIT_AUTHOR_DATE", &date),
+            ("GIT_COMMITTER_DATE", &date),
+        ]);
+        Oid::from_str(&repo.rev_parse("HEAD")).unwrap()
+    }
+
+    fn commit_as(repo: &TempRepo, message: &str, author: (&str, &str), offset: i64) -> Oid {
+        run_as(repo, &["commit", "-q", "--allow-empty", "-m", message], author, offset)
+    }
+
+    /// 被过滤的提交和保留的提交交错的历史，side 分支上只有 Bob 的提交
+    ///
+    /// ```text
+    /// t(A) - z(B) - m(A) - y(B) - k1(A) - k0(A) - root(A)
+    ///                  \                  /
+    ///                   x(B) ------------
+    /// ```
+    struct Fixture {
+        repo: TempRepo,
+        root: Oid,
+        k0: Oid,
+        k1: Oid,
+        x: Oid,
+        y: Oid,
+        m: Oid,
+        z: Oid,
+        t: Oid,
+    }
+
+    fn fixture() -> Fixture {
+        let repo = TempRepo::new("history-filter");
+        let root = commit_as(&repo, "root", ALICE, 0);
+        let k0 = commit_as(&repo, "k0", ALICE, 100);
+        repo.git(&["branch", "side"]);
+        let k1 = commit_as(&repo, "k1", ALICE, 200);
+        let y = commit_as(&repo, "fix: y", BOB, 300);
+        repo.git(&["checkout", "-q", "side"]);
+        let x = commit_as(&repo, "x", BOB, 400);
+        repo.git(&["checkout", "-q", "main"]);
+        let m = run_as(&repo, &["merge", "-q", "--no-ff", "-m", "merge side", "side"], ALICE, 500);
+        let z = commit_as(&repo, "z", BOB, 600);
+        let t = commit_as(&repo, "t", ALICE, 700);
+        Fixture { repo, root, k0, k1, x, y, m, z, t }
+    }
+
+    impl Fixture {
+        fn all(&self) -> [Oid; 8] {
+            [self.root, self.k0, self.k1, self.x, self.y, self.m, self.z, self.t]
+        }
+
+        /// 满足过滤条件的提交
+        fn matching(&self, options: &HistoryOptions) -> Vec<Oid> {
+            let repo = Repository::open(self.repo.path()).unwrap();
+            let filter = HistoryFilter::new(options, &ref_labels(&repo)).unwrap();
+            self.all().into_iter()
+                .filter(|id| filter.matches(&repo.find_commit(*id).unwrap()))
+                .collect()
+        }
+
+        fn rewriter(&self, options: &HistoryOptions) -> (Repository, ParentRewriter) {
+            let repo = Repository::open(self.repo.path()).unwrap();
+            let filter = HistoryFilter::new(options, &ref_labels(&repo)).unwrap();
+            (repo, ParentRewriter::new(filter, options.first_parent))
+        }
+    }
+
+    fn rewrite(repo: &Repository, rewriter: &mut ParentRewriter, id: Oid) -> Vec<Oid> {
+        rewriter.rewrite(repo, &repo.find_commit(id).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn filters_people_by_name_and_email() {
+        let f = fixture();
+        let alice = vec![f.root, f.k0, f.k1, f.m, f.t];
+
+        let author = |pattern: &str| HistoryOptions { author: Some(pattern.to_string()), ..Default::default() };
+        assert_eq!(f.matching(&author("alice")), alice);
+        assert_eq!(f.matching(&author("^BOB <bob@corp\\.org>$")), vec![f.x, f.y, f.z]);
+        assert_eq!(f.matching(&author("example\\.com>")), alice);
+        // 空白条件视为未设置
+        assert_eq!(f.matching(&author("  ")), f.all());
+        assert!(HistoryFilter::new(&author("("), &HashMap::new()).is_err());
+
+        let committer = |pattern: &str| HistoryOptions { committer: Some(pattern.to_string()), ..Default::default() };
+        assert_eq!(f.matching(&committer("^tester <tester@example.com>$")), f.all());
+        assert!(f.matching(&committer("alice")).is_empty());
+
+        let message = HistoryOptions { message: Some("^FIX:".to_string()), ..Default::default() };
+        assert_eq!(f.matching(&message), vec![f.y]);
+    }
+
+    #[test]
+    fn time_bounds_are_inclusive() {
+        let f = fixture();
+        let range = |since: Option<i64>, until: Option<i64>| HistoryOptions {
+            since: since.map(|offset| T0 + offset),
+            until: until.map(|offset| T0 + offset),
+            ..Default::default()
+        };
+
+        assert_eq!(f.matching(&range(Some(200), Some(400))), vec![f.k1, f.x, f.y]);
+        assert_eq!(f.matching(&range(Some(700), None)), vec![f.t]);
+        assert_eq!(f.matching(&range(None, Some(0))), vec![f.root]);
+        assert!(f.matching(&range(Some(201), Some(299))).is_empty());
+    }
+
+    #[test]
+    fn filters_merges() {
+        let f = fixture();
+        let merges = |filter| HistoryOptions { merges: Some(filter), ..Default::default() };
+
+        assert_eq!(f.matching(&merges(MergeFilter::MergesOnly)), vec![f.m]);
+        let no_merges = f.matching(&merges(MergeFilter::NoMerges));
+        assert_eq!(no_merges.len(), 7);
+        assert!(!no_merges.contains(&f.m));
+    }
+
+    #[test]
+    fn simplify_by_decoration_keeps_referenced_commits() {
+        let f = fixture();
+        f.repo.git(&["tag", "v1", &f.k0.to_string()]);
+        let options = HistoryOptions { simplify_by_decoration: true, ..Default::default() };
+
+        // main 和 HEAD 指向 t，side 指向 x
+        assert_eq!(f.matching(&options), vec![f.k0, f.x, f.t]);
+    }
+
+    #[test]
+    fn rewritten_parents_skip_filtered_commits() {
+        let f = fixture();
+        let alice = HistoryOptions { author: Some("alice".to_string()), ..Default::default() };
+        let (repo, mut rewriter) = f.rewriter(&alice);
+
+        assert_eq!(rewrite(&repo, &mut rewriter, f.t), vec![f.m]);
+        // 合并的两侧都被过滤，分别改写为各自最近的保留祖先
+        assert_eq!(rewrite(&repo, &mut rewriter, f.m), vec![f.k1, f.k0]);
+        assert_eq!(rewrite(&repo, &mut rewriter, f.k1), vec![f.k0]);
+        assert_eq!(rewrite(&repo, &mut rewriter, f.root), Vec::<Oid>::new());
+
+        let first_parent = HistoryOptions { first_parent: true, ..alice.clone() };
+        let (repo, mut rewriter) = f.rewriter(&first_parent);
+        assert_eq!(rewrite(&repo, &mut rewriter, f.m), vec![f.k1]);
+
+        // 没有过滤条件时保持原来的父提交
+        let (repo, mut rewriter) = f.rewriter(&HistoryOptions::default());
+        assert_eq!(rewrite(&repo, &mut rewriter, f.m), vec![f.y, f.x]);
+        assert_eq!(rewrite(&repo, &mut rewriter, f.t), vec![f.z]);
+    }
+
+    #[test]
+    fn rewrite_stops_below_since() {
+        let f = fixture();
+        let options = HistoryOptions {
+            author: Some("alice".to_string()),
+            since: Some(T0 + 200),
+            ..Default::default()
+        };
+        let (repo, mut rewriter) = f.rewriter(&options);
+
+        assert_eq!(rewriter.nearest_included(&repo, f.x).unwrap(), Vec::<Oid>::new());
+        assert_eq!(rewrite(&repo, &mut rewriter, f.m), vec![f.k1]);
+        // k0 早于 since，不再继续查看它的父提交
+        assert_eq!(rewriter.nearest.get(&f.k0), Some(&Vec::new()));
+        assert!(!rewriter.included.contains_key(&f.root));
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = encode_cursor("session", 42);
+        assert_eq!(decode_cursor(&cursor).unwrap(), ("session", 42));
+        // 会话 id 中的冒号不影响解析
+        assert_eq!(decode_cursor(&encode_cursor("a:b", 0)).unwrap(), ("a:b", 0));
+    }
+
+    #[test]
+    fn rejects_malformed_cursors() {
+        for cursor in ["", "session", "session:", "session:x", "session:-1", "session:1.5"] {
+            assert!(decode_cursor(cursor).is_err(), "{}", cursor);
+        }
+    }
+}
@@ -0,0 +1,757 @@
+use std::fs;
+use std::process::Command;
+
+use git2::{Commit, Oid, Repository, Sort};
+use serde::{Deserialize, Serialize};
+
+use super::rebase::{pop_autostash, read_autostash, read_progress, resolve_commit, state_dir, AUTOSTASH_FILE};
+use super::{checkout_merge_index, ensure_clean_state, has_local_changes, head_commit, open, reset_merge, signature};
+use crate::models::*;
+
+const STATE_FILE: &str = "prism-rebase.json";
+
+/// 持久化在 `.git/rebase-merge` 中的执行状态，应用重启后可继续
+#[derive(Debug, Serialize, Deserialize)]
+struct PlanState {
+    plan: Vec<RebaseTodoItem>,
+    /// 下一项要执行的计划序号
+    next: usize,
+    /// 因冲突停下、等待解决后提交的计划序号
+    pending: Option<usize>,
+    /// 停在 edit 时的提交，继续前如有已暂存修改则修补该提交
+    amend: Option<String>,
+    head_name: String,
+    orig_head: String,
+    onto: String,
+}
+
+/// 生成 `upstream..HEAD` 的变基计划，`autosquash` 为空时读取 `rebase.autoSquash` 配置
+pub fn plan(path: &str, upstream: &str, autosquash: Option<bool>) -> Result<Vec<RebaseTodoItem>, String> {
+    let repo = open(path)?;
+    let upstream_id = resolve_commit(&repo, upstream)?;
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("无法创建 revwalk: {}", e))?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(|e| format!("设置排序失败: {}", e))?;
+    revwalk.push_head()
+        .map_err(|e| format!("无法推送 HEAD: {}", e))?;
+    revwalk.hide(upstream_id)
+        .map_err(|e| format!("无法排除上游: {}", e))?;
+
+    let mut items = Vec::new();
+    for oid in revwalk {
+        let oid = oid.map_err(|e| format!("遍历提交失败: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("无法找到提交: {}", e))?;
+        // 与 git 一致，默认不保留合并提交
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        items.push(RebaseTodoItem {
+            action: RebaseAction::Pick,
+            commit_id: Some(oid.to_string()),
+            summary: commit.summary().unwrap_or("").to_string(),
+            message: None,
+            command: None,
+        });
+    }
+
+    let autosquash = autosquash.unwrap_or_else(|| {
+        repo.config()
+            .and_then(|config| config.get_bool("rebase.autosquash"))
+            .unwrap_or(false)
+    });
+    if autosquash {
+        items = apply_autosquash(items);
+        // amend! 提交相当于 git 的 fixup -C，用它的说明替换目标提交的说明
+        for item in items.iter_mut() {
+            if item.action != RebaseAction::Fixup || !item.summary.starts_with("amend! ") {
+                continue;
+            }
+            let Some(id) = item.commit_id.as_deref() else { continue };
+            let commit = resolve_commit(&repo, id)
+                .and_then(|oid| repo.find_commit(oid).map_err(|e| format!("无法找到提交: {}", e)))?;
+            item.message = amend_message(commit.message().unwrap_or(""));
+        }
+    }
+
+    Ok(items)
+}
+
+/// 按计划执行交互式变基，`onto` 为空时变基到 `upstream`
+pub fn start(
+    path: &str,
+    upstream: &str,
+    onto: Option<&str>,
+    plan: Vec<RebaseTodoItem>,
+    autostash: bool,
+) -> Result<RebaseResult, String> {
+    let mut repo = open(path)?;
+    ensure_clean_state(&repo)?;
+    validate_plan(&repo, &plan)?;
+
+    let onto_id = resolve_commit(&repo, onto.unwrap_or(upstream))?;
+    let head = head_commit(&repo)?;
+    let orig_head = head.id();
+    drop(head);
+
+    let head_ref = repo.head()
+        .map_err(|e| format!("无法获取 HEAD: {}", e))?;
+    let head_name = if head_ref.is_branch() {
+        head_ref.name().unwrap_or("HEAD").to_string()
+    } else {
+        "detached HEAD".to_string()
+    };
+    drop(head_ref);
+
+    let stash_id = if has_local_changes(&repo)? {
+        if !autostash {
+            return Err("工作区有未提交的修改，请先提交或启用自动贮藏".to_string());
+        }
+        let signature = signature(&repo)?;
+        let oid = repo.stash_save2(&signature, Some("autostash"), None)
+            .map_err(|e| format!("自动贮藏失败: {}", e))?;
+        Some(oid)
+    } else {
+        None
+    };
+
+    let onto_commit = repo.find_commit(onto_id)
+        .map_err(|e| format!("无法找到提交: {}", e))?;
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe();
+    if let Err(e) = repo.checkout_tree(onto_commit.as_object(), Some(&mut checkout))
+        .and_then(|_| repo.set_head_detached(onto_id))
+    {
+        if let Some(stash_id) = stash_id {
+            let _ = pop_autostash(&repo, stash_id);
+        }
+        return Err(format!("无法检出 {}: {}", onto_id, e));
+    }
+
+    let dir = state_dir(&repo);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("写入变基状态失败: {}", e))?;
+    if let Some(stash_id) = stash_id {
+        write_state_file(&repo, AUTOSTASH_FILE, &format!("{}\n", stash_id))?;
+    }
+
+    let mut state = PlanState {
+        plan,
+        next: 0,
+        pending: None,
+        amend: None,
+        head_name,
+        orig_head: orig_head.to_string(),
+        onto: onto_id.to_string(),
+    };
+    write_state_file(&repo, "head-name", &format!("{}\n", state.head_name))?;
+    write_state_file(&repo, "orig-head", &format!("{}\n", state.orig_head))?;
+    write_state_file(&repo, "onto", &format!("{}\n", state.onto))?;
+    write_state_file(&repo, "interactive", "")?;
+    save_state(&repo, &state)?;
+
+    run(&repo, &mut state, Vec::new())
+}
+
+/// 当前是否为 Prism 发起的交互式变基
+pub(crate) fn is_in_progress(repo: &Repository) -> bool {
+    state_dir(repo).join(STATE_FILE).is_file()
+}
+
+/// 解决冲突或编辑完成后继续
+pub(crate) fn resume(repo: &Repository) -> Result<RebaseResult, String> {
+    let mut state = load_state(repo)?;
+
+    let mut index = repo.index()
+        .map_err(|e| format!("无法读取索引: {}", e))?;
+    if index.has_conflicts() {
+        let conflicts = super::merge::conflicted_paths(&index)?;
+        return Err(format!("仍有未解决的冲突: {}", conflicts.join(", ")));
+    }
+    let tree_id = index.write_tree()
+        .map_err(|e| format!("写入树失败: {}", e))?;
+
+    let mut dropped = Vec::new();
+    if let Some(pending) = state.pending.take() {
+        let item = state.plan[pending].clone();
+        let commit = find_item_commit(repo, &item)?;
+        if !commit_step(repo, &item, &commit, tree_id)? {
+            dropped.push(commit.id().to_string());
+        }
+    } else if let Some(amend) = state.amend.take() {
+        // 停在 edit 时暂存的修改修补到该提交上
+        let head = head_commit(repo)?;
+        if head.id().to_string() == amend && head.tree_id() != tree_id {
+            amend_head(repo, &head, tree_id, head.message().unwrap_or(""))?;
+        }
+    }
+
+    save_state(repo, &state)?;
+    run(repo, &mut state, dropped)
+}
+
+/// 跳过当前提交
+pub(crate) fn skip(repo: &Repository) -> Result<RebaseResult, String> {
+    let mut state = load_state(repo)?;
+    reset_merge(repo)?;
+    state.pending = None;
+    state.amend = None;
+    save_state(repo, &state)?;
+    run(repo, &mut state, Vec::new())
+}
+
+/// 放弃变基，恢复原分支和 HEAD
+pub(crate) fn abort(repo: &Repository) -> Result<Option<String>, String> {
+    let state = load_state(repo)?;
+    let stash_id = read_autostash(repo);
+    let orig_head = Oid::from_str(&state.orig_head)
+        .map_err(|e| format!("无法读取变基状态: {}", e))?;
+    let orig_commit = repo.find_commit(orig_head)
+        .map_err(|e| format!("无法找到提交: {}", e))?;
+
+    if state.head_name.starts_with("refs/") {
+        repo.set_head(&state.head_name)
+    } else {
+        repo.set_head_detached(orig_head)
+    }
+    .map_err(|e| format!("恢复 HEAD 失败: {}", e))?;
+
+    // hard reset 会一并清除 .git/rebase-merge
+    repo.reset(orig_commit.as_object(), git2::ResetType::Hard, None)
+        .map_err(|e| format!("放弃变基失败: {}", e))?;
+    let _ = fs::remove_dir_all(state_dir(repo));
+
+    match stash_id {
+        Some(stash_id) => pop_autostash(repo, stash_id),
+        None => Ok(None),
+    }
+}
+
+/// 从 `state.next` 开始逐项执行，遇到冲突、edit 或 exec 失败时停下
+///
+/// `dropped` 为继续执行前已跳过的空提交，与本轮跳过的一起返回。
+fn run(repo: &Repository, state: &mut PlanState, mut dropped: Vec<String>) -> Result<RebaseResult, String> {
+    while state.next < state.plan.len() {
+        let step = state.next;
+        let item = state.plan[step].clone();
+        state.next += 1;
+        write_progress(repo, state)?;
+
+        match item.action {
+            RebaseAction::Drop => {}
+            RebaseAction::Exec => {
+                let command = item.command.as_deref().unwrap_or("");
+                if let Err(output) = run_exec(repo, command) {
+                    save_state(repo, state)?;
+                    let message = format!("命令执行失败: {}\n{}", command, output);
+                    return Ok(stopped(repo, "exec_failed", Some(message), dropped));
+                }
+            }
+            _ => {
+                let commit = find_item_commit(repo, &item)?;
+                let head = head_commit(repo)?;
+
+                // 父提交未变化且无需改写时直接沿用原提交
+                let reuse = matches!(item.action, RebaseAction::Pick | RebaseAction::Edit)
+                    && commit.parent_ids().next() == Some(head.id())
+                    && commit.parent_count() == 1;
+                if reuse {
+                    let mut checkout = git2::build::CheckoutBuilder::new();
+                    checkout.safe();
+                    repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+                        .map_err(|e| format!("检出失败: {}", e))?;
+                    repo.set_head_detached(commit.id())
+                        .map_err(|e| format!("更新 HEAD 失败: {}", e))?;
+                } else {
+                    let mut merged = repo.cherrypick_commit(&commit, &head, 0, None)
+                        .map_err(|e| format!("应用提交失败: {}", e))?;
+                    match checkout_merge_index(repo, &mut merged, false)? {
+                        Ok(tree_id) => {
+                            if !commit_step(repo, &item, &commit, tree_id)? {
+                                dropped.push(commit.id().to_string());
+                            }
+                        }
+                        Err(conflicts) => {
+                            state.pending = Some(step);
+                            save_state(repo, state)?;
+                            let mut result = stopped(repo, "conflicted", None, dropped);
+                            result.conflicts = conflicts;
+                            return Ok(result);
+                        }
+                    }
+                }
+
+                if item.action == RebaseAction::Edit {
+                    state.amend = Some(head_commit(repo)?.id().to_string());
+                    save_state(repo, state)?;
+                    let message = format!("已停在 {}，修改完成后继续", item.summary);
+                    return Ok(stopped(repo, "stopped", Some(message), dropped));
+                }
+            }
+        }
+        save_state(repo, state)?;
+    }
+
+    finish(repo, state, dropped)
+}
+
+/// 根据计划项把结果树提交为新提交，squash / fixup 修补到上一个提交
+///
+/// 与 git 一致，改动已包含在 HEAD 中（结果树与 HEAD 相同）的提交不再创建空提交，
+/// 此时返回 false；原提交本身就是空提交时仍保留。
+fn commit_step(
+    repo: &Repository,
+    item: &RebaseTodoItem,
+    commit: &Commit,
+    tree_id: Oid,
+) -> Result<bool, String> {
+    let head = head_commit(repo)?;
+    let original = commit.message().unwrap_or("");
+
+    match item.action {
+        RebaseAction::Squash | RebaseAction::Fixup => {
+            let message = match (&item.message, item.action) {
+                (Some(message), _) => message.clone(),
+                (None, RebaseAction::Squash) => {
+                    // 连续 squash 时 HEAD 信息已包含前面的合并结果
+                    let previous = head.message().unwrap_or("").trim_end();
+                    format!("{}\n\n{}", previous, strip_autosquash_prefix(original).trim_end())
+                }
+                _ => head.message().unwrap_or("").to_string(),
+            };
+            amend_head(repo, &head, tree_id, &message)?;
+        }
+        _ if tree_id == head.tree_id() && !is_empty_commit(commit) => return Ok(false),
+        _ => {
+            let message = match item.action {
+                RebaseAction::Reword => item.message.clone().unwrap_or_else(|| original.to_string()),
+                _ => original.to_string(),
+            };
+            let tree = repo.find_tree(tree_id)
+                .map_err(|e| format!("写入树失败: {}", e))?;
+            let committer = signature(repo)?;
+            let oid = repo.commit(
+                None,
+                &commit.author(),
+                &committer,
+                &normalize_message(&message),
+                &tree,
+                &[&head],
+            ).map_err(|e| format!("提交失败: {}", e))?;
+            set_head(repo, oid, &format!("rebase (pick): {}", commit.summary().unwrap_or("")))?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// 提交本身没有改动，如 `git commit --allow-empty` 创建的提交
+fn is_empty_commit(commit: &Commit) -> bool {
+    match commit.parent(0) {
+        Ok(parent) => parent.tree_id() == commit.tree_id(),
+        Err(_) => commit.tree().map(|tree| tree.is_empty()).unwrap_or(false),
+    }
+}
+
+fn amend_head(repo: &Repository, head: &Commit, tree_id: Oid, message: &str) -> Result<(), String> {
+    let tree = repo.find_tree(tree_id)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let parents: Vec<Commit> = head.parents().collect();
+    let parent_refs: Vec<&Commit> = parents.iter().collect();
+    let committer = signature(repo)?;
+    let oid = repo.commit(
+        None,
+        &head.author(),
+        &committer,
+        &normalize_message(message),
+        &tree,
+        &parent_refs,
+    ).map_err(|e| format!("提交失败: {}", e))?;
+    set_head(repo, oid, &format!("rebase (squash): {}", head.summary().unwrap_or("")))
+}
+
+fn finish(repo: &Repository, state: &PlanState, dropped: Vec<String>) -> Result<RebaseResult, String> {
+    let head = head_commit(repo)?;
+    if state.head_name.starts_with("refs/") {
+        repo.reference(
+            &state.head_name,
+            head.id(),
+            true,
+            &format!("rebase (finish): {} onto {}", state.head_name, state.onto),
+        ).map_err(|e| format!("更新分支失败: {}", e))?;
+        repo.set_head(&state.head_name)
+            .map_err(|e| format!("更新 HEAD 失败: {}", e))?;
+    }
+
+    let stash_id = read_autostash(repo);
+    fs::remove_dir_all(state_dir(repo))
+        .map_err(|e| format!("清理变基状态失败: {}", e))?;
+
+    let message = match stash_id {
+        Some(stash_id) => pop_autostash(repo, stash_id)?,
+        None => None,
+    };
+
+    Ok(RebaseResult {
+        status: "completed".to_string(),
+        progress: None,
+        conflicts: Vec::new(),
+        message,
+        dropped,
+    })
+}
+
+fn stopped(repo: &Repository, status: &str, message: Option<String>, dropped: Vec<String>) -> RebaseResult {
+    RebaseResult {
+        status: status.to_string(),
+        progress: read_progress(repo),
+        conflicts: Vec::new(),
+        message,
+        dropped,
+    }
+}
+
+/// 把 `fixup! <subject>` / `squash! <subject>` 提交移动到目标提交之后
+fn apply_autosquash(items: Vec<RebaseTodoItem>) -> Vec<RebaseTodoItem> {
+    let mut result: Vec<RebaseTodoItem> = Vec::new();
+    // 每个目标已经挂接的 fixup 数量，用于保持它们的原始顺序
+    let mut attached: Vec<usize> = Vec::new();
+
+    for mut item in items {
+        let target = autosquash_target(&item.summary).and_then(|(action, subject)| {
+            result.iter().position(|candidate| {
+                candidate.action != RebaseAction::Fixup
+                    && candidate.action != RebaseAction::Squash
+                    && (candidate.summary == subject
+                        || (subject.len() >= 4
+                            && candidate.commit_id.as_deref().is_some_and(|id| id.starts_with(subject))))
+            }).map(|pos| (action, pos))
+        });
+
+        match target {
+            Some((action, pos)) => {
+                item.action = action;
+                let insert_at = pos + 1 + attached[pos];
+                attached[pos] += 1;
+                result.insert(insert_at, item);
+                attached.insert(insert_at, 0);
+            }
+            None => {
+                result.push(item);
+                attached.push(0);
+            }
+        }
+    }
+
+    result
+}
+
+/// 解析 autosquash 前缀，返回对应的动作和目标提交标题
+fn autosquash_target(summary: &str) -> Option<(RebaseAction, &str)> {
+    let (action, mut rest) = if let Some(rest) = summary.strip_prefix("fixup! ") {
+        (RebaseAction::Fixup, rest)
+    } else if let Some(rest) = summary.strip_prefix("amend! ") {
+        (RebaseAction::Fixup, rest)
+    } else if let Some(rest) = summary.strip_prefix("squash! ") {
+        (RebaseAction::Squash, rest)
+    } else {
+        return None;
+    };
+
+    // fixup! fixup! xxx 指向同一个目标
+    while let Some(inner) = rest.strip_prefix("fixup! ")
+        .or_else(|| rest.strip_prefix("squash! "))
+        .or_else(|| rest.strip_prefix("amend! "))
+    {
+        rest = inner;
+    }
+    Some((action, rest))
+}
+
+/// `amend! <subject>` 提交中标题之后的部分，即目标提交的新说明；为空时保留原说明
+fn amend_message(message: &str) -> Option<String> {
+    let (_, rest) = message.split_once('\n')?;
+    let rest = rest.trim();
+    (!rest.is_empty()).then(|| rest.to_string())
+}
+
+fn strip_autosquash_prefix(message: &str) -> &str {
+    message.strip_prefix("squash! ")
+        .map(|rest| rest.split_once('\n').map(|(_, body)| body.trim_start()).unwrap_or(""))
+        .unwrap_or(message)
+}
+
+fn validate_plan(repo: &Repository, plan: &[RebaseTodoItem]) -> Result<(), String> {
+    let mut has_base = false;
+    for item in plan {
+        match item.action {
+            RebaseAction::Exec => {
+                if item.command.as_deref().unwrap_or("").trim().is_empty() {
+                    return Err("exec 需要指定命令".to_string());
+                }
+            }
+            RebaseAction::Drop => {}
+            action => {
+                find_item_commit(repo, item)?;
+                if matches!(action, RebaseAction::Squash | RebaseAction::Fixup) && !has_base {
+                    return Err(format!("{} 之前没有可合并的提交", item.summary));
+                }
+                has_base = true;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn find_item_commit<'r>(repo: &'r Repository, item: &RebaseTodoItem) -> Result<Commit<'r>, String> {
+    let id = item.commit_id.as_deref()
+        .ok_or_else(|| format!("计划项缺少提交: {}", item.summary))?;
+    repo.revparse_single(id)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("无法找到提交 {}: {}", id, e))
+}
+
+fn run_exec(repo: &Repository, command: &str) -> Result<(), String> {
+    let workdir = repo.workdir()
+        .ok_or_else(|| "裸仓库不支持 exec".to_string())?;
+
+    #[cfg(unix)]
+    let output = Command::new("sh").arg("-c").arg(command).current_dir(workdir).output();
+    #[cfg(not(unix))]
+    let output = Command::new("cmd").arg("/C").arg(command).current_dir(workdir).output();
+
+    match output {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn set_head(repo: &Repository, oid: Oid, reflog: &str) -> Result<(), String> {
+    let mut head = repo.find_reference("HEAD")
+        .map_err(|e| format!("无法获取 HEAD: {}", e))?;
+    head.set_target(oid, reflog)
+        .map(|_| ())
+        .map_err(|e| format!("更新 HEAD 失败: {}", e))
+}
+
+fn normalize_message(message: &str) -> String {
+    format!("{}\n", message.trim_end())
+}
+
+fn write_progress(repo: &Repository, state: &PlanState) -> Result<(), String> {
+    write_state_file(repo, "msgnum", &format!("{}\n", state.next))?;
+    write_state_file(repo, "end", &format!("{}\n", state.plan.len()))?;
+
+    // 与 git 相同的 todo 文本，便于在命令行中查看
+    let mut todo = String::new();
+    for item in &state.plan[state.next..] {
+        todo.push_str(&todo_line(item));
+    }
+    write_state_file(repo, "git-rebase-todo", &todo)?;
+    let mut done = String::new();
+    for item in &state.plan[..state.next] {
+        done.push_str(&todo_line(item));
+    }
+    write_state_file(repo, "done", &done)
+}
+
+fn todo_line(item: &RebaseTodoItem) -> String {
+    match item.action {
+        RebaseAction::Exec => format!("exec {}\n", item.command.as_deref().unwrap_or("")),
+        action => {
+            let name = match action {
+                RebaseAction::Pick => "pick",
+                RebaseAction::Reword => "reword",
+                RebaseAction::Edit => "edit",
+                RebaseAction::Squash => "squash",
+                RebaseAction::Fixup => "fixup",
+                _ => "drop",
+            };
+            let id = item.commit_id.as_deref().unwrap_or("");
+            format!("{} {} {}\n", name, &id[..id.len().min(7)], item.summary)
+        }
+    }
+}
+
+fn write_state_file(repo: &Repository, name: &str, content: &str) -> Result<(), String> {
+    fs::write(state_dir(repo).join(name), content)
+        .map_err(|e| format!("写入变基状态失败: {}", e))
+}
+
+fn save_state(repo: &Repository, state: &PlanState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("写入变基状态失败: {}", e))?;
+    write_state_file(repo, STATE_FILE, &content)
+}
+
+fn load_state(repo: &Repository) -> Result<PlanState, String> {
+    let content = fs::read_to_string(state_dir(repo).join(STATE_FILE))
+        .map_err(|_| "当前没有进行中的交互式变基".to_string())?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("无法读取变基状态: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::rebase::{rebase_abort, rebase_continue};
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// base 之后依次为 add b、add c、fixup! add b、add d、squash! add c
+    fn with_fixups() -> TempRepo {
+        let repo = TempRepo::new("irebase");
+        repo.write("a.txt", "a\n");
+        repo.commit("base");
+        repo.write("b.txt", "b\n");
+        repo.commit("add b");
+        repo.write("c.txt", "c\n");
+        repo.commit("add c");
+        repo.write("b.txt", "b2\n");
+        repo.commit("fixup! add b");
+        repo.write("d.txt", "d\n");
+        repo.commit("add d");
+        repo.write("c.txt", "c2\n");
+        repo.commit("squash! add c\n\nextra c");
+        repo
+    }
+
+    fn steps(plan: &[RebaseTodoItem]) -> Vec<(RebaseAction, &str)> {
+        plan.iter().map(|item| (item.action, item.summary.as_str())).collect()
+    }
+
+    fn subjects(repo: &TempRepo, range: &str) -> String {
+        repo.git(&["log", "--reverse", "--format=%s", range])
+    }
+
+    #[test]
+    fn autosquash_moves_fixups_after_targets() {
+        let repo = with_fixups();
+        let todo = plan(repo.path(), "HEAD~5", Some(true)).unwrap();
+        assert_eq!(steps(&todo), vec![
+            (RebaseAction::Pick, "add b"),
+            (RebaseAction::Fixup, "fixup! add b"),
+            (RebaseAction::Pick, "add c"),
+            (RebaseAction::Squash, "squash! add c"),
+            (RebaseAction::Pick, "add d"),
+        ]);
+        assert!(plan(repo.path(), "HEAD~5", Some(false)).unwrap()
+            .iter()
+            .all(|item| item.action == RebaseAction::Pick));
+
+        let result = start(repo.path(), "HEAD~5", None, todo, false).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(subjects(&repo, "HEAD~3..HEAD"), "add b\nadd c\nadd d");
+        assert_eq!(repo.git(&["show", "HEAD~2:b.txt"]), "b2");
+        assert_eq!(repo.git(&["log", "-1", "--format=%B", "HEAD~1"]), "add c\n\nextra c");
+        assert_eq!(repo.git(&["rev-parse", "--abbrev-ref", "HEAD"]), "main");
+    }
+
+    #[test]
+    fn autosquash_amend_replaces_target_message() {
+        let repo = TempRepo::new("irebase-amend");
+        repo.write("a.txt", "a\n");
+        repo.commit("base");
+        repo.write("b.txt", "b\n");
+        repo.commit("add b");
+        repo.write("c.txt", "c\n");
+        repo.commit("add c");
+        repo.write("b.txt", "b2\n");
+        repo.commit("amend! add b\n\nadd b properly\n\nwith a body");
+
+        let todo = plan(repo.path(), "HEAD~3", Some(true)).unwrap();
+        assert_eq!(steps(&todo), vec![
+            (RebaseAction::Pick, "add b"),
+            (RebaseAction::Fixup, "amend! add b"),
+            (RebaseAction::Pick, "add c"),
+        ]);
+        assert_eq!(todo[1].message.as_deref(), Some("add b properly\n\nwith a body"));
+
+        let result = start(repo.path(), "HEAD~3", None, todo, false).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(subjects(&repo, "HEAD~2..HEAD"), "add b properly\nadd c");
+        assert_eq!(repo.git(&["log", "-1", "--format=%B", "HEAD~1"]), "add b properly\n\nwith a body");
+        assert_eq!(repo.git(&["show", "HEAD~1:b.txt"]), "b2");
+    }
+
+    #[test]
+    fn edit_stops_and_amends_staged_changes() {
+        let repo = with_fixups();
+        let mut todo = plan(repo.path(), "HEAD~5", Some(false)).unwrap();
+        todo[0].action = RebaseAction::Edit;
+
+        let result = start(repo.path(), "HEAD~5", None, todo, false).unwrap();
+        assert_eq!(result.status, "stopped");
+        assert_eq!(result.progress.map(|progress| progress.current), Some(1));
+        assert_eq!(repo.git(&["log", "-1", "--format=%s"]), "add b");
+
+        repo.write("e.txt", "edited\n");
+        repo.git(&["add", "e.txt"]);
+        let result = rebase_continue(repo.path()).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(repo.git(&["show", "--format=", "--name-only", "HEAD~4"]), "b.txt\ne.txt");
+        assert_eq!(subjects(&repo, "HEAD~5..HEAD").lines().count(), 5);
+    }
+
+    #[test]
+    fn conflict_stops_until_resolved() {
+        let repo = TempRepo::new("irebase-conflict");
+        repo.write("a.txt", "base\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "other"]);
+        repo.write("a.txt", "other\n");
+        repo.commit("other a");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("a.txt", "main\n");
+        repo.commit("main a");
+        repo.write("b.txt", "b\n");
+        repo.commit("main b");
+        let head = repo.rev_parse("HEAD");
+
+        let result = start(repo.path(), "other", None, plan(repo.path(), "other", None).unwrap(), false).unwrap();
+        assert_eq!(result.status, "conflicted");
+        assert_eq!(result.conflicts, vec!["a.txt".to_string()]);
+        rebase_abort(repo.path()).unwrap();
+        assert_eq!(repo.rev_parse("HEAD"), head);
+        assert_eq!(repo.git(&["rev-parse", "--abbrev-ref", "HEAD"]), "main");
+
+        let result = start(repo.path(), "other", None, plan(repo.path(), "other", None).unwrap(), false).unwrap();
+        assert_eq!(result.status, "conflicted");
+        assert!(rebase_continue(repo.path()).is_err());
+        repo.write("a.txt", "resolved\n");
+        repo.git(&["add", "a.txt"]);
+        let result = rebase_continue(repo.path()).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(subjects(&repo, "other..HEAD"), "main a\nmain b");
+        assert!(!repo.dir().join(".git/rebase-merge").exists());
+    }
+
+    #[test]
+    fn drops_picks_already_in_upstream() {
+        let repo = TempRepo::new("irebase-applied");
+        repo.write("a.txt", "base\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("b.txt", "b\n");
+        let applied = repo.commit("add b");
+        repo.commit("intentionally empty");
+        repo.write("c.txt", "c\n");
+        repo.commit("add c");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("d.txt", "d\n");
+        repo.commit("main d");
+        repo.git(&["cherry-pick", &applied]);
+        repo.git(&["checkout", "-q", "topic"]);
+
+        let result = start(repo.path(), "main", None, plan(repo.path(), "main", None).unwrap(), false).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(result.dropped, vec![applied]);
+        assert_eq!(subjects(&repo, "main..HEAD"), "intentionally empty\nadd c");
+    }
+}
@@ -0,0 +1,392 @@
+use std::fs;
+
+use git2::{build::CheckoutBuilder, AnnotatedCommit, Oid, Repository, RepositoryState};
+
+use super::{ensure_clean_state, head_commit, open, reset_merge, signature};
+use crate::models::*;
+
+/// 合并指定版本到当前分支
+///
+/// `policy` 为空时按 `merge.ff` 配置决定是否快进。出现冲突时仓库停留在合并状态，
+/// 需要通过 `merge_continue` 或 `merge_abort` 结束。
+pub fn merge(
+    path: &str,
+    source: &str,
+    policy: Option<MergePolicy>,
+    message: Option<&str>,
+) -> Result<MergeResult, String> {
+    let repo = open(path)?;
+    ensure_clean_state(&repo)?;
+
+    let (their, source_desc) = resolve_merge_source(&repo, source)?;
+    let policy = policy.unwrap_or_else(|| configured_policy(&repo));
+
+    let (analysis, _) = repo.merge_analysis(&[&their])
+        .map_err(|e| format!("合并分析失败: {}", e))?;
+
+    if analysis.is_up_to_date() {
+        return Ok(MergeResult {
+            status: "up_to_date".to_string(),
+            commit_id: None,
+            conflicts: Vec::new(),
+        });
+    }
+
+    if analysis.is_unborn() || (analysis.is_fast_forward() && policy != MergePolicy::NoFf) {
+        fast_forward(&repo, their.id(), source)?;
+        return Ok(MergeResult {
+            status: "fast_forward".to_string(),
+            commit_id: Some(their.id().to_string()),
+            conflicts: Vec::new(),
+        });
+    }
+
+    if policy == MergePolicy::FfOnly {
+        return Err("无法快进，已按 ff-only 策略取消合并".to_string());
+    }
+
+    let head = head_commit(&repo)?;
+    let their_commit = repo.find_commit(their.id())
+        .map_err(|e| format!("无法找到提交: {}", e))?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe().allow_conflicts(true).conflict_style_merge(true);
+    if let Err(e) = repo.merge(&[&their], None, Some(&mut checkout)) {
+        let _ = repo.cleanup_state();
+        return Err(format!("合并失败: {}", e));
+    }
+    write_orig_head(&repo, head.id())?;
+
+    let message = match message {
+        Some(msg) if !msg.trim().is_empty() => msg.trim().to_string(),
+        _ => default_merge_message(&repo, &source_desc),
+    };
+
+    let mut index = repo.index()
+        .map_err(|e| format!("无法读取索引: {}", e))?;
+    if index.has_conflicts() {
+        let conflicts = conflicted_paths(&index)?;
+        let mut merge_msg = format!("{}\n\n# Conflicts:\n", message);
+        for path in &conflicts {
+            merge_msg.push_str(&format!("#\t{}\n", path));
+        }
+        fs::write(repo.path().join("MERGE_MSG"), merge_msg)
+            .map_err(|e| format!("写入 MERGE_MSG 失败: {}", e))?;
+
+        return Ok(MergeResult {
+            status: "conflicted".to_string(),
+            commit_id: None,
+            conflicts,
+        });
+    }
+
+    let tree_id = index.write_tree()
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let commit_id = commit_merge(&repo, tree_id, &[head.id(), their_commit.id()], &message)?;
+
+    Ok(MergeResult {
+        status: "merged".to_string(),
+        commit_id: Some(commit_id.to_string()),
+        conflicts: Vec::new(),
+    })
+}
+
+/// 解决冲突后继续合并，创建合并提交
+pub fn merge_continue(path: &str, message: Option<&str>) -> Result<String, String> {
+    let mut repo = open(path)?;
+    if repo.state() != RepositoryState::Merge {
+        return Err("当前没有进行中的合并".to_string());
+    }
+
+    let mut index = repo.index()
+        .map_err(|e| format!("无法读取索引: {}", e))?;
+    if index.has_conflicts() {
+        let conflicts = conflicted_paths(&index)?;
+        return Err(format!("仍有未解决的冲突: {}", conflicts.join(", ")));
+    }
+
+    let message = match message {
+        Some(msg) if !msg.trim().is_empty() => msg.trim().to_string(),
+        _ => read_merge_msg(&repo)?,
+    };
+
+    let mut parents = vec![head_commit(&repo)?.id()];
+    repo.mergehead_foreach(|oid| {
+        parents.push(*oid);
+        true
+    }).map_err(|e| format!("读取 MERGE_HEAD 失败: {}", e))?;
+
+    let tree_id = index.write_tree()
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let commit_id = commit_merge(&repo, tree_id, &parents, &message)?;
+
+    Ok(commit_id.to_string())
+}
+
+/// 放弃进行中的合并
+pub fn merge_abort(path: &str) -> Result<(), String> {
+    let repo = open(path)?;
+    if repo.state() != RepositoryState::Merge {
+        return Err("当前没有进行中的合并".to_string());
+    }
+
+    reset_merge(&repo)?;
+    repo.cleanup_state()
+        .map_err(|e| format!("清理合并状态失败: {}", e))
+}
+
+/// 解析合并来源，同时返回用于合并信息的描述（如 `branch 'feature'`）
+fn resolve_merge_source<'r>(
+    repo: &'r Repository,
+    source: &str,
+) -> Result<(AnnotatedCommit<'r>, String), String> {
+    if let Ok(reference) = repo.resolve_reference_from_short_name(source) {
+        let shorthand = reference.shorthand().unwrap_or(source).to_string();
+        let desc = if reference.is_branch() {
+            format!("branch '{}'", shorthand)
+        } else if reference.is_remote() {
+            format!("remote-tracking branch '{}'", shorthand)
+        } else if reference.is_tag() {
+            format!("tag '{}'", shorthand)
+        } else {
+            format!("commit '{}'", source)
+        };
+        let annotated = repo.reference_to_annotated_commit(&reference)
+            .map_err(|e| format!("无法解析版本 {}: {}", source, e))?;
+        return Ok((annotated, desc));
+    }
+
+    let commit = repo.revparse_single(source)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("无法解析版本 {}: {}", source, e))?;
+    let annotated = repo.find_annotated_commit(commit.id())
+        .map_err(|e| format!("无法解析版本 {}: {}", source, e))?;
+    Ok((annotated, format!("commit '{}'", source)))
+}
+
+/// 读取 `merge.ff` 配置：false 表示总是创建合并提交，only 表示只允许快进
+fn configured_policy(repo: &Repository) -> MergePolicy {
+    let value = repo.config()
+        .and_then(|config| config.get_string("merge.ff"))
+        .unwrap_or_default();
+    match value.to_lowercase().as_str() {
+        "false" | "no" | "off" | "0" => MergePolicy::NoFf,
+        "only" => MergePolicy::FfOnly,
+        _ => MergePolicy::Ff,
+    }
+}
+
+/// 生成与 git 一致的默认合并信息，合并到 `merge.suppressDest` 中的分支时省略 "into"
+fn default_merge_message(repo: &Repository, source_desc: &str) -> String {
+    let branch = repo.head().ok()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand().map(|s| s.to_string()));
+
+    let mut suppress = vec!["master".to_string()];
+    if let Ok(config) = repo.config() {
+        if let Ok(mut entries) = config.multivar("merge.suppressdest", None) {
+            let mut configured = Vec::new();
+            while let Some(Ok(entry)) = entries.next() {
+                match entry.value() {
+                    Some("") | None => configured.clear(),
+                    Some(value) => configured.push(value.to_string()),
+                }
+            }
+            if !configured.is_empty() {
+                suppress = configured;
+            }
+        }
+    }
+
+    match branch {
+        Some(branch) if !suppress.contains(&branch) => {
+            format!("Merge {} into {}", source_desc, branch)
+        }
+        _ => format!("Merge {}", source_desc),
+    }
+}
+
+fn fast_forward(repo: &Repository, target: Oid, source: &str) -> Result<(), String> {
+    let commit = repo.find_commit(target)
+        .map_err(|e| format!("无法找到提交: {}", e))?;
+    let reflog = format!("merge {}: Fast-forward", source);
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe();
+    repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+        .map_err(|e| format!("检出失败: {}", e))?;
+
+    match repo.head() {
+        Ok(mut head) => {
+            if let Some(old) = head.target() {
+                write_orig_head(repo, old)?;
+            }
+            if head.is_branch() {
+                head.set_target(target, &reflog)
+                    .map_err(|e| format!("更新分支失败: {}", e))?;
+            } else {
+                repo.set_head_detached(target)
+                    .map_err(|e| format!("更新 HEAD 失败: {}", e))?;
+            }
+        }
+        Err(_) => {
+            // 空仓库：HEAD 指向尚不存在的分支
+            let head_ref = repo.find_reference("HEAD")
+                .map_err(|e| format!("无法获取 HEAD: {}", e))?;
+            let branch = head_ref.symbolic_target()
+                .ok_or_else(|| "无法获取 HEAD".to_string())?
+                .to_string();
+            repo.reference(&branch, target, true, &reflog)
+                .map_err(|e| format!("更新分支失败: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn commit_merge(
+    repo: &Repository,
+    tree_id: Oid,
+    parent_ids: &[Oid],
+    message: &str,
+) -> Result<Oid, String> {
+    let signature = signature(repo)?;
+    let tree = repo.find_tree(tree_id)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let parents = parent_ids.iter()
+        .map(|id| repo.find_commit(*id))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("无法找到提交: {}", e))?;
+    let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+
+    let commit_id = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &format!("{}\n", message.trim_end()),
+        &tree,
+        &parent_refs,
+    ).map_err(|e| format!("创建合并提交失败: {}", e))?;
+
+    repo.cleanup_state()
+        .map_err(|e| format!("清理合并状态失败: {}", e))?;
+    Ok(commit_id)
+}
+
+fn read_merge_msg(repo: &Repository) -> Result<String, String> {
+    let raw = fs::read_to_string(repo.path().join("MERGE_MSG")).unwrap_or_default();
+    let message = git2::message_prettify(raw, Some(b'#'))
+        .map_err(|e| format!("读取 MERGE_MSG 失败: {}", e))?;
+    if message.trim().is_empty() {
+        return Err("合并信息不能为空".to_string());
+    }
+    Ok(message)
+}
+
+pub(crate) fn write_orig_head(repo: &Repository, oid: Oid) -> Result<(), String> {
+    fs::write(repo.path().join("ORIG_HEAD"), format!("{}\n", oid))
+        .map_err(|e| format!("写入 ORIG_HEAD 失败: {}", e))
+}
+
+pub(crate) fn conflicted_paths(index: &git2::Index) -> Result<Vec<String>, String> {
+    let conflicts = index.conflicts()
+        .map_err(|e| format!("读取冲突失败: {}", e))?;
+    let mut paths = Vec::new();
+    for conflict in conflicts {
+        let conflict = conflict.map_err(|e| format!("读取冲突失败: {}", e))?;
+        let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+        if let Some(entry) = entry {
+            paths.push(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// main 与 topic 都修改了 a.txt
+    fn conflicting_branches() -> TempRepo {
+        let repo = TempRepo::new("merge");
+        repo.write("a.txt", "base\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("a.txt", "topic\n");
+        repo.commit("topic");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("a.txt", "main\n");
+        repo.commit("main");
+        repo
+    }
+
+    #[test]
+    fn fast_forward_follows_policy() {
+        let repo = TempRepo::new("merge-ff");
+        repo.write("a.txt", "base\n");
+        let base = repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("b.txt", "topic\n");
+        let topic = repo.commit("topic");
+        repo.git(&["checkout", "-q", "main"]);
+
+        let result = merge(repo.path(), "topic", Some(MergePolicy::NoFf), None).unwrap();
+        assert_eq!(result.status, "merged");
+        assert_eq!(repo.git(&["log", "-1", "--format=%P"]), format!("{} {}", base, topic));
+
+        repo.git(&["reset", "-q", "--hard", &base]);
+        let result = merge(repo.path(), "topic", None, None).unwrap();
+        assert_eq!((result.status.as_str(), result.commit_id), ("fast_forward", Some(topic)));
+
+        let result = merge(repo.path(), "topic", None, None).unwrap();
+        assert_eq!(result.status, "up_to_date");
+    }
+
+    #[test]
+    fn ff_only_refuses_divergent_branches() {
+        let repo = conflicting_branches();
+        let head = repo.rev_parse("HEAD");
+        assert!(merge(repo.path(), "topic", Some(MergePolicy::FfOnly), None).is_err());
+        assert_eq!(repo.rev_parse("HEAD"), head);
+        assert_eq!(repo.git(&["status", "--porcelain"]), "");
+    }
+
+    #[test]
+    fn conflict_then_continue() {
+        let repo = conflicting_branches();
+        let head = repo.rev_parse("HEAD");
+        let topic = repo.rev_parse("topic");
+
+        let result = merge(repo.path(), "topic", None, None).unwrap();
+        assert_eq!(result.status, "conflicted");
+        assert_eq!(result.conflicts, vec!["a.txt".to_string()]);
+        assert!(merge_continue(repo.path(), None).is_err());
+
+        repo.write("a.txt", "resolved\n");
+        repo.git(&["add", "a.txt"]);
+        let commit_id = merge_continue(repo.path(), None).unwrap();
+        assert_eq!(repo.rev_parse("HEAD"), commit_id);
+        assert_eq!(repo.git(&["log", "-1", "--format=%P"]), format!("{} {}", head, topic));
+        assert_eq!(repo.git(&["log", "-1", "--format=%s"]), "Merge branch 'topic' into main");
+        assert_eq!(open(repo.path()).unwrap().state(), RepositoryState::Clean);
+    }
+
+    #[test]
+    fn conflict_then_abort_keeps_local_changes() {
+        let repo = conflicting_branches();
+        repo.write("local.txt", "tracked\n");
+        let head = repo.commit("local");
+        repo.write("local.txt", "dirty\n");
+
+        let result = merge(repo.path(), "topic", None, None).unwrap();
+        assert_eq!(result.status, "conflicted");
+        merge_abort(repo.path()).unwrap();
+
+        assert_eq!(repo.rev_parse("HEAD"), head);
+        assert_eq!(repo.read("a.txt").unwrap(), "main\n");
+        assert_eq!(repo.read("local.txt").unwrap(), "dirty\n");
+        assert_eq!(open(repo.path()).unwrap().state(), RepositoryState::Clean);
+        assert!(merge_abort(repo.path()).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use git2::{Commit, Diff, DiffOptions, Oid, Repository};
+use regex::bytes::{Regex, RegexBuilder};
+
+use super::{commit_info, open};
+use crate::models::*;
+
+/// 积攒到这么多结果或距上次发送超过该时间时把结果交给 `on_matches`
+const REPORT_BATCH: usize = 50;
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 在历史中查找内容发生变化的提交（`git log -S` / `git log -G`）
+///
+/// 需要比较每个提交的 diff，结果通过 `on_matches` 分批返回；`cancel` 被设置后在下一个提交前停止。
+/// 与 git 相同，合并提交不参与比较。
+pub fn search(
+    path: &str,
+    options: &PickaxeOptions,
+    cancel: &AtomicBool,
+    on_matches: &dyn Fn(Vec<PickaxeMatch>),
+) -> Result<PickaxeSummary, String> {
+    if options.pattern.is_empty() {
+        return Err("搜索内容不能为空".to_string());
+    }
+    let pattern = match options.kind {
+        PickaxeKind::String => regex::escape(&options.pattern),
+        PickaxeKind::Regex => options.pattern.clone(),
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .map_err(|e| format!("无效的正则表达式 {}: {}", options.pattern, e))?;
+
+    let repo = open(path)?;
+    let rev = options.rev.as_deref().unwrap_or("HEAD");
+    let start = repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("无法解析版本 {}: {}", rev, e))?;
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("无法创建 revwalk: {}", e))?;
+    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)
+        .map_err(|e| format!("设置排序失败: {}", e))?;
+    revwalk.push(start.id())
+        .map_err(|e| format!("无法推送起点: {}", e))?;
+
+    let mut summary = PickaxeSummary {
+        scanned: 0,
+        matched: 0,
+        cancelled: false,
+    };
+    let mut batch = Vec::new();
+    let mut reported = Instant::now();
+    for oid in revwalk {
+        if cancel.load(Ordering::Relaxed) {
+            summary.cancelled = true;
+            break;
+        }
+        if options.max_count.is_some_and(|max| summary.matched >= max) {
+            break;
+        }
+
+        let oid = oid.map_err(|e| format!("遍历提交失败: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("无法读取提交 {}: {}", oid, e))?;
+        summary.scanned += 1;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+
+        let diff = commit_diff(&repo, &commit, &options.paths)?;
+        let paths = match options.kind {
+            PickaxeKind::String => changed_occurrences(&repo, &diff, &regex)?,
+            PickaxeKind::Regex => matching_lines(&diff, &regex)?,
+        };
+        if !paths.is_empty() {
+            summary.matched += 1;
+            batch.push(PickaxeMatch {
+                commit: commit_info(&commit),
+                paths,
+            });
+        }
+
+        if !batch.is_empty() && (batch.len() >= REPORT_BATCH || reported.elapsed() >= REPORT_INTERVAL) {
+            on_matches(std::mem::take(&mut batch));
+            reported = Instant::now();
+        }
+    }
+    if !batch.is_empty() {
+        on_matches(batch);
+    }
+
+    Ok(summary)
+}
+
+/// 提交相对父提交（根提交相对空树）的 diff
+fn commit_diff<'r>(repo: &'r Repository, commit: &Commit, paths: &[String]) -> Result<Diff<'r>, String> {
+    let tree = commit.tree()
+        .map_err(|e| format!("无法获取树: {}", e))?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().map_err(|e| format!("无法获取树: {}", e))?),
+        Err(_) => None,
+    };
+
+    let mut diff_opts = DiffOptions::new();
+    for path in paths {
+        diff_opts.pathspec(path);
+    }
+    repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))
+        .map_err(|e| format!("创建 diff 失败: {}", e))
+}
+
+/// `-S`：修改前后出现次数不同的文件
+fn changed_occurrences(repo: &Repository, diff: &Diff, regex: &Regex) -> Result<Vec<String>, String> {
+    let count = |id: Oid| -> Result<usize, String> {
+        if id.is_zero() {
+            return Ok(0);
+        }
+        let blob = repo.find_blob(id)
+            .map_err(|e| format!("无法读取文件内容 {}: {}", id, e))?;
+        Ok(regex.find_iter(blob.content()).count())
+    };
+
+    let mut paths = Vec::new();
+    for delta in diff.deltas() {
+        if count(delta.old_file().id())? != count(delta.new_file().id())? {
+            if let Some(path) = delta.new_file().path().or(delta.old_file().path()) {
+                paths.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// `-G`：新增或删除的行匹配正则的文件
+fn matching_lines(diff: &Diff, regex: &Regex) -> Result<Vec<String>, String> {
+    let mut paths = BTreeSet::new();
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        None,
+        Some(&mut |delta, _, line| {
+            if matches!(line.origin(), '+' | '-') && regex.is_match(line.content()) {
+                if let Some(path) = delta.new_file().path().or(delta.old_file().path()) {
+                    paths.insert(path.to_string_lossy().to_string());
+                }
+            }
+            true
+        }),
+    ).map_err(|e| format!("读取 diff 失败: {}", e))?;
+
+    Ok(paths.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// a.txt 中 needle 被加入、所在行被修改、最后被删除，b.txt 加入大写的 Needle
+    fn needle_history() -> TempRepo {
+        let repo = TempRepo::new("pickaxe");
+        repo.write("a.txt", "hello\n");
+        repo.write("b.txt", "x\n");
+        repo.commit("initial");
+        repo.write("a.txt", "hello\nneedle here\n");
+        repo.commit("add needle");
+        repo.write("a.txt", "hello\nneedle there\n");
+        repo.commit("edit line");
+        repo.write("b.txt", "Needle\n");
+        repo.commit("b needle");
+        repo.write("a.txt", "hello\n");
+        repo.commit("remove needle");
+        repo
+    }
+
+    /// 执行搜索，返回按顺序找到的提交标题
+    fn run(repo: &TempRepo, options: PickaxeOptions) -> (Vec<String>, PickaxeSummary) {
+        let found = RefCell::new(Vec::new());
+        let summary = search(repo.path(), &options, &AtomicBool::new(false), &|matches| {
+            found.borrow_mut().extend(matches.into_iter().map(|m| m.commit.summary));
+        })
+        .unwrap();
+        (found.into_inner(), summary)
+    }
+
+    fn options(kind: PickaxeKind, pattern: &str) -> PickaxeOptions {
+        PickaxeOptions { kind, pattern: pattern.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn string_search_matches_changed_occurrence_count() {
+        let repo = needle_history();
+
+        // -S 只列出出现次数变化的提交，修改所在行但次数不变的提交不算
+        let (found, summary) = run(&repo, options(PickaxeKind::String, "needle"));
+        assert_eq!(found, vec!["remove needle", "add needle"]);
+        assert_eq!((summary.scanned, summary.matched, summary.cancelled), (5, 2, false));
+
+        let (found, _) = run(&repo, PickaxeOptions { ignore_case: true, ..options(PickaxeKind::String, "needle") });
+        assert_eq!(found, vec!["remove needle", "b needle", "add needle"]);
+
+        // 按字面匹配，不解释正则元字符
+        let (found, _) = run(&repo, options(PickaxeKind::String, "needle t.ere"));
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn regex_search_matches_changed_lines() {
+        let repo = needle_history();
+
+        // -G 列出增删行匹配的提交，包括只修改了所在行的提交
+        let (found, _) = run(&repo, options(PickaxeKind::Regex, "needle"));
+        assert_eq!(found, vec!["remove needle", "edit line", "add needle"]);
+
+        let (found, _) = run(&repo, options(PickaxeKind::Regex, "needle t.ere"));
+        assert_eq!(found, vec!["remove needle", "edit line"]);
+
+        assert!(search(repo.path(), &options(PickaxeKind::Regex, "("), &AtomicBool::new(false), &|_| {}).is_err());
+        assert!(search(repo.path(), &options(PickaxeKind::String, ""), &AtomicBool::new(false), &|_| {}).is_err());
+    }
+
+    #[test]
+    fn limits_paths_count_and_cancels() {
+        let repo = needle_history();
+
+        let only_b = PickaxeOptions { ignore_case: true, paths: vec!["b.txt".to_string()], ..options(PickaxeKind::String, "needle") };
+        assert_eq!(run(&repo, only_b).0, vec!["b needle"]);
+
+        let first = PickaxeOptions { max_count: Some(1), ..options(PickaxeKind::String, "needle") };
+        assert_eq!(run(&repo, first).0, vec!["remove needle"]);
+
+        let from = PickaxeOptions { rev: Some("HEAD~2".to_string()), ..options(PickaxeKind::Regex, "needle") };
+        assert_eq!(run(&repo, from).0, vec!["edit line", "add needle"]);
+
+        let summary = search(repo.path(), &options(PickaxeKind::String, "needle"), &AtomicBool::new(true), &|_| {}).unwrap();
+        assert!(summary.cancelled);
+        assert_eq!(summary.scanned, 0);
+    }
+}
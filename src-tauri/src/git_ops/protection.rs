@@ -0,0 +1,382 @@
+use git2::{Oid, Repository};
+
+use super::open;
+use super::reflog::full_ref_name;
+use super::reset::{backup_entry, find_backup};
+use super::sync::{pull_strategy, push_destination, tracking_ref_of};
+use crate::models::*;
+
+/// 分支名是否匹配受保护分支规则，规则中的 `*` 匹配任意字符（如 `release/*`）
+pub fn is_protected(patterns: &[String], branch: &str) -> bool {
+    patterns.iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .any(|pattern| glob_match(pattern, branch))
+}
+
+/// 重置到 `target` 会改写的受保护分支：当前分支受保护且目标不包含当前提交时返回分支名
+pub fn check_reset(path: &str, target: &str, patterns: &[String]) -> Result<Option<String>, String> {
+    let repo = open(path)?;
+    let target = resolve_commit(&repo, target)?;
+    check_head_move(&repo, target, patterns)
+}
+
+/// 变基到 `onto`（为空时为 `upstream`）会改写的受保护分支
+///
+/// 当前提交已包含在目标中（快进）或已基于目标（无需变基）时不视为改写。
+pub fn check_rebase(
+    path: &str,
+    upstream: &str,
+    onto: Option<&str>,
+    patterns: &[String],
+) -> Result<Option<String>, String> {
+    let repo = open(path)?;
+    let Some((branch, head)) = current_branch(&repo) else { return Ok(None) };
+    if !is_protected(patterns, &branch) {
+        return Ok(None);
+    }
+    let upstream_id = resolve_commit(&repo, upstream)?;
+    let onto_id = match onto {
+        Some(onto) => resolve_commit(&repo, onto)?,
+        None => upstream_id,
+    };
+    let based = onto_id == upstream_id && !rewrites(&repo, upstream_id, head);
+    Ok((rewrites(&repo, head, onto_id) && !based).then_some(branch))
+}
+
+/// 交互式变基会改写的受保护分支，除快进外的计划都可能修改提交，均视为改写
+pub fn check_interactive_rebase(
+    path: &str,
+    upstream: &str,
+    onto: Option<&str>,
+    patterns: &[String],
+) -> Result<Option<String>, String> {
+    check_reset(path, onto.unwrap_or(upstream), patterns)
+}
+
+/// 恢复引用日志记录会改写的受保护分支
+pub fn check_reflog_restore(
+    path: &str,
+    reference: Option<&str>,
+    index: usize,
+    patterns: &[String],
+) -> Result<Option<String>, String> {
+    let repo = open(path)?;
+    let name = full_ref_name(&repo, reference.unwrap_or("HEAD"));
+    let reflog = repo.reflog(&name)
+        .map_err(|e| format!("读取引用日志失败: {}", e))?;
+    let Some(entry) = reflog.get(index) else { return Ok(None) };
+    let target = entry.id_new();
+
+    if name == "HEAD" {
+        return check_head_move(&repo, target, patterns);
+    }
+    let Some(branch) = name.strip_prefix("refs/heads/") else { return Ok(None) };
+    if !is_protected(patterns, branch) {
+        return Ok(None);
+    }
+    let current = repo.refname_to_id(&name)
+        .map_err(|e| format!("无法找到引用 {}: {}", name, e))?;
+    Ok(rewrites(&repo, current, target).then_some(branch.to_string()))
+}
+
+/// 恢复备份会改写的受保护分支：备份记录的分支受保护且备份时的 HEAD 不包含该分支当前提交时返回分支名
+pub fn check_backup_restore(path: &str, name: &str, patterns: &[String]) -> Result<Option<String>, String> {
+    let repo = open(path)?;
+    let commit = find_backup(&repo, name)?;
+    let entry = backup_entry(name, &commit)
+        .ok_or_else(|| format!("无效的备份: {}", name))?;
+    let Some(reference) = entry.branch else { return Ok(None) };
+    let branch = reference.trim_start_matches("refs/heads/");
+    if !is_protected(patterns, branch) {
+        return Ok(None);
+    }
+    // 分支已被删除时恢复只会重新创建它
+    let Ok(current) = repo.refname_to_id(&reference) else { return Ok(None) };
+    let target = Oid::from_str(&entry.previous_head)
+        .map_err(|e| format!("无效的备份: {}", e))?;
+    Ok(rewrites(&repo, current, target).then_some(branch.to_string()))
+}
+
+/// 拉取会改写的受保护分支
+///
+/// 按与 `pull` 相同的规则确定整合方式，只有变基会改写本地分支：本地分支有上游没有的提交时
+/// 返回分支名。检查时尚未获取远程更新，按本地记录的上游分支比较，尚未获取过上游时同样视为改写。
+pub fn check_pull(path: &str, strategy: Option<PullStrategy>, patterns: &[String]) -> Result<Option<String>, String> {
+    let repo = open(path)?;
+    let Some((branch, head)) = current_branch(&repo) else { return Ok(None) };
+    if !is_protected(patterns, &branch) {
+        return Ok(None);
+    }
+    if pull_strategy(&repo, &branch, strategy)? != Some(PullStrategy::Rebase) {
+        return Ok(None);
+    }
+    let Some(upstream) = repo.branch_upstream_name(&format!("refs/heads/{}", branch)).ok()
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+    else {
+        return Ok(None);
+    };
+    match repo.refname_to_id(&upstream) {
+        Ok(upstream_id) if !rewrites(&repo, head, upstream_id) => Ok(None),
+        _ => Ok(Some(branch)),
+    }
+}
+
+/// 强制推送会改写的受保护远程分支
+///
+/// 受保护分支只允许带租约的强制推送，不带租约的强制推送直接拒绝。
+pub fn check_force_push(
+    path: &str,
+    remote: Option<&str>,
+    branch: Option<&str>,
+    force: Option<ForcePush>,
+    patterns: &[String],
+) -> Result<Option<String>, String> {
+    let Some(force) = force else { return Ok(None) };
+    let repo = open(path)?;
+    let (local_ref, remote_name, dst) = push_destination(&repo, remote, branch)?;
+    let Some(branch) = dst.strip_prefix("refs/heads/") else { return Ok(None) };
+    if !is_protected(patterns, branch) {
+        return Ok(None);
+    }
+    if force == ForcePush::Force {
+        return Err(format!("分支 {} 受保护，只能使用 --force-with-lease 强制推送", branch));
+    }
+
+    // 远程跟踪分支已包含在本地分支中时推送只是快进，不视为改写
+    let local = repo.refname_to_id(&local_ref)
+        .map_err(|e| format!("无法找到分支 {}: {}", local_ref, e))?;
+    let tracking = tracking_ref_of(&repo, &remote_name, &dst)
+        .and_then(|name| repo.refname_to_id(&name).ok());
+    match tracking {
+        Some(tracking) if !rewrites(&repo, tracking, local) => Ok(None),
+        _ => Ok(Some(branch.to_string())),
+    }
+}
+
+fn check_head_move(repo: &Repository, target: Oid, patterns: &[String]) -> Result<Option<String>, String> {
+    let Some((branch, head)) = current_branch(repo) else { return Ok(None) };
+    if !is_protected(patterns, &branch) {
+        return Ok(None);
+    }
+    Ok(rewrites(repo, head, target).then_some(branch))
+}
+
+fn resolve_commit(repo: &Repository, spec: &str) -> Result<Oid, String> {
+    repo.revparse_single(spec)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id())
+        .map_err(|e| format!("无法解析版本 {}: {}", spec, e))
+}
+
+/// 当前检出的分支短名及其指向的提交，分离 HEAD 或空分支时返回 None
+fn current_branch(repo: &Repository) -> Option<(String, Oid)> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    Some((head.shorthand()?.to_string(), head.target()?))
+}
+
+/// 从 `from` 移动到 `to` 是否会丢弃 `from` 上的提交
+fn rewrites(repo: &Repository, from: Oid, to: Oid) -> bool {
+    from != to && !repo.graph_descendant_of(to, from).unwrap_or(false)
+}
+
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(name) = name.strip_prefix(prefix) else { return false };
+    if rest.is_empty() {
+        return true;
+    }
+    (0..=name.len())
+        .filter(|&i| name.is_char_boundary(i))
+        .any(|i| glob_match(rest, &name[i..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::reset::reset;
+    use crate::git_ops::test_repo::TempRepo;
+
+    fn protected() -> Vec<String> {
+        vec!["main".to_string(), "release/*".to_string()]
+    }
+
+    #[test]
+    fn matches_glob_patterns() {
+        assert!(is_protected(&protected(), "release/1.0"));
+        assert!(!is_protected(&protected(), "feature"));
+        assert!(is_protected(&["*-stable".to_string()], "v1-stable"));
+        assert!(!is_protected(&[" ".to_string()], "main"));
+    }
+
+    #[test]
+    fn reset_guards_moving_protected_head_backwards() {
+        let repo = TempRepo::new("protection-reset");
+        repo.commit("first");
+        repo.commit("second");
+        repo.git(&["checkout", "-q", "-b", "feature"]);
+        repo.commit("feature");
+        repo.git(&["checkout", "-q", "main"]);
+
+        assert_eq!(check_reset(repo.path(), "HEAD~1", &protected()).unwrap().as_deref(), Some("main"));
+        assert_eq!(check_reset(repo.path(), "HEAD", &protected()).unwrap(), None);
+        // 快进到包含当前提交的目标
+        assert_eq!(check_reset(repo.path(), "feature", &protected()).unwrap(), None);
+        assert_eq!(check_reset(repo.path(), "HEAD~1", &["dev".to_string()]).unwrap(), None);
+        assert!(check_reset(repo.path(), "missing", &protected()).is_err());
+
+        repo.git(&["checkout", "-q", "--detach"]);
+        assert_eq!(check_reset(repo.path(), "HEAD~1", &protected()).unwrap(), None);
+    }
+
+    #[test]
+    fn rebase_guards_only_rewriting_rebases() {
+        let repo = TempRepo::new("protection-rebase");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "upstream"]);
+        repo.commit("upstream");
+        repo.git(&["checkout", "-q", "main"]);
+
+        // 上游包含当前提交，变基只是快进
+        assert_eq!(check_rebase(repo.path(), "upstream", None, &protected()).unwrap(), None);
+
+        repo.commit("local");
+        assert_eq!(check_rebase(repo.path(), "upstream", None, &protected()).unwrap().as_deref(), Some("main"));
+        assert_eq!(check_rebase(repo.path(), "upstream", None, &["dev".to_string()]).unwrap(), None);
+
+        // 已基于上游时无需变基
+        assert_eq!(check_rebase(repo.path(), "HEAD~1", None, &protected()).unwrap(), None);
+        // 指定 onto 时即使基于上游也会移动提交
+        assert_eq!(check_rebase(repo.path(), "HEAD~1", Some("upstream"), &protected()).unwrap().as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn interactive_rebase_guards_all_but_fast_forward() {
+        let repo = TempRepo::new("protection-irebase");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "upstream"]);
+        repo.commit("upstream");
+        repo.git(&["checkout", "-q", "main"]);
+
+        assert_eq!(check_interactive_rebase(repo.path(), "upstream", None, &protected()).unwrap(), None);
+
+        repo.commit("local");
+        // 即使已基于上游，计划也可能修改提交
+        assert_eq!(check_interactive_rebase(repo.path(), "HEAD~1", None, &protected()).unwrap().as_deref(), Some("main"));
+        assert_eq!(check_interactive_rebase(repo.path(), "HEAD~1", Some("upstream"), &protected()).unwrap().as_deref(), Some("main"));
+        assert_eq!(check_interactive_rebase(repo.path(), "HEAD~1", None, &["dev".to_string()]).unwrap(), None);
+    }
+
+    #[test]
+    fn reflog_restore_guards_dropped_commits() {
+        let repo = TempRepo::new("protection-reflog");
+        repo.commit("first");
+        repo.commit("second");
+
+        // HEAD 和分支自身的引用日志中 1 号记录都是 first
+        assert_eq!(check_reflog_restore(repo.path(), None, 1, &protected()).unwrap().as_deref(), Some("main"));
+        assert_eq!(check_reflog_restore(repo.path(), Some("main"), 1, &protected()).unwrap().as_deref(), Some("main"));
+        assert_eq!(check_reflog_restore(repo.path(), None, 0, &protected()).unwrap(), None);
+        assert_eq!(check_reflog_restore(repo.path(), None, 99, &protected()).unwrap(), None);
+        assert_eq!(check_reflog_restore(repo.path(), None, 1, &["dev".to_string()]).unwrap(), None);
+
+        // 恢复到重置前的提交只是快进
+        repo.git(&["reset", "-q", "--hard", "HEAD~1"]);
+        assert_eq!(check_reflog_restore(repo.path(), None, 1, &protected()).unwrap(), None);
+
+        // 未检出的分支按其自身的引用日志检查
+        repo.git(&["branch", "dev", "HEAD"]);
+        repo.git(&["branch", "-f", "dev", "HEAD@{1}"]);
+        assert_eq!(check_reflog_restore(repo.path(), Some("dev"), 1, &["dev".to_string()]).unwrap().as_deref(), Some("dev"));
+        assert_eq!(check_reflog_restore(repo.path(), Some("dev"), 1, &protected()).unwrap(), None);
+        repo.git(&["branch", "-f", "dev", "HEAD"]);
+        assert_eq!(check_reflog_restore(repo.path(), Some("dev"), 1, &["dev".to_string()]).unwrap(), None);
+    }
+
+    #[test]
+    fn force_push_requires_lease_and_guards_rewrites() {
+        let remote = TempRepo::bare("protection-push-remote");
+        let repo = TempRepo::clone_of(&remote, "protection-push");
+        repo.commit("initial");
+        repo.git(&["push", "-q", "-u", "origin", "main"]);
+
+        assert_eq!(check_force_push(repo.path(), None, None, None, &protected()).unwrap(), None);
+        assert!(check_force_push(repo.path(), None, None, Some(ForcePush::Force), &protected()).is_err());
+        assert_eq!(check_force_push(repo.path(), None, None, Some(ForcePush::Force), &["dev".to_string()]).unwrap(), None);
+
+        // 远程分支包含在本地分支中，带租约推送只是快进
+        repo.commit("local");
+        assert_eq!(check_force_push(repo.path(), None, None, Some(ForcePush::WithLease), &protected()).unwrap(), None);
+
+        repo.git(&["push", "-q"]);
+        repo.git(&["commit", "-q", "--amend", "--allow-empty", "-m", "rewritten"]);
+        assert_eq!(
+            check_force_push(repo.path(), None, None, Some(ForcePush::WithLease), &protected()).unwrap().as_deref(),
+            Some("main")
+        );
+        assert_eq!(
+            check_force_push(repo.path(), Some("origin"), Some("main"), Some(ForcePush::WithLease), &protected()).unwrap().as_deref(),
+            Some("main")
+        );
+    }
+
+    #[test]
+    fn pull_only_guards_rebase_over_local_commits() {
+        let remote = TempRepo::bare("protection-remote");
+        let repo = TempRepo::clone_of(&remote, "protection-pull");
+        repo.write("a.txt", "a\n");
+        repo.commit("initial");
+        repo.git(&["push", "-q", "-u", "origin", "main"]);
+
+        // 本地没有上游之外的提交时变基不会改写分支
+        assert_eq!(check_pull(repo.path(), Some(PullStrategy::Rebase), &protected()).unwrap(), None);
+
+        repo.write("a.txt", "local\n");
+        repo.commit("local");
+        assert_eq!(check_pull(repo.path(), Some(PullStrategy::Rebase), &protected()).unwrap().as_deref(), Some("main"));
+        assert_eq!(check_pull(repo.path(), Some(PullStrategy::Merge), &protected()).unwrap(), None);
+        assert_eq!(check_pull(repo.path(), Some(PullStrategy::Rebase), &["dev".to_string()]).unwrap(), None);
+
+        // 未指定方式时按配置决定
+        assert_eq!(check_pull(repo.path(), None, &protected()).unwrap(), None);
+        repo.git(&["config", "pull.rebase", "true"]);
+        assert_eq!(check_pull(repo.path(), None, &protected()).unwrap().as_deref(), Some("main"));
+
+        // 尚未获取过上游时同样视为改写
+        repo.git(&["update-ref", "-d", "refs/remotes/origin/main"]);
+        assert_eq!(check_pull(repo.path(), None, &protected()).unwrap().as_deref(), Some("main"));
+
+        repo.git(&["branch", "--unset-upstream"]);
+        assert_eq!(check_pull(repo.path(), None, &protected()).unwrap(), None);
+    }
+
+    #[test]
+    fn backup_restore_guards_dropped_commits() {
+        let repo = TempRepo::new("protection-backup");
+        repo.write("a.txt", "first\n");
+        repo.commit("first");
+        repo.write("a.txt", "second\n");
+        repo.commit("second");
+        let backup = reset(repo.path(), "HEAD~1", ResetMode::Hard).unwrap().backup.unwrap();
+
+        // 恢复只是把分支快进回备份时的提交
+        assert_eq!(check_backup_restore(repo.path(), &backup.name, &protected()).unwrap(), None);
+
+        repo.write("a.txt", "third\n");
+        repo.commit("third");
+        assert_eq!(check_backup_restore(repo.path(), &backup.name, &protected()).unwrap().as_deref(), Some("main"));
+        assert_eq!(check_backup_restore(repo.path(), &backup.name, &["dev".to_string()]).unwrap(), None);
+
+        // 分支已删除时恢复只会重新创建它
+        repo.git(&["checkout", "-q", "-b", "other"]);
+        repo.git(&["branch", "-q", "-D", "main"]);
+        assert_eq!(check_backup_restore(repo.path(), &backup.name, &protected()).unwrap(), None);
+
+        assert!(check_backup_restore(repo.path(), "refs/heads/other", &protected()).is_err());
+    }
+}
@@ -0,0 +1,390 @@
+use std::fs;
+
+use git2::{ErrorCode, Oid, Rebase, RebaseOptions, Repository, Signature};
+
+use super::interactive_rebase;
+use super::merge::conflicted_paths;
+use super::{ensure_clean_state, has_local_changes, open, reset_merge, signature};
+use crate::models::*;
+
+pub(super) const AUTOSTASH_FILE: &str = "autostash";
+
+/// 将当前分支变基到 `upstream` 上（`onto` 不为空时等价于 `git rebase --onto <onto> <upstream>`）
+///
+/// 遇到冲突时停下并保留 `.git/rebase-merge` 中的状态，之后可通过
+/// `rebase_continue`、`rebase_skip`、`rebase_abort` 继续处理，应用重启后同样有效。
+pub fn rebase(
+    path: &str,
+    upstream: &str,
+    onto: Option<&str>,
+    autostash: bool,
+) -> Result<RebaseResult, String> {
+    let mut repo = open(path)?;
+    ensure_clean_state(&repo)?;
+
+    let upstream_id = resolve_commit(&repo, upstream)?;
+    let onto_id = match onto {
+        Some(onto) => Some(resolve_commit(&repo, onto)?),
+        None => None,
+    };
+
+    let stash_id = if has_local_changes(&repo)? {
+        if !autostash {
+            return Err("工作区有未提交的修改，请先提交或启用自动贮藏".to_string());
+        }
+        let signature = signature(&repo)?;
+        let oid = repo.stash_save2(&signature, Some("autostash"), None)
+            .map_err(|e| format!("自动贮藏失败: {}", e))?;
+        Some(oid)
+    } else {
+        None
+    };
+
+    let upstream_commit = repo.find_annotated_commit(upstream_id)
+        .map_err(|e| format!("无法解析版本 {}: {}", upstream, e))?;
+    let onto_commit = match onto_id {
+        Some(onto_id) => Some(repo.find_annotated_commit(onto_id)
+            .map_err(|e| format!("无法解析版本: {}", e))?),
+        None => None,
+    };
+
+    let signature = signature(&repo)?;
+    let mut opts = RebaseOptions::new();
+    let mut rebase = match repo.rebase(None, Some(&upstream_commit), onto_commit.as_ref(), Some(&mut opts)) {
+        Ok(rebase) => rebase,
+        Err(e) => {
+            let message = format!("无法开始变基: {}", e);
+            if let Some(stash_id) = stash_id {
+                let _ = pop_autostash(&repo, stash_id);
+            }
+            return Err(message);
+        }
+    };
+
+    if let Some(stash_id) = stash_id {
+        fs::write(state_dir(&repo).join(AUTOSTASH_FILE), format!("{}\n", stash_id))
+            .map_err(|e| format!("写入变基状态失败: {}", e))?;
+    }
+
+    run(&repo, &mut rebase, &signature)
+}
+
+/// 解决冲突后继续变基
+pub fn rebase_continue(path: &str) -> Result<RebaseResult, String> {
+    let repo = open(path)?;
+    if interactive_rebase::is_in_progress(&repo) {
+        return interactive_rebase::resume(&repo);
+    }
+    let signature = signature(&repo)?;
+    let mut rebase = open_rebase(&repo)?;
+
+    let index = repo.index()
+        .map_err(|e| format!("无法读取索引: {}", e))?;
+    if index.has_conflicts() {
+        let conflicts = conflicted_paths(&index)?;
+        return Err(format!("仍有未解决的冲突: {}", conflicts.join(", ")));
+    }
+
+    let mut dropped = Vec::new();
+    if let Some(current) = rebase.operation_current() {
+        let commit_id = rebase.nth(current).map(|operation| operation.id());
+        if !commit_current(&mut rebase, &signature)? {
+            dropped.extend(commit_id.map(|id| id.to_string()));
+        }
+    }
+
+    let mut result = run(&repo, &mut rebase, &signature)?;
+    dropped.append(&mut result.dropped);
+    result.dropped = dropped;
+    Ok(result)
+}
+
+/// 跳过当前提交并继续变基
+pub fn rebase_skip(path: &str) -> Result<RebaseResult, String> {
+    let repo = open(path)?;
+    if interactive_rebase::is_in_progress(&repo) {
+        return interactive_rebase::skip(&repo);
+    }
+    let signature = signature(&repo)?;
+    let mut rebase = open_rebase(&repo)?;
+
+    // 不能使用 hard reset，它会清除 .git/rebase-merge 中的变基状态
+    reset_merge(&repo)?;
+
+    run(&repo, &mut rebase, &signature)
+}
+
+/// 放弃变基，恢复到变基前的分支
+pub fn rebase_abort(path: &str) -> Result<Option<String>, String> {
+    let repo = open(path)?;
+    if interactive_rebase::is_in_progress(&repo) {
+        return interactive_rebase::abort(&repo);
+    }
+    let mut rebase = open_rebase(&repo)?;
+    let stash_id = read_autostash(&repo);
+
+    rebase.abort()
+        .map_err(|e| format!("放弃变基失败: {}", e))?;
+
+    match stash_id {
+        Some(stash_id) => pop_autostash(&repo, stash_id),
+        None => Ok(None),
+    }
+}
+
+/// 读取进行中的变基进度，没有变基时返回 None
+pub fn read_progress(repo: &Repository) -> Option<RebaseProgress> {
+    let merge_dir = repo.path().join("rebase-merge");
+    let apply_dir = repo.path().join("rebase-apply");
+
+    let (dir, current_file, total_file) = if merge_dir.is_dir() {
+        (merge_dir, "msgnum", "end")
+    } else if apply_dir.is_dir() {
+        (apply_dir, "next", "last")
+    } else {
+        return None;
+    };
+
+    let read = |name: &str| {
+        fs::read_to_string(dir.join(name))
+            .map(|s| s.trim().to_string())
+            .ok()
+    };
+
+    let head_name = read("head-name").unwrap_or_default();
+    let head_name = head_name.strip_prefix("refs/heads/")
+        .unwrap_or(&head_name)
+        .to_string();
+    let onto = read("onto_name")
+        .or_else(|| read("onto"))
+        .unwrap_or_default();
+
+    Some(RebaseProgress {
+        current: read(current_file).and_then(|s| s.parse().ok()).unwrap_or(0),
+        total: read(total_file).and_then(|s| s.parse().ok()).unwrap_or(0),
+        head_name,
+        onto,
+    })
+}
+
+/// 逐个应用剩余的提交，遇到冲突时停下
+fn run(repo: &Repository, rebase: &mut Rebase, signature: &Signature) -> Result<RebaseResult, String> {
+    let mut dropped = Vec::new();
+    while let Some(operation) = rebase.next() {
+        let commit_id = operation.map_err(|e| format!("应用提交失败: {}", e))?.id();
+
+        let index = repo.index()
+            .map_err(|e| format!("无法读取索引: {}", e))?;
+        if index.has_conflicts() {
+            return Ok(RebaseResult {
+                status: "conflicted".to_string(),
+                progress: read_progress(repo),
+                conflicts: conflicted_paths(&index)?,
+                message: None,
+                dropped,
+            });
+        }
+
+        if !commit_current(rebase, signature)? {
+            dropped.push(commit_id.to_string());
+        }
+    }
+
+    let stash_id = read_autostash(repo);
+    rebase.finish(Some(signature))
+        .map_err(|e| format!("完成变基失败: {}", e))?;
+
+    let message = match stash_id {
+        Some(stash_id) => pop_autostash(repo, stash_id)?,
+        None => None,
+    };
+
+    Ok(RebaseResult {
+        status: "completed".to_string(),
+        progress: None,
+        conflicts: Vec::new(),
+        message,
+        dropped,
+    })
+}
+
+/// 提交当前操作；与上游已有的修改相同时（结果为空）跳过并返回 false
+fn commit_current(rebase: &mut Rebase, signature: &Signature) -> Result<bool, String> {
+    match rebase.commit(None, signature, None) {
+        Ok(_) => Ok(true),
+        Err(e) if e.code() == ErrorCode::Applied => Ok(false),
+        Err(e) => Err(format!("提交失败: {}", e)),
+    }
+}
+
+fn open_rebase(repo: &Repository) -> Result<Rebase<'_>, String> {
+    repo.open_rebase(None)
+        .map_err(|e| match e.code() {
+            ErrorCode::NotFound => "当前没有进行中的变基".to_string(),
+            _ => format!("无法读取变基状态: {}", e),
+        })
+}
+
+pub(super) fn resolve_commit(repo: &Repository, spec: &str) -> Result<Oid, String> {
+    repo.revparse_single(spec)
+        .and_then(|obj| obj.peel_to_commit())
+        .map(|commit| commit.id())
+        .map_err(|e| format!("无法解析版本 {}: {}", spec, e))
+}
+
+pub(super) fn state_dir(repo: &Repository) -> std::path::PathBuf {
+    repo.path().join("rebase-merge")
+}
+
+pub(super) fn read_autostash(repo: &Repository) -> Option<Oid> {
+    fs::read_to_string(state_dir(repo).join(AUTOSTASH_FILE))
+        .ok()
+        .and_then(|s| Oid::from_str(s.trim()).ok())
+}
+
+/// 恢复自动贮藏；冲突时保留在贮藏列表中并返回提示
+pub(super) fn pop_autostash(repo: &Repository, stash_id: Oid) -> Result<Option<String>, String> {
+    // 贮藏操作需要可变的仓库句柄，变基期间的句柄仍被 Rebase 借用
+    let mut repo = Repository::open(repo.path())
+        .map_err(|e| format!("无法打开仓库: {}", e))?;
+
+    let mut stash_index = None;
+    repo.stash_foreach(|index, _, oid| {
+        if *oid == stash_id {
+            stash_index = Some(index);
+            false
+        } else {
+            true
+        }
+    }).map_err(|e| format!("读取贮藏失败: {}", e))?;
+
+    let Some(stash_index) = stash_index else {
+        return Ok(Some("未找到自动贮藏的修改".to_string()));
+    };
+
+    match repo.stash_pop(stash_index, None) {
+        Ok(()) => Ok(None),
+        Err(_) => Ok(Some(format!(
+            "自动贮藏的修改与当前工作区冲突，已保留在贮藏列表中（stash@{{{}}}）",
+            stash_index
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// topic 上有一个与 main 冲突的提交（修改 a.txt）和一个不冲突的提交，当前检出 topic
+    fn diverged() -> TempRepo {
+        let repo = TempRepo::new("rebase");
+        repo.write("a.txt", "base\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("a.txt", "topic\n");
+        repo.commit("topic a");
+        repo.write("b.txt", "b\n");
+        repo.commit("topic b");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("a.txt", "main\n");
+        repo.commit("main a");
+        repo.git(&["checkout", "-q", "topic"]);
+        repo
+    }
+
+    fn subjects(repo: &TempRepo) -> String {
+        repo.git(&["log", "--format=%s", "main~1..HEAD"])
+    }
+
+    #[test]
+    fn rebase_without_conflicts_completes() {
+        let repo = TempRepo::new("rebase-clean");
+        repo.write("a.txt", "base\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("b.txt", "b\n");
+        repo.commit("topic b");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("c.txt", "c\n");
+        let main = repo.commit("main c");
+        repo.git(&["checkout", "-q", "topic"]);
+
+        let result = rebase(repo.path(), "main", None, false).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(repo.rev_parse("HEAD~1"), main);
+        assert_eq!(repo.git(&["rev-parse", "--abbrev-ref", "HEAD"]), "topic");
+        assert!(read_progress(&open(repo.path()).unwrap()).is_none());
+    }
+
+    #[test]
+    fn reports_commits_already_upstream() {
+        let repo = TempRepo::new("rebase-applied");
+        repo.write("a.txt", "base\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("b.txt", "b\n");
+        let applied = repo.commit("add b");
+        repo.write("c.txt", "c\n");
+        repo.commit("add c");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("d.txt", "d\n");
+        repo.commit("main d");
+        repo.git(&["cherry-pick", &applied]);
+        repo.git(&["checkout", "-q", "topic"]);
+
+        let result = rebase(repo.path(), "main", None, false).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(result.dropped, vec![applied]);
+        assert_eq!(repo.git(&["log", "--format=%s", "main..HEAD"]), "add c");
+    }
+
+    #[test]
+    fn conflict_then_continue_with_autostash() {
+        let repo = diverged();
+        repo.write("b.txt", "dirty\n");
+        assert!(rebase(repo.path(), "main", None, false).is_err());
+
+        let result = rebase(repo.path(), "main", None, true).unwrap();
+        assert_eq!(result.status, "conflicted");
+        assert_eq!(result.conflicts, vec!["a.txt".to_string()]);
+        let progress = result.progress.unwrap();
+        assert_eq!((progress.current, progress.total), (1, 2));
+        assert!(rebase_continue(repo.path()).is_err());
+
+        repo.write("a.txt", "resolved\n");
+        repo.git(&["add", "a.txt"]);
+        let result = rebase_continue(repo.path()).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(subjects(&repo), "topic b\ntopic a\nmain a");
+        assert_eq!(repo.read("a.txt").unwrap(), "resolved\n");
+        assert_eq!(repo.read("b.txt").unwrap(), "dirty\n");
+        assert_eq!(repo.git(&["stash", "list"]), "");
+    }
+
+    #[test]
+    fn conflict_then_skip_drops_commit() {
+        let repo = diverged();
+        assert_eq!(rebase(repo.path(), "main", None, false).unwrap().status, "conflicted");
+
+        let result = rebase_skip(repo.path()).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(subjects(&repo), "topic b\nmain a");
+        assert_eq!(repo.read("a.txt").unwrap(), "main\n");
+    }
+
+    #[test]
+    fn conflict_then_abort_restores_branch() {
+        let repo = diverged();
+        let head = repo.rev_parse("HEAD");
+        repo.write("b.txt", "dirty\n");
+        assert_eq!(rebase(repo.path(), "main", None, true).unwrap().status, "conflicted");
+
+        rebase_abort(repo.path()).unwrap();
+        assert_eq!(repo.rev_parse("HEAD"), head);
+        assert_eq!(repo.git(&["rev-parse", "--abbrev-ref", "HEAD"]), "topic");
+        assert_eq!(repo.read("a.txt").unwrap(), "topic\n");
+        assert_eq!(repo.read("b.txt").unwrap(), "dirty\n");
+        assert!(rebase_abort(repo.path()).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use git2::{Oid, Repository};
+
+use super::open;
+use super::reset::reset;
+use crate::models::*;
+
+/// 读取引用日志，最新的记录在前
+///
+/// `reference` 为空时读取 HEAD 的日志，也可以传入分支短名（如 `main`）或完整引用名。
+pub fn get_reflog(path: &str, reference: Option<&str>) -> Result<Vec<ReflogEntry>, String> {
+    let repo = open(path)?;
+    let name = full_ref_name(&repo, reference.unwrap_or("HEAD"));
+    let reflog = repo.reflog(&name)
+        .map_err(|e| format!("读取引用日志失败: {}", e))?;
+
+    let entries = reflog.iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let committer = entry.committer();
+            ReflogEntry {
+                index,
+                old_id: entry.id_old().to_string(),
+                new_id: entry.id_new().to_string(),
+                message: entry.message().unwrap_or("").to_string(),
+                committer_name: committer.name().unwrap_or("").to_string(),
+                committer_email: committer.email().unwrap_or("").to_string(),
+                timestamp: committer.when().seconds(),
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// 将引用恢复到引用日志中第 `index` 条记录的位置（`git reset --hard <ref>@{index}`）
+///
+/// 当前检出的分支或 HEAD 通过 hard reset 恢复，工作区修改会先保存为备份；
+/// 其它分支只移动引用本身。两种情况都会在引用日志中留下记录，可以再次撤销。
+pub fn restore_reflog_entry(
+    path: &str,
+    reference: Option<&str>,
+    index: usize,
+) -> Result<ResetResult, String> {
+    let repo = open(path)?;
+    let name = full_ref_name(&repo, reference.unwrap_or("HEAD"));
+    let reflog = repo.reflog(&name)
+        .map_err(|e| format!("读取引用日志失败: {}", e))?;
+    let entry = reflog.get(index)
+        .ok_or_else(|| format!("引用日志中没有 {}@{{{}}}", name, index))?;
+    let target = entry.id_new();
+    if target.is_zero() {
+        return Err(format!("{}@{{{}}} 没有指向任何提交", name, index));
+    }
+    repo.find_commit(target)
+        .map_err(|e| format!("无法找到提交 {}: {}", target, e))?;
+
+    if is_checked_out(&repo, &name) {
+        return reset(path, &target.to_string(), ResetMode::Hard);
+    }
+
+    move_reference(&repo, &name, target, index)?;
+    Ok(ResetResult {
+        head: target.to_string(),
+        backup: None,
+    })
+}
+
+/// 将分支短名补全为完整引用名，HEAD 和已存在的完整名称保持不变
+pub(super) fn full_ref_name(repo: &Repository, name: &str) -> String {
+    if name == "HEAD" || name.starts_with("refs/") {
+        return name.to_string();
+    }
+    repo.resolve_reference_from_short_name(name)
+        .ok()
+        .and_then(|reference| reference.name().map(|s| s.to_string()))
+        .unwrap_or_else(|| format!("refs/heads/{}", name))
+}
+
+fn is_checked_out(repo: &Repository, name: &str) -> bool {
+    if name == "HEAD" {
+        return true;
+    }
+    repo.head().ok()
+        .and_then(|head| head.name().map(|s| s == name))
+        .unwrap_or(false)
+}
+
+fn move_reference(repo: &Repository, name: &str, target: Oid, index: usize) -> Result<(), String> {
+    let mut reference = repo.find_reference(name)
+        .map_err(|e| format!("无法找到引用 {}: {}", name, e))?;
+    let message = format!("reset: moving to {}@{{{}}}", name, index);
+    reference.set_target(target, &message)
+        .map_err(|e| format!("更新引用失败: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    #[test]
+    fn lists_head_and_branch_reflogs_newest_first() {
+        let repo = TempRepo::new("reflog");
+        repo.write("a.txt", "1\n");
+        repo.commit("first");
+        repo.write("a.txt", "2\n");
+        let second = repo.commit("second");
+        repo.git(&["reset", "-q", "--hard", "HEAD~1"]);
+
+        let entries = get_reflog(repo.path(), None).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].index, 0);
+        assert!(entries[0].message.starts_with("reset: moving to"));
+        assert_eq!(entries[0].old_id, second);
+        assert_eq!(get_reflog(repo.path(), Some("main")).unwrap().len(), 3);
+        assert_eq!(get_reflog(repo.path(), Some("refs/heads/main")).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn restore_checked_out_branch_resets_with_backup() {
+        let repo = TempRepo::new("reflog-head");
+        repo.write("a.txt", "1\n");
+        repo.commit("first");
+        repo.write("a.txt", "2\n");
+        let second = repo.commit("second");
+        repo.git(&["reset", "-q", "--hard", "HEAD~1"]);
+        repo.write("a.txt", "dirty\n");
+
+        let result = restore_reflog_entry(repo.path(), None, 1).unwrap();
+        assert_eq!(result.head, second);
+        assert!(result.backup.is_some_and(|backup| backup.has_changes));
+        assert_eq!(repo.rev_parse("main"), second);
+        assert_eq!(repo.read("a.txt").as_deref(), Some("2\n"));
+        assert!(restore_reflog_entry(repo.path(), None, 99).is_err());
+    }
+
+    #[test]
+    fn restore_other_branch_moves_only_the_ref() {
+        let repo = TempRepo::new("reflog-branch");
+        repo.write("a.txt", "1\n");
+        let first = repo.commit("first");
+        repo.git(&["branch", "side"]);
+        repo.write("a.txt", "2\n");
+        let second = repo.commit("second");
+        repo.git(&["branch", "-f", "side", "HEAD"]);
+
+        let result = restore_reflog_entry(repo.path(), Some("side"), 1).unwrap();
+        assert_eq!(result.head, first);
+        assert!(result.backup.is_none());
+        assert_eq!(repo.rev_parse("side"), first);
+        assert_eq!(repo.rev_parse("HEAD"), second);
+        assert!(get_reflog(repo.path(), Some("side")).unwrap()[0].message.starts_with("reset: moving to"));
+    }
+}
@@ -0,0 +1,211 @@
+use git2::{ErrorCode, Remote, Repository};
+
+use super::open;
+use crate::models::*;
+
+/// 列出远程仓库及其地址和 refspec
+pub fn list_remotes(path: &str) -> Result<Vec<RemoteInfo>, String> {
+    let repo = open(path)?;
+    let names = repo.remotes()
+        .map_err(|e| format!("读取远程仓库失败: {}", e))?;
+
+    let mut remotes = Vec::new();
+    for name in names.iter().flatten() {
+        let remote = find_remote(&repo, name)?;
+        remotes.push(remote_info(&remote)?);
+    }
+
+    Ok(remotes)
+}
+
+/// 添加远程仓库；`fetch_refspec` 为空时使用默认的 `+refs/heads/*:refs/remotes/<name>/*`
+pub fn add_remote(
+    path: &str,
+    name: &str,
+    url: &str,
+    fetch_refspec: Option<&str>,
+) -> Result<RemoteInfo, String> {
+    let repo = open(path)?;
+    validate_name(name)?;
+
+    let remote = match fetch_refspec {
+        Some(refspec) => repo.remote_with_fetch(name, url, refspec),
+        None => repo.remote(name, url),
+    }
+    .map_err(|e| match e.code() {
+        ErrorCode::Exists => format!("远程仓库 {} 已存在", name),
+        _ => format!("添加远程仓库失败: {}", e),
+    })?;
+
+    remote_info(&remote)
+}
+
+/// 重命名远程仓库，同时移动远程跟踪分支并更新分支的上游配置
+///
+/// 返回无法自动改写的非默认 fetch refspec，需要用户手动调整。
+pub fn rename_remote(path: &str, name: &str, new_name: &str) -> Result<Vec<String>, String> {
+    let repo = open(path)?;
+    validate_name(new_name)?;
+    find_remote(&repo, name)?;
+
+    let problems = repo.remote_rename(name, new_name)
+        .map_err(|e| match e.code() {
+            ErrorCode::Exists => format!("远程仓库 {} 已存在", new_name),
+            _ => format!("重命名远程仓库失败: {}", e),
+        })?;
+
+    Ok(problems.iter().flatten().map(|s| s.to_string()).collect())
+}
+
+/// 删除远程仓库及其远程跟踪分支
+pub fn remove_remote(path: &str, name: &str) -> Result<(), String> {
+    let repo = open(path)?;
+    find_remote(&repo, name)?;
+    repo.remote_delete(name)
+        .map_err(|e| format!("删除远程仓库失败: {}", e))
+}
+
+/// 修改远程仓库地址；`push` 为 true 时修改推送地址，地址为空时清除推送地址
+pub fn set_remote_url(path: &str, name: &str, url: Option<&str>, push: bool) -> Result<RemoteInfo, String> {
+    let repo = open(path)?;
+    find_remote(&repo, name)?;
+
+    let url = url.map(|s| s.trim()).filter(|s| !s.is_empty());
+    let result = match (push, url) {
+        (true, url) => repo.remote_set_pushurl(name, url),
+        (false, Some(url)) => repo.remote_set_url(name, url),
+        (false, None) => return Err("远程仓库地址不能为空".to_string()),
+    };
+    result.map_err(|e| format!("修改远程仓库地址失败: {}", e))?;
+
+    let remote = find_remote(&repo, name)?;
+    remote_info(&remote)
+}
+
+/// 替换远程仓库的全部 fetch refspec，任一 refspec 无效时保持原配置不变
+pub fn set_fetch_refspecs(path: &str, name: &str, refspecs: &[String]) -> Result<RemoteInfo, String> {
+    let repo = open(path)?;
+    let previous = find_remote(&repo, name)?
+        .fetch_refspecs()
+        .map_err(|e| format!("读取 refspec 失败: {}", e))?
+        .iter()
+        .flatten()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+
+    let refspecs: Vec<&str> = refspecs.iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if refspecs.is_empty() {
+        return Err("至少需要一个 fetch refspec".to_string());
+    }
+
+    if let Err(e) = replace_fetch_refspecs(&repo, name, &refspecs) {
+        let previous: Vec<&str> = previous.iter().map(|s| s.as_str()).collect();
+        let _ = replace_fetch_refspecs(&repo, name, &previous);
+        return Err(e);
+    }
+
+    let remote = find_remote(&repo, name)?;
+    remote_info(&remote)
+}
+
+fn replace_fetch_refspecs(repo: &Repository, name: &str, refspecs: &[&str]) -> Result<(), String> {
+    let mut config = repo.config()
+        .map_err(|e| format!("读取配置失败: {}", e))?;
+    match config.remove_multivar(&format!("remote.{}.fetch", name), ".*") {
+        Ok(()) => {}
+        Err(e) if e.code() == ErrorCode::NotFound => {}
+        Err(e) => return Err(format!("修改 refspec 失败: {}", e)),
+    }
+
+    for refspec in refspecs {
+        repo.remote_add_fetch(name, refspec)
+            .map_err(|e| format!("无效的 refspec {}: {}", refspec, e))?;
+    }
+    Ok(())
+}
+
+fn find_remote<'r>(repo: &'r Repository, name: &str) -> Result<Remote<'r>, String> {
+    repo.find_remote(name)
+        .map_err(|e| format!("无法找到远程仓库 {}: {}", name, e))
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if Remote::is_valid_name(name) {
+        Ok(())
+    } else {
+        Err(format!("无效的远程仓库名称: {}", name))
+    }
+}
+
+fn remote_info(remote: &Remote) -> Result<RemoteInfo, String> {
+    let collect = |specs: Result<git2::string_array::StringArray, git2::Error>| {
+        specs.map(|specs| specs.iter().flatten().map(|s| s.to_string()).collect::<Vec<_>>())
+            .map_err(|e| format!("读取 refspec 失败: {}", e))
+    };
+
+    Ok(RemoteInfo {
+        name: remote.name().unwrap_or("").to_string(),
+        url: remote.url().map(|s| s.to_string()),
+        push_url: remote.pushurl().map(|s| s.to_string()),
+        fetch_refspecs: collect(remote.fetch_refspecs())?,
+        push_refspecs: collect(remote.push_refspecs())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    #[test]
+    fn add_validates_names_and_duplicates() {
+        let repo = TempRepo::new("remote-add");
+        let info = add_remote(repo.path(), "origin", "https://example.com/a.git", None).unwrap();
+        assert_eq!(info.url.as_deref(), Some("https://example.com/a.git"));
+        assert_eq!(info.fetch_refspecs, vec!["+refs/heads/*:refs/remotes/origin/*".to_string()]);
+
+        assert!(add_remote(repo.path(), "origin", "https://example.com/b.git", None).is_err());
+        assert!(add_remote(repo.path(), "bad name", "https://example.com/b.git", None).is_err());
+        assert_eq!(list_remotes(repo.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rename_moves_tracking_configuration() {
+        let repo = TempRepo::new("remote-rename");
+        repo.commit("initial");
+        add_remote(repo.path(), "origin", "https://example.com/a.git", None).unwrap();
+        repo.git(&["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        repo.git(&["branch", "-q", "-u", "origin/main"]);
+
+        rename_remote(repo.path(), "origin", "upstream").unwrap();
+        assert_eq!(repo.git(&["rev-parse", "--abbrev-ref", "main@{upstream}"]), "upstream/main");
+        assert!(rename_remote(repo.path(), "origin", "other").is_err());
+
+        remove_remote(repo.path(), "upstream").unwrap();
+        assert!(list_remotes(repo.path()).unwrap().is_empty());
+        assert!(remove_remote(repo.path(), "upstream").is_err());
+    }
+
+    #[test]
+    fn updates_urls_and_refspecs() {
+        let repo = TempRepo::new("remote-urls");
+        add_remote(repo.path(), "origin", "https://example.com/a.git", None).unwrap();
+
+        let info = set_remote_url(repo.path(), "origin", Some("https://example.com/push.git"), true).unwrap();
+        assert_eq!(info.push_url.as_deref(), Some("https://example.com/push.git"));
+        let info = set_remote_url(repo.path(), "origin", None, true).unwrap();
+        assert_eq!(info.push_url, None);
+        assert!(set_remote_url(repo.path(), "origin", None, false).is_err());
+
+        assert!(set_fetch_refspecs(repo.path(), "origin", &["bad refspec ::".to_string()]).is_err());
+        let refspecs = vec![
+            "+refs/heads/main:refs/remotes/origin/main".to_string(),
+            "+refs/tags/*:refs/tags/*".to_string(),
+        ];
+        let info = set_fetch_refspecs(repo.path(), "origin", &refspecs).unwrap();
+        assert_eq!(info.fetch_refspecs, refspecs);
+    }
+}
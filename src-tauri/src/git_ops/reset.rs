@@ -0,0 +1,294 @@
+use git2::{build::CheckoutBuilder, Commit, Repository, ResetType};
+
+use super::stash::snapshot_worktree;
+use super::{head_commit, open, short_id, signature};
+use crate::models::*;
+
+const BACKUP_PREFIX: &str = "refs/prism/backups/";
+
+/// 将当前分支重置到指定提交（`git reset --soft/--mixed/--hard`）
+///
+/// hard 模式会先把原 HEAD、索引和工作区保存为 `refs/prism/backups/` 下的备份，
+/// 之后可通过 `restore_backup` 恢复。
+pub fn reset(path: &str, target: &str, mode: ResetMode) -> Result<ResetResult, String> {
+    let repo = open(path)?;
+    let target_commit = repo.revparse_single(target)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("无法解析版本 {}: {}", target, e))?;
+
+    let backup = match mode {
+        ResetMode::Hard => Some(create_backup(
+            &repo,
+            &format!("reset --hard to {}", short_id(target_commit.as_object())),
+        )?),
+        _ => None,
+    };
+
+    let reset_type = match mode {
+        ResetMode::Soft => ResetType::Soft,
+        ResetMode::Mixed => ResetType::Mixed,
+        ResetMode::Hard => ResetType::Hard,
+    };
+    repo.reset(target_commit.as_object(), reset_type, None)
+        .map_err(|e| format!("重置失败: {}", e))?;
+
+    Ok(ResetResult {
+        head: target_commit.id().to_string(),
+        backup,
+    })
+}
+
+/// 列出 Prism 备份，最新的在前
+pub fn list_backups(path: &str) -> Result<Vec<BackupEntry>, String> {
+    let repo = open(path)?;
+    let references = repo.references_glob(&format!("{}*", BACKUP_PREFIX))
+        .map_err(|e| format!("读取备份失败: {}", e))?;
+
+    let mut backups = Vec::new();
+    for reference in references {
+        let reference = reference.map_err(|e| format!("读取备份失败: {}", e))?;
+        let Some(name) = reference.name() else { continue };
+        let Ok(commit) = reference.peel_to_commit() else { continue };
+        if let Some(entry) = backup_entry(name, &commit) {
+            backups.push(entry);
+        }
+    }
+    backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| b.name.cmp(&a.name)));
+
+    Ok(backups)
+}
+
+/// 恢复备份：分支回到备份时的 HEAD，并还原索引和工作区
+///
+/// 恢复前会为当前状态再创建一个备份，因此恢复本身也可以撤销。
+pub fn restore_backup(path: &str, name: &str) -> Result<ResetResult, String> {
+    let repo = open(path)?;
+    let commit = find_backup(&repo, name)?;
+    let entry = backup_entry(name, &commit)
+        .ok_or_else(|| format!("无效的备份: {}", name))?;
+
+    let previous_head = commit.parent(0)
+        .map_err(|e| format!("无效的备份: {}", e))?;
+    let index_tree = commit.parent(1)
+        .and_then(|index_commit| index_commit.tree())
+        .map_err(|e| format!("无效的备份: {}", e))?;
+    let worktree_tree = commit.tree()
+        .map_err(|e| format!("无效的备份: {}", e))?;
+
+    let backup = create_backup(&repo, &format!("restore {}", name))?;
+
+    match &entry.branch {
+        Some(branch) => repo.set_head(branch),
+        None => repo.set_head_detached(previous_head.id()),
+    }
+    .map_err(|e| format!("恢复 HEAD 失败: {}", e))?;
+
+    repo.reset(previous_head.as_object(), ResetType::Hard, None)
+        .map_err(|e| format!("恢复失败: {}", e))?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+    repo.checkout_tree(worktree_tree.as_object(), Some(&mut checkout))
+        .map_err(|e| format!("恢复工作区失败: {}", e))?;
+    let mut index = repo.index()
+        .map_err(|e| format!("无法读取索引: {}", e))?;
+    index.read_tree(&index_tree)
+        .and_then(|_| index.write())
+        .map_err(|e| format!("恢复索引失败: {}", e))?;
+
+    Ok(ResetResult {
+        head: previous_head.id().to_string(),
+        backup: Some(backup),
+    })
+}
+
+/// 删除备份
+pub fn delete_backup(path: &str, name: &str) -> Result<(), String> {
+    let repo = open(path)?;
+    if !name.starts_with(BACKUP_PREFIX) {
+        return Err(format!("无效的备份: {}", name));
+    }
+    repo.find_reference(name)
+        .and_then(|mut reference| reference.delete())
+        .map_err(|e| format!("删除备份失败: {}", e))
+}
+
+/// 保存当前 HEAD、索引和工作区，结构与贮藏相同：
+/// 提交树为工作区快照，父提交依次为原 HEAD 和索引快照
+pub(crate) fn create_backup(repo: &Repository, reason: &str) -> Result<BackupEntry, String> {
+    let head = head_commit(repo)?;
+    let (index_tree_id, worktree_tree_id) = snapshot_worktree(repo)?;
+    let signature = signature(repo)?;
+    let branch = repo.head().ok()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.name().map(|s| s.to_string()));
+
+    let index_tree = repo.find_tree(index_tree_id)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let index_commit = repo.commit(
+        None,
+        &signature,
+        &signature,
+        &format!("index on {}\n", short_id(head.as_object())),
+        &index_tree,
+        &[&head],
+    ).map_err(|e| format!("创建备份失败: {}", e))?;
+    let index_commit = repo.find_commit(index_commit)
+        .map_err(|e| format!("创建备份失败: {}", e))?;
+
+    let worktree_tree = repo.find_tree(worktree_tree_id)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let message = format!(
+        "Prism backup before {}\n\nbranch: {}\n",
+        reason,
+        branch.as_deref().unwrap_or("HEAD")
+    );
+    let backup_id = repo.commit(
+        None,
+        &signature,
+        &signature,
+        &message,
+        &worktree_tree,
+        &[&head, &index_commit],
+    ).map_err(|e| format!("创建备份失败: {}", e))?;
+    let commit = repo.find_commit(backup_id)
+        .map_err(|e| format!("创建备份失败: {}", e))?;
+
+    let name = format!(
+        "{}{}-{}",
+        BACKUP_PREFIX,
+        signature.when().seconds(),
+        short_id(commit.as_object())
+    );
+    repo.reference(&name, backup_id, false, &format!("prism backup: {}", reason))
+        .map_err(|e| format!("创建备份失败: {}", e))?;
+
+    backup_entry(&name, &commit)
+        .ok_or_else(|| "创建备份失败".to_string())
+}
+
+pub(super) fn find_backup<'r>(repo: &'r Repository, name: &str) -> Result<Commit<'r>, String> {
+    if !name.starts_with(BACKUP_PREFIX) {
+        return Err(format!("无效的备份: {}", name));
+    }
+    repo.find_reference(name)
+        .and_then(|reference| reference.peel_to_commit())
+        .map_err(|e| format!("无法找到备份 {}: {}", name, e))
+}
+
+pub(super) fn backup_entry(name: &str, commit: &Commit) -> Option<BackupEntry> {
+    let previous_head = commit.parent(0).ok()?;
+    let index_tree = commit.parent(1).ok()?.tree_id();
+    let head_tree = previous_head.tree_id();
+
+    let message = commit.message().unwrap_or("");
+    let branch = message.lines()
+        .find_map(|line| line.strip_prefix("branch: "))
+        .filter(|branch| *branch != "HEAD")
+        .map(|branch| branch.to_string());
+
+    Some(BackupEntry {
+        name: name.to_string(),
+        id: commit.id().to_string(),
+        previous_head: previous_head.id().to_string(),
+        branch,
+        message: commit.summary().unwrap_or("").to_string(),
+        timestamp: commit.time().seconds(),
+        has_changes: commit.tree_id() != head_tree || index_tree != head_tree,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// 两个提交，a.txt 有已暂存和未暂存的修改，另有一个未跟踪文件
+    fn with_local_changes() -> TempRepo {
+        let repo = TempRepo::new("reset");
+        repo.write("a.txt", "first\n");
+        repo.commit("first");
+        repo.write("a.txt", "second\n");
+        repo.commit("second");
+        repo.write("a.txt", "staged\n");
+        repo.git(&["add", "a.txt"]);
+        repo.write("a.txt", "work\n");
+        repo.write("new.txt", "untracked\n");
+        repo
+    }
+
+    #[test]
+    fn soft_and_mixed_reset_keep_worktree_without_backup() {
+        let repo = with_local_changes();
+        let first = repo.rev_parse("HEAD~1");
+
+        let result = reset(repo.path(), "HEAD~1", ResetMode::Soft).unwrap();
+        assert_eq!(result.head, first);
+        assert!(result.backup.is_none());
+        assert_eq!(repo.git(&["show", ":a.txt"]), "staged");
+
+        reset(repo.path(), "HEAD", ResetMode::Mixed).unwrap();
+        assert_eq!(repo.git(&["show", ":a.txt"]), "first");
+        assert_eq!(repo.read("a.txt").as_deref(), Some("work\n"));
+        assert!(list_backups(repo.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn hard_reset_backup_restores_head_index_and_worktree() {
+        let repo = with_local_changes();
+        let second = repo.rev_parse("HEAD");
+
+        let backup = reset(repo.path(), "HEAD~1", ResetMode::Hard).unwrap().backup.unwrap();
+        assert!(backup.has_changes);
+        assert_eq!(backup.previous_head, second);
+        assert_eq!(backup.branch.as_deref(), Some("refs/heads/main"));
+        assert_eq!(repo.read("a.txt").as_deref(), Some("first\n"));
+
+        let restored = restore_backup(repo.path(), &backup.name).unwrap();
+        assert_eq!(restored.head, second);
+        assert_eq!(repo.rev_parse("main"), second);
+        assert_eq!(repo.git(&["show", ":a.txt"]), "staged");
+        assert_eq!(repo.read("a.txt").as_deref(), Some("work\n"));
+        assert_eq!(repo.read("new.txt").as_deref(), Some("untracked\n"));
+
+        // 恢复前的状态同样保存为备份
+        let undo = restored.backup.unwrap();
+        let backups = list_backups(repo.path()).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups.iter().any(|entry| entry.name == undo.name));
+    }
+
+    #[test]
+    fn restore_moves_recorded_branch_from_another_checkout() {
+        let repo = with_local_changes();
+        let second = repo.rev_parse("HEAD");
+        let backup = reset(repo.path(), "HEAD~1", ResetMode::Hard).unwrap().backup.unwrap();
+        repo.git(&["checkout", "-q", "-b", "other"]);
+
+        restore_backup(repo.path(), &backup.name).unwrap();
+        assert_eq!(repo.git(&["rev-parse", "--abbrev-ref", "HEAD"]), "main");
+        assert_eq!(repo.rev_parse("main"), second);
+    }
+
+    #[test]
+    fn backup_ids_follow_core_abbrev() {
+        let repo = with_local_changes();
+        let first = repo.rev_parse("HEAD~1");
+        repo.git(&["config", "core.abbrev", "12"]);
+
+        let backup = reset(repo.path(), "HEAD~1", ResetMode::Hard).unwrap().backup.unwrap();
+        assert!(backup.name.ends_with(&format!("-{}", &backup.id[..12])));
+        assert_eq!(backup.message, format!("Prism backup before reset --hard to {}", &first[..12]));
+    }
+
+    #[test]
+    fn delete_backup_only_accepts_backup_refs() {
+        let repo = with_local_changes();
+        let backup = reset(repo.path(), "HEAD", ResetMode::Hard).unwrap().backup.unwrap();
+
+        assert!(delete_backup(repo.path(), "refs/heads/main").is_err());
+        assert!(restore_backup(repo.path(), "refs/heads/main").is_err());
+        delete_backup(repo.path(), &backup.name).unwrap();
+        assert!(list_backups(repo.path()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeSet;
+
+use git2::{ErrorCode, Object, ObjectType, Oid, Repository};
+
+use super::history::HistoryWalk;
+use super::{commit_info, open, ref_labels};
+use crate::models::*;
+
+/// 缩写 id 至少需要的十六进制位数，与 git 相同
+const MIN_ABBREV: usize = 4;
+
+/// 解析 `git rev-parse` 支持的任意版本表达式（`HEAD~3`、`main@{yesterday}`、`v1.0^{tree}`、`:/fix typo`、缩写 id 等）
+///
+/// 提交附带其在提交历史中的位置：传入 `walk` 时为该遍历会话中的位置，否则为从 HEAD 开始的默认历史中的位置。
+/// 缩写 id 有歧义时返回所有能解析成功的候选。
+pub fn resolve(path: &str, spec: &str, walk: Option<&HistoryWalk>) -> Result<RevisionResolution, String> {
+    let repo = open(path)?;
+    let spec = spec.trim();
+    if spec.is_empty() {
+        return Err("版本表达式不能为空".to_string());
+    }
+
+    let (objects, ambiguous) = match repo.revparse_single(spec) {
+        Ok(object) => (vec![object], false),
+        Err(e) if e.code() == ErrorCode::Ambiguous => (ambiguous_candidates(&repo, spec)?, true),
+        Err(e) => return Err(format!("无法解析版本 {}: {}", spec, e)),
+    };
+    if objects.is_empty() {
+        return Err(format!("无法解析版本 {}: 没有符合的对象", spec));
+    }
+
+    let labels = ref_labels(&repo);
+    let mut candidates = Vec::new();
+    for object in objects {
+        let id = object.id();
+        let mut resolved = ResolvedObject {
+            id: id.to_string(),
+            kind: object.kind().map_or("unknown", |kind| kind.str()).to_string(),
+            summary: String::new(),
+            commit: None,
+            position: None,
+        };
+        match object.kind() {
+            Some(ObjectType::Commit) => {
+                let commit = object.peel_to_commit()
+                    .map_err(|e| format!("无法读取提交 {}: {}", id, e))?;
+                let mut info = commit_info(&commit);
+                info.refs = labels.get(&id).cloned().unwrap_or_default();
+                resolved.summary = commit.summary().unwrap_or("").to_string();
+                resolved.commit = Some(info);
+                resolved.position = match walk {
+                    Some(walk) => walk.position(id)?,
+                    None => head_position(&repo, id)?,
+                };
+            }
+            Some(ObjectType::Tag) => {
+                resolved.summary = object.as_tag()
+                    .and_then(|tag| tag.name())
+                    .unwrap_or("")
+                    .to_string();
+            }
+            _ => {}
+        }
+        candidates.push(resolved);
+    }
+
+    Ok(RevisionResolution {
+        spec: spec.to_string(),
+        ambiguous,
+        candidates,
+    })
+}
+
+/// 有歧义的缩写 id：查找所有以该前缀开头的对象，并把表达式中的前缀替换为完整 id 重新解析
+fn ambiguous_candidates<'r>(repo: &'r Repository, spec: &str) -> Result<Vec<Object<'r>>, String> {
+    let split = spec.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(spec.len());
+    let (prefix, rest) = spec.split_at(split);
+    if prefix.len() < MIN_ABBREV {
+        return Err(format!("版本 {} 有歧义", spec));
+    }
+    let prefix = prefix.to_ascii_lowercase();
+
+    let odb = repo.odb()
+        .map_err(|e| format!("无法打开对象库: {}", e))?;
+    let mut ids = BTreeSet::new();
+    odb.foreach(|id| {
+        if id.to_string().starts_with(&prefix) {
+            ids.insert(*id);
+        }
+        true
+    }).map_err(|e| format!("读取对象库失败: {}", e))?;
+
+    let mut seen = BTreeSet::new();
+    let objects = ids.into_iter()
+        .filter_map(|id| repo.revparse_single(&format!("{}{}", id, rest)).ok())
+        .filter(|object| seen.insert(object.id()))
+        .collect();
+    Ok(objects)
+}
+
+/// 提交在从 HEAD 开始的默认提交历史（与 `get_commit_history` 顺序相同）中的位置
+fn head_position(repo: &Repository, id: Oid) -> Result<Option<usize>, String> {
+    let Some(head) = repo.head().ok().and_then(|head| head.target()) else { return Ok(None) };
+    if head != id && !repo.graph_descendant_of(head, id).unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("无法创建 revwalk: {}", e))?;
+    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)
+        .map_err(|e| format!("设置排序失败: {}", e))?;
+    revwalk.push(head)
+        .map_err(|e| format!("无法推送 HEAD: {}", e))?;
+    Ok(revwalk.flatten().position(|oid| oid == id))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// c0 到 c4 之后是打了附注标签 v1.0 的 "fix typo in docs"，最后是 "after"
+    fn tagged_history() -> TempRepo {
+        let repo = TempRepo::new("revision");
+        for i in 0..5 {
+            repo.write("f.txt", &format!("{}\n", i));
+            repo.commit(&format!("c{}", i));
+        }
+        repo.commit("fix typo in docs");
+        repo.git(&["tag", "-a", "v1.0", "-m", "release"]);
+        repo.write("f.txt", "after\n");
+        repo.commit("after");
+        repo
+    }
+
+    fn first(repo: &TempRepo, spec: &str) -> ResolvedObject {
+        let mut resolution = resolve(repo.path(), spec, None).unwrap();
+        assert!(!resolution.ambiguous);
+        resolution.candidates.remove(0)
+    }
+
+    #[test]
+    fn resolves_revision_expressions() {
+        let repo = tagged_history();
+
+        let commit = first(&repo, "HEAD~3");
+        assert_eq!((commit.kind.as_str(), commit.summary.as_str(), commit.position), ("commit", "c3", Some(3)));
+        assert_eq!(commit.commit.unwrap().id, repo.rev_parse("HEAD~3"));
+
+        let tag = first(&repo, "v1.0");
+        assert_eq!((tag.kind.as_str(), tag.summary.as_str()), ("tag", "v1.0"));
+        assert_eq!(first(&repo, "v1.0^{tree}").kind, "tree");
+        assert_eq!(first(&repo, ":/fix typo").position, Some(1));
+        assert_eq!(first(&repo, "main@{1}").summary, "fix typo in docs");
+
+        let blob = first(&repo, "HEAD:f.txt");
+        assert_eq!((blob.kind.as_str(), blob.position), ("blob", None));
+
+        assert!(resolve(repo.path(), "nope", None).is_err());
+        assert!(resolve(repo.path(), "  ", None).is_err());
+    }
+
+    #[test]
+    fn positions_follow_history_walk() {
+        let repo = tagged_history();
+        // 只保留 c0 到 c4 时 c3 是第 2 个提交
+        let options = HistoryOptions { message: Some("^c\\d".to_string()), ..Default::default() };
+        let walk = HistoryWalk::start(repo.path(), &options).unwrap();
+
+        let resolution = resolve(repo.path(), "HEAD~3", Some(&walk)).unwrap();
+        assert_eq!(resolution.candidates[0].position, Some(1));
+        let resolution = resolve(repo.path(), "HEAD", Some(&walk)).unwrap();
+        assert_eq!(resolution.candidates[0].position, None);
+    }
+
+    #[test]
+    fn lists_candidates_for_ambiguous_ids() {
+        let repo = tagged_history();
+        // 写入文件内容直到两个对象的前 4 位相同
+        let git = git2::Repository::open(repo.path()).unwrap();
+        let mut seen = HashMap::new();
+        let (prefix, ids) = (0..10_000)
+            .find_map(|i| {
+                let id = git.blob(format!("blob {}\n", i).as_bytes()).unwrap().to_string();
+                let prefix = id[..MIN_ABBREV].to_string();
+                let other = seen.insert(prefix.clone(), id.clone())?;
+                Some((prefix, [other, id]))
+            })
+            .unwrap();
+
+        let resolution = resolve(repo.path(), &prefix, None).unwrap();
+        assert!(resolution.ambiguous);
+        let found: BTreeSet<_> = resolution.candidates.iter().map(|c| c.id.clone()).collect();
+        assert!(ids.iter().all(|id| found.contains(id)));
+        assert!(resolution.candidates.iter().all(|c| c.kind == "blob"));
+
+        // 大写前缀同样可以解析
+        assert!(resolve(repo.path(), &prefix.to_uppercase(), None).unwrap().ambiguous);
+        // 前缀之后的表达式对所有候选都不成立
+        assert!(resolve(repo.path(), &format!("{}~1", prefix), None).is_err());
+    }
+}
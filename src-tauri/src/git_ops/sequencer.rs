@@ -0,0 +1,544 @@
+use std::fs;
+use std::path::PathBuf;
+
+use git2::{Commit, Oid, Repository, RepositoryState, StatusOptions, Tree};
+use serde::{Deserialize, Serialize};
+
+use super::merge::conflicted_paths;
+use super::{checkout_merge_index, ensure_clean_state, has_local_changes, head_commit, open, signature};
+use crate::models::*;
+
+const STATE_FILE: &str = "prism-sequence.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum SequenceKind {
+    CherryPick,
+    Revert,
+}
+
+/// 持久化在 `.git/sequencer` 中的执行状态
+#[derive(Debug, Serialize, Deserialize)]
+struct SequenceState {
+    kind: SequenceKind,
+    commits: Vec<String>,
+    next: usize,
+    /// 因冲突停下、等待解决后提交的提交序号
+    pending: Option<usize>,
+    mainline: Option<u32>,
+    no_commit: bool,
+    orig_head: String,
+    created: Vec<String>,
+    /// 应用后与 HEAD 相同而没有创建提交的提交
+    #[serde(default)]
+    skipped: Vec<String>,
+}
+
+/// 拣选提交到当前分支（`git cherry-pick`）
+///
+/// 多个提交按历史顺序从旧到新依次应用；`no_commit` 时只把修改写入索引和工作区。
+/// 合并提交需要通过 `mainline`（从 1 开始）指定作为基准的父提交，与 git 一致，
+/// 指定 `mainline` 时所有提交都必须是合并提交。修改已存在于 HEAD 的提交不会创建空提交，
+/// 而是记录在结果的 `skipped` 中。
+pub fn cherry_pick(
+    path: &str,
+    commits: &[String],
+    mainline: Option<u32>,
+    no_commit: bool,
+) -> Result<SequenceResult, String> {
+    start(path, SequenceKind::CherryPick, commits, mainline, no_commit)
+}
+
+/// 撤销提交（`git revert`），多个提交按历史顺序从新到旧依次撤销
+pub fn revert(
+    path: &str,
+    commits: &[String],
+    mainline: Option<u32>,
+    no_commit: bool,
+) -> Result<SequenceResult, String> {
+    start(path, SequenceKind::Revert, commits, mainline, no_commit)
+}
+
+/// 解决冲突后继续拣选或撤销
+pub fn sequence_continue(path: &str) -> Result<SequenceResult, String> {
+    let repo = open(path)?;
+    let mut state = load_state(&repo)?;
+
+    let mut index = repo.index()
+        .map_err(|e| format!("无法读取索引: {}", e))?;
+    if index.has_conflicts() {
+        let conflicts = conflicted_paths(&index)?;
+        return Err(format!("仍有未解决的冲突: {}", conflicts.join(", ")));
+    }
+    if state.no_commit && has_unstaged_changes(&repo)? {
+        return Err("请先暂存冲突的解决结果".to_string());
+    }
+
+    if let Some(pending) = state.pending.take() {
+        if !state.no_commit {
+            let commit = find_commit(&repo, &state.commits[pending])?;
+            let tree_id = index.write_tree()
+                .map_err(|e| format!("写入树失败: {}", e))?;
+            match commit_step(&repo, state.kind, &commit, state.mainline, tree_id)? {
+                Some(oid) => state.created.push(oid.to_string()),
+                None => state.skipped.push(commit.id().to_string()),
+            }
+        }
+        remove_pending_files(&repo);
+    }
+
+    save_state(&repo, &state)?;
+    run(&repo, &mut state)
+}
+
+/// 放弃拣选或撤销，恢复到开始前的 HEAD
+pub fn sequence_abort(path: &str) -> Result<(), String> {
+    let repo = open(path)?;
+    let state = load_state(&repo)?;
+    let orig_head = Oid::from_str(&state.orig_head)
+        .and_then(|oid| repo.find_commit(oid))
+        .map_err(|e| format!("无法读取拣选状态: {}", e))?;
+
+    // hard reset 同时清理 CHERRY_PICK_HEAD、REVERT_HEAD 和 .git/sequencer
+    repo.reset(orig_head.as_object(), git2::ResetType::Hard, None)
+        .map_err(|e| format!("放弃操作失败: {}", e))?;
+    let _ = fs::remove_dir_all(sequencer_dir(&repo));
+    Ok(())
+}
+
+fn start(
+    path: &str,
+    kind: SequenceKind,
+    commits: &[String],
+    mainline: Option<u32>,
+    no_commit: bool,
+) -> Result<SequenceResult, String> {
+    let repo = open(path)?;
+    ensure_clean_state(&repo)?;
+    if commits.is_empty() {
+        return Err("没有选择提交".to_string());
+    }
+    if has_local_changes(&repo)? {
+        return Err("工作区有未提交的修改，请先提交或贮藏".to_string());
+    }
+
+    let mut resolved = Vec::new();
+    for spec in commits {
+        let commit = repo.revparse_single(spec)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|e| format!("无法解析版本 {}: {}", spec, e))?;
+        match (commit.parent_count(), mainline) {
+            (count, None) if count > 1 => {
+                return Err(format!("{} 是合并提交，需要指定主线父提交", spec));
+            }
+            (count, Some(_)) if count <= 1 => {
+                return Err(format!("指定了主线父提交，但 {} 不是合并提交", spec));
+            }
+            (count, Some(mainline)) if mainline == 0 || mainline as usize > count => {
+                return Err(format!("{} 没有第 {} 个父提交", spec, mainline));
+            }
+            _ => {}
+        }
+        if !resolved.contains(&commit.id()) {
+            resolved.push(commit.id());
+        }
+    }
+    let ordered = history_order(&repo, &resolved, kind == SequenceKind::CherryPick)?;
+
+    let mut state = SequenceState {
+        kind,
+        commits: ordered.iter().map(|id| id.to_string()).collect(),
+        next: 0,
+        pending: None,
+        mainline,
+        no_commit,
+        orig_head: head_commit(&repo)?.id().to_string(),
+        created: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    fs::create_dir_all(sequencer_dir(&repo))
+        .map_err(|e| format!("写入拣选状态失败: {}", e))?;
+    fs::write(sequencer_dir(&repo).join("head"), format!("{}\n", state.orig_head))
+        .map_err(|e| format!("写入拣选状态失败: {}", e))?;
+    save_state(&repo, &state)?;
+
+    run(&repo, &mut state)
+}
+
+/// 按拓扑顺序排列选中的提交：`oldest_first` 时从旧到新（拣选），否则从新到旧（撤销）
+fn history_order(repo: &Repository, commits: &[Oid], oldest_first: bool) -> Result<Vec<Oid>, String> {
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("无法创建 revwalk: {}", e))?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+        .map_err(|e| format!("设置排序失败: {}", e))?;
+    for id in commits {
+        revwalk.push(*id)
+            .map_err(|e| format!("遍历提交失败: {}", e))?;
+    }
+
+    let mut ordered = Vec::with_capacity(commits.len());
+    for oid in revwalk {
+        let oid = oid.map_err(|e| format!("遍历提交失败: {}", e))?;
+        if commits.contains(&oid) {
+            ordered.push(oid);
+            if ordered.len() == commits.len() {
+                break;
+            }
+        }
+    }
+    if oldest_first {
+        ordered.reverse();
+    }
+    Ok(ordered)
+}
+
+/// 依次应用剩余的提交，遇到冲突时停下
+fn run(repo: &Repository, state: &mut SequenceState) -> Result<SequenceResult, String> {
+    while state.next < state.commits.len() {
+        let step = state.next;
+        state.next += 1;
+        let commit = find_commit(repo, &state.commits[step])?;
+
+        let ours_id = repo.index()
+            .and_then(|mut index| index.write_tree())
+            .map_err(|e| format!("写入索引树失败: {}", e))?;
+        let ours = repo.find_tree(ours_id)
+            .map_err(|e| format!("写入索引树失败: {}", e))?;
+        let parent_tree = parent_tree(repo, &commit, state.mainline)?;
+        let commit_tree = commit.tree()
+            .map_err(|e| format!("无法获取树: {}", e))?;
+
+        let (ancestor, theirs) = match state.kind {
+            SequenceKind::CherryPick => (&parent_tree, &commit_tree),
+            SequenceKind::Revert => (&commit_tree, &parent_tree),
+        };
+        let mut merged = repo.merge_trees(ancestor, &ours, theirs, None)
+            .map_err(|e| format!("应用提交失败: {}", e))?;
+
+        match checkout_merge_index(repo, &mut merged, state.no_commit)? {
+            Ok(tree_id) => {
+                if state.no_commit {
+                    let tree = repo.find_tree(tree_id)
+                        .map_err(|e| format!("写入树失败: {}", e))?;
+                    let mut index = repo.index()
+                        .map_err(|e| format!("无法读取索引: {}", e))?;
+                    index.read_tree(&tree)
+                        .and_then(|_| index.write())
+                        .map_err(|e| format!("写入索引失败: {}", e))?;
+                } else {
+                    match commit_step(repo, state.kind, &commit, state.mainline, tree_id)? {
+                        Some(oid) => state.created.push(oid.to_string()),
+                        None => state.skipped.push(commit.id().to_string()),
+                    }
+                }
+                save_state(repo, state)?;
+            }
+            Err(conflicts) => {
+                state.pending = Some(step);
+                save_state(repo, state)?;
+                write_pending_files(repo, state.kind, &commit, state.mainline)?;
+                return Ok(SequenceResult {
+                    status: "conflicted".to_string(),
+                    commit_ids: state.created.clone(),
+                    skipped: state.skipped.clone(),
+                    current: Some(commit.id().to_string()),
+                    conflicts,
+                });
+            }
+        }
+    }
+
+    let created = std::mem::take(&mut state.created);
+    let skipped = std::mem::take(&mut state.skipped);
+    fs::remove_dir_all(sequencer_dir(repo))
+        .map_err(|e| format!("清理拣选状态失败: {}", e))?;
+
+    Ok(SequenceResult {
+        status: "completed".to_string(),
+        commit_ids: created,
+        skipped,
+        current: None,
+        conflicts: Vec::new(),
+    })
+}
+
+/// 提交一步的结果；与 HEAD 相同（修改已存在）时不创建提交
+fn commit_step(
+    repo: &Repository,
+    kind: SequenceKind,
+    commit: &Commit,
+    mainline: Option<u32>,
+    tree_id: Oid,
+) -> Result<Option<Oid>, String> {
+    let head = head_commit(repo)?;
+    if head.tree_id() == tree_id {
+        return Ok(None);
+    }
+
+    let tree = repo.find_tree(tree_id)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let committer = signature(repo)?;
+    let author = match kind {
+        SequenceKind::CherryPick => commit.author().to_owned(),
+        SequenceKind::Revert => committer.clone(),
+    };
+    let message = step_message(kind, commit, mainline);
+
+    let reflog_prefix = match kind {
+        SequenceKind::CherryPick => "cherry-pick",
+        SequenceKind::Revert => "revert",
+    };
+    let oid = repo.commit(None, &author, &committer, &message, &tree, &[&head])
+        .map_err(|e| format!("提交失败: {}", e))?;
+
+    // repo.head() 返回解析后的分支引用，分离 HEAD 时即 HEAD 本身
+    let mut head_ref = repo.head()
+        .map_err(|e| format!("无法获取 HEAD: {}", e))?;
+    head_ref.set_target(oid, &format!("{}: {}", reflog_prefix, message.lines().next().unwrap_or("")))
+        .map_err(|e| format!("更新 HEAD 失败: {}", e))?;
+
+    Ok(Some(oid))
+}
+
+/// 生成提交信息，撤销时与 `git revert` 的默认格式一致
+fn step_message(kind: SequenceKind, commit: &Commit, mainline: Option<u32>) -> String {
+    match kind {
+        SequenceKind::CherryPick => {
+            format!("{}\n", commit.message().unwrap_or("").trim_end())
+        }
+        SequenceKind::Revert => {
+            let mut message = format!(
+                "Revert \"{}\"\n\nThis reverts commit {}",
+                commit.summary().unwrap_or(""),
+                commit.id()
+            );
+            match (commit.parent_count() > 1, mainline) {
+                (true, Some(mainline)) => {
+                    let parent = commit.parent_id(mainline.saturating_sub(1) as usize)
+                        .map(|id| id.to_string())
+                        .unwrap_or_default();
+                    message.push_str(&format!(", reversing\nchanges made to {}.\n", parent));
+                }
+                _ => message.push_str(".\n"),
+            }
+            message
+        }
+    }
+}
+
+fn parent_tree<'r>(
+    repo: &'r Repository,
+    commit: &Commit<'r>,
+    mainline: Option<u32>,
+) -> Result<Tree<'r>, String> {
+    if commit.parent_count() == 0 {
+        let empty = repo.treebuilder(None)
+            .and_then(|builder| builder.write())
+            .map_err(|e| format!("写入树失败: {}", e))?;
+        return repo.find_tree(empty)
+            .map_err(|e| format!("写入树失败: {}", e));
+    }
+
+    let index = match (commit.parent_count() > 1, mainline) {
+        (true, Some(mainline)) if mainline >= 1 && (mainline as usize) <= commit.parent_count() => {
+            mainline as usize - 1
+        }
+        (true, _) => return Err(format!("主线父提交序号无效: {:?}", mainline)),
+        (false, _) => 0,
+    };
+    commit.parent(index)
+        .and_then(|parent| parent.tree())
+        .map_err(|e| format!("无法获取父提交: {}", e))
+}
+
+/// 冲突时写入 CHERRY_PICK_HEAD / REVERT_HEAD 和 MERGE_MSG，使状态与 git 一致
+fn write_pending_files(
+    repo: &Repository,
+    kind: SequenceKind,
+    commit: &Commit,
+    mainline: Option<u32>,
+) -> Result<(), String> {
+    let head_file = match kind {
+        SequenceKind::CherryPick => "CHERRY_PICK_HEAD",
+        SequenceKind::Revert => "REVERT_HEAD",
+    };
+    fs::write(repo.path().join(head_file), format!("{}\n", commit.id()))
+        .and_then(|_| fs::write(repo.path().join("MERGE_MSG"), step_message(kind, commit, mainline)))
+        .map_err(|e| format!("写入拣选状态失败: {}", e))
+}
+
+fn remove_pending_files(repo: &Repository) {
+    for name in ["CHERRY_PICK_HEAD", "REVERT_HEAD", "MERGE_MSG"] {
+        let _ = fs::remove_file(repo.path().join(name));
+    }
+}
+
+fn find_commit<'r>(repo: &'r Repository, id: &str) -> Result<Commit<'r>, String> {
+    Oid::from_str(id)
+        .and_then(|oid| repo.find_commit(oid))
+        .map_err(|e| format!("无法找到提交 {}: {}", id, e))
+}
+
+fn has_unstaged_changes(repo: &Repository) -> Result<bool, String> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false);
+    let statuses = repo.statuses(Some(&mut opts))
+        .map_err(|e| format!("获取状态失败: {}", e))?;
+    Ok(statuses.iter().any(|entry| entry.status().intersects(
+        git2::Status::WT_MODIFIED | git2::Status::WT_DELETED | git2::Status::WT_TYPECHANGE
+    )))
+}
+
+fn sequencer_dir(repo: &Repository) -> PathBuf {
+    repo.path().join("sequencer")
+}
+
+fn save_state(repo: &Repository, state: &SequenceState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("写入拣选状态失败: {}", e))?;
+    let mut todo = String::new();
+    let verb = match state.kind {
+        SequenceKind::CherryPick => "pick",
+        SequenceKind::Revert => "revert",
+    };
+    for id in &state.commits[state.next..] {
+        todo.push_str(&format!("{} {}\n", verb, id));
+    }
+    fs::write(sequencer_dir(repo).join(STATE_FILE), content)
+        .and_then(|_| fs::write(sequencer_dir(repo).join("todo"), todo))
+        .map_err(|e| format!("写入拣选状态失败: {}", e))
+}
+
+fn load_state(repo: &Repository) -> Result<SequenceState, String> {
+    let in_progress = matches!(
+        repo.state(),
+        RepositoryState::CherryPick
+            | RepositoryState::CherryPickSequence
+            | RepositoryState::Revert
+            | RepositoryState::RevertSequence
+    ) || sequencer_dir(repo).join(STATE_FILE).is_file();
+    if !in_progress {
+        return Err("当前没有进行中的拣选或撤销".to_string());
+    }
+
+    let content = fs::read_to_string(sequencer_dir(repo).join(STATE_FILE))
+        .map_err(|_| "当前的拣选或撤销不是由 Prism 发起的，请在命令行中处理".to_string())?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("无法读取拣选状态: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::slice;
+
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    /// main 合并了修改 b.txt 的 topic 分支，另有一个不相关的 side 分支
+    fn with_merge() -> TempRepo {
+        let repo = TempRepo::new("sequencer");
+        repo.write("a.txt", "a\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("b.txt", "b\n");
+        repo.commit("add b");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("c.txt", "c\n");
+        repo.commit("add c");
+        repo.git(&["merge", "-q", "--no-ff", "-m", "merge topic", "topic"]);
+        repo.git(&["checkout", "-q", "-b", "side", "main~2"]);
+        repo
+    }
+
+    #[test]
+    fn cherry_pick_applies_oldest_first_and_reports_skipped() {
+        let repo = TempRepo::new("sequencer-pick");
+        repo.write("a.txt", "a\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("b.txt", "b\n");
+        let first = repo.commit("add b");
+        repo.write("c.txt", "c\n");
+        let second = repo.commit("add c");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("b.txt", "b\n");
+        repo.commit("same b");
+
+        let result = cherry_pick(repo.path(), &[second.clone(), first.clone()], None, false).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(result.skipped, vec![first]);
+        assert_eq!(result.commit_ids, vec![repo.rev_parse("HEAD")]);
+        assert_eq!(repo.git(&["log", "--format=%s", "-2"]), "add c\nsame b");
+        assert!(!repo.dir().join(".git/sequencer").exists());
+    }
+
+    #[test]
+    fn mainline_must_match_merge_commits() {
+        let repo = with_merge();
+        let merge = repo.rev_parse("main");
+        let plain = repo.rev_parse("main~1");
+
+        assert!(cherry_pick(repo.path(), slice::from_ref(&merge), None, false).is_err());
+        assert!(cherry_pick(repo.path(), slice::from_ref(&plain), Some(1), false).is_err());
+        assert!(cherry_pick(repo.path(), &[merge.clone(), plain], Some(1), false).is_err());
+        assert!(cherry_pick(repo.path(), slice::from_ref(&merge), Some(3), false).is_err());
+        assert_eq!(repo.git(&["rev-parse", "HEAD"]), repo.rev_parse("main~2"));
+
+        let result = cherry_pick(repo.path(), &[merge], Some(1), false).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(repo.read("b.txt").as_deref(), Some("b\n"));
+        assert_eq!(repo.read("c.txt"), None);
+    }
+
+    #[test]
+    fn revert_merge_against_mainline() {
+        let repo = with_merge();
+        repo.git(&["checkout", "-q", "main"]);
+        let merge = repo.rev_parse("main");
+        let first_parent = repo.rev_parse("main^1");
+
+        let result = revert(repo.path(), slice::from_ref(&merge), Some(1), false).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(repo.read("b.txt"), None);
+        assert_eq!(repo.read("c.txt").as_deref(), Some("c\n"));
+        assert_eq!(
+            repo.git(&["log", "-1", "--format=%B"]),
+            format!(
+                "Revert \"merge topic\"\n\nThis reverts commit {}, reversing\nchanges made to {}.",
+                merge, first_parent
+            )
+        );
+    }
+
+    #[test]
+    fn conflict_then_continue_or_abort() {
+        let repo = TempRepo::new("sequencer-conflict");
+        repo.write("a.txt", "base\n");
+        repo.commit("base");
+        repo.git(&["checkout", "-q", "-b", "topic"]);
+        repo.write("a.txt", "topic\n");
+        let picked = repo.commit("topic a");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.write("a.txt", "main\n");
+        let head = repo.commit("main a");
+
+        let result = cherry_pick(repo.path(), slice::from_ref(&picked), None, false).unwrap();
+        assert_eq!(result.status, "conflicted");
+        assert_eq!(result.current.as_deref(), Some(picked.as_str()));
+        assert_eq!(result.conflicts, vec!["a.txt".to_string()]);
+        sequence_abort(repo.path()).unwrap();
+        assert_eq!(repo.rev_parse("HEAD"), head);
+        assert_eq!(repo.read("a.txt").as_deref(), Some("main\n"));
+
+        cherry_pick(repo.path(), &[picked], None, false).unwrap();
+        assert!(sequence_continue(repo.path()).is_err());
+        repo.write("a.txt", "resolved\n");
+        repo.git(&["add", "a.txt"]);
+        let result = sequence_continue(repo.path()).unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(result.commit_ids, vec![repo.rev_parse("HEAD")]);
+        assert_eq!(repo.git(&["log", "-1", "--format=%s"]), "topic a");
+        assert!(sequence_abort(repo.path()).is_err());
+    }
+}
@@ -0,0 +1,633 @@
+use std::fs;
+use std::path::Path;
+
+use git2::{build::CheckoutBuilder, Commit, Index, IndexEntry, IndexTime, Oid, Repository, StashFlags, StatusOptions};
+
+use super::{head_commit, open, short_id, signature};
+use crate::models::*;
+
+const STASH_REF: &str = "refs/stash";
+
+/// 贮藏整个工作区（等价于 `git stash push`）
+///
+/// `paths` 非空时只贮藏匹配的路径，其余修改保留在工作区（`git stash push -- <paths>`）。
+pub fn stash_push(
+    path: &str,
+    message: Option<&str>,
+    paths: &[String],
+    include_untracked: bool,
+) -> Result<String, String> {
+    let mut repo = open(path)?;
+    let signature = signature(&repo)?;
+
+    if paths.is_empty() {
+        let flags = if include_untracked {
+            StashFlags::INCLUDE_UNTRACKED
+        } else {
+            StashFlags::DEFAULT
+        };
+        let oid = repo.stash_save2(&signature, message, Some(flags))
+            .map_err(|e| format!("贮藏失败: {}", e))?;
+        return Ok(oid.to_string());
+    }
+
+    stash_paths(&repo, message, paths, include_untracked)
+}
+
+/// 只贮藏选中的 hunk（等价于 `git stash -p`）
+///
+/// hunk 以 HEAD 与工作区之间的 diff 为准，即 `get_file_diff` 返回的内容。
+pub fn stash_push_hunks(
+    path: &str,
+    message: Option<&str>,
+    hunks: &[HunkSelection],
+) -> Result<String, String> {
+    let repo = open(path)?;
+    if hunks.is_empty() {
+        return Err("没有可贮藏的修改".to_string());
+    }
+
+    let head = head_commit(&repo)?;
+    let head_tree = head.tree()
+        .map_err(|e| format!("无法获取树: {}", e))?;
+    let index_tree = write_index_tree(&repo)?;
+    let workdir = workdir(&repo)?;
+
+    let mut file_paths: Vec<&str> = hunks.iter().map(|h| h.file_path.as_str()).collect();
+    file_paths.sort();
+    file_paths.dedup();
+
+    let mut stash_index = tree_to_index(&index_tree)?;
+    let mut remaining = Index::new()
+        .map_err(|e| format!("创建索引失败: {}", e))?;
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force().update_index(false);
+
+    for file_path in file_paths {
+        let head_entry = head_tree.get_path(Path::new(file_path))
+            .map_err(|_| format!("只能按 hunk 贮藏已跟踪的文件: {}", file_path))?;
+        let index_entry = index_tree.get_path(Path::new(file_path)).ok();
+        if index_entry.map(|e| e.id()) != Some(head_entry.id()) {
+            return Err(format!("文件存在已暂存的修改，无法按 hunk 贮藏: {}", file_path));
+        }
+
+        // 工作区内容按 clean 过滤（autocrlf、eol、filter 等）转换后再比较，与 get_file_diff 的 hunk 一致
+        let old = repo.find_blob(head_entry.id())
+            .map_err(|e| format!("无法读取文件: {}", e))?;
+        let new = repo.blob_path(&workdir.join(file_path))
+            .and_then(|id| repo.find_blob(id))
+            .map_err(|e| format!("无法读取文件 {}: {}", file_path, e))?;
+
+        let selected: Vec<&HunkSelection> = hunks.iter()
+            .filter(|h| h.file_path == file_path)
+            .collect();
+        let (stashed, kept) = split_hunks(old.content(), new.content(), &selected)?;
+
+        let mode = head_entry.filemode() as u32;
+        let blob = repo.blob(&stashed)
+            .map_err(|e| format!("写入对象失败: {}", e))?;
+        stash_index.add(&index_entry_for(file_path, blob, mode))
+            .map_err(|e| format!("更新索引失败: {}", e))?;
+        let blob = repo.blob(&kept)
+            .map_err(|e| format!("写入对象失败: {}", e))?;
+        remaining.add(&index_entry_for(file_path, blob, mode))
+            .map_err(|e| format!("更新索引失败: {}", e))?;
+        checkout.path(file_path);
+    }
+
+    let oid = write_stash(&repo, &head, &index_tree, &mut stash_index, None, message)?;
+
+    // 留在工作区的版本通过检出写回，应用与检出时相同的 smudge 过滤，不修改仓库索引
+    repo.checkout_index(Some(&mut remaining), Some(&mut checkout))
+        .map_err(|e| format!("无法写入工作区: {}", e))?;
+
+    Ok(oid.to_string())
+}
+
+/// 列出贮藏
+pub fn list_stashes(path: &str) -> Result<Vec<StashEntry>, String> {
+    let mut repo = open(path)?;
+    let mut stashes = Vec::new();
+
+    repo.stash_foreach(|index, message, oid| {
+        stashes.push(StashEntry {
+            index,
+            id: oid.to_string(),
+            message: message.to_string(),
+        });
+        true
+    }).map_err(|e| format!("读取贮藏失败: {}", e))?;
+
+    Ok(stashes)
+}
+
+/// 应用贮藏，`pop` 为 true 时成功后删除该贮藏
+///
+/// 默认与 `git stash apply` 一样只恢复工作区，已暂存的修改也作为未暂存修改恢复；
+/// `restore_index` 为 true 时同时恢复暂存区（`git stash apply --index`）。
+pub fn stash_apply(path: &str, index: usize, pop: bool, restore_index: bool) -> Result<(), String> {
+    let mut repo = open(path)?;
+    let mut opts = git2::StashApplyOptions::new();
+    if restore_index {
+        opts.reinstantiate_index();
+    }
+
+    if pop {
+        repo.stash_pop(index, Some(&mut opts))
+    } else {
+        repo.stash_apply(index, Some(&mut opts))
+    }
+    .map_err(|e| format!("应用贮藏失败: {}", e))
+}
+
+/// 删除贮藏
+pub fn stash_drop(path: &str, index: usize) -> Result<(), String> {
+    let mut repo = open(path)?;
+    repo.stash_drop(index)
+        .map_err(|e| format!("删除贮藏失败: {}", e))
+}
+
+/// 把索引和已跟踪文件的工作区内容写入对象库，返回 (索引树, 工作区树)，不修改工作区
+pub(crate) fn snapshot_worktree(repo: &Repository) -> Result<(Oid, Oid), String> {
+    let index_tree = write_index_tree(repo)?;
+    let workdir = workdir(repo)?;
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false);
+    let statuses = repo.statuses(Some(&mut opts))
+        .map_err(|e| format!("获取状态失败: {}", e))?;
+
+    let mut snapshot = tree_to_index(&index_tree)?;
+    for entry in statuses.iter() {
+        if let Some(file_path) = entry.path() {
+            add_workdir_file(repo, &mut snapshot, &workdir, file_path)?;
+        }
+    }
+    let worktree_tree = snapshot.write_tree_to(repo)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+
+    Ok((index_tree.id(), worktree_tree))
+}
+
+/// 按路径贮藏：工作区版本写入贮藏，随后将这些路径的索引和工作区恢复到 HEAD
+fn stash_paths(
+    repo: &Repository,
+    message: Option<&str>,
+    paths: &[String],
+    include_untracked: bool,
+) -> Result<String, String> {
+    let head = head_commit(repo)?;
+    let head_tree = head.tree()
+        .map_err(|e| format!("无法获取树: {}", e))?;
+    let index_tree = write_index_tree(repo)?;
+    let workdir = workdir(repo)?;
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(include_untracked)
+        .recurse_untracked_dirs(include_untracked);
+    for p in paths {
+        opts.pathspec(p);
+    }
+    let statuses = repo.statuses(Some(&mut opts))
+        .map_err(|e| format!("获取状态失败: {}", e))?;
+
+    let mut tracked = Vec::new();
+    let mut untracked = Vec::new();
+    for entry in statuses.iter() {
+        let Some(file_path) = entry.path() else { continue };
+        let status = entry.status();
+        if status.contains(git2::Status::WT_NEW) {
+            untracked.push(file_path.to_string());
+        } else if !status.is_ignored() && status != git2::Status::CURRENT {
+            tracked.push(file_path.to_string());
+        }
+    }
+
+    if tracked.is_empty() && untracked.is_empty() {
+        return Err("没有可贮藏的修改".to_string());
+    }
+
+    let mut stash_index = tree_to_index(&index_tree)?;
+    for file_path in &tracked {
+        add_workdir_file(repo, &mut stash_index, &workdir, file_path)?;
+    }
+
+    let untracked_tree = if untracked.is_empty() {
+        None
+    } else {
+        let mut untracked_index = Index::new()
+            .map_err(|e| format!("创建索引失败: {}", e))?;
+        for file_path in &untracked {
+            add_workdir_file(repo, &mut untracked_index, &workdir, file_path)?;
+        }
+        Some(untracked_index)
+    };
+
+    let oid = write_stash(repo, &head, &index_tree, &mut stash_index, untracked_tree, message)?;
+
+    // 恢复索引和工作区
+    let mut index = repo.index()
+        .map_err(|e| format!("无法读取索引: {}", e))?;
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+    let mut has_checkout = false;
+    for file_path in &tracked {
+        let p = Path::new(file_path);
+        match head_tree.get_path(p) {
+            Ok(entry) => {
+                index.add(&index_entry_for(file_path, entry.id(), entry.filemode() as u32))
+                    .map_err(|e| format!("更新索引失败: {}", e))?;
+                checkout.path(file_path);
+                has_checkout = true;
+            }
+            Err(_) => {
+                let _ = index.remove_path(p);
+                remove_workdir_file(&workdir, file_path)?;
+            }
+        }
+    }
+    index.write()
+        .map_err(|e| format!("写入索引失败: {}", e))?;
+    if has_checkout {
+        repo.checkout_index(Some(&mut index), Some(&mut checkout))
+            .map_err(|e| format!("恢复工作区失败: {}", e))?;
+    }
+    for file_path in &untracked {
+        remove_workdir_file(&workdir, file_path)?;
+    }
+
+    Ok(oid.to_string())
+}
+
+/// 按 git stash 的格式写入贮藏提交并更新 refs/stash
+fn write_stash(
+    repo: &Repository,
+    head: &Commit,
+    index_tree: &git2::Tree,
+    stash_index: &mut Index,
+    untracked_index: Option<Index>,
+    message: Option<&str>,
+) -> Result<Oid, String> {
+    let signature = signature(repo)?;
+
+    let branch = repo.head().ok()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand().map(|s| s.to_string()))
+        .unwrap_or_else(|| "(no branch)".to_string());
+    let head_desc = format!("{} {}", short_id(head.as_object()), head.summary().unwrap_or(""));
+
+    let index_commit = repo.commit(
+        None,
+        &signature,
+        &signature,
+        &format!("index on {}: {}\n", branch, head_desc),
+        index_tree,
+        &[head],
+    ).map_err(|e| format!("创建贮藏提交失败: {}", e))?;
+    let index_commit = repo.find_commit(index_commit)
+        .map_err(|e| format!("创建贮藏提交失败: {}", e))?;
+
+    let untracked_commit = match untracked_index {
+        Some(mut untracked_index) => {
+            let tree_id = untracked_index.write_tree_to(repo)
+                .map_err(|e| format!("写入树失败: {}", e))?;
+            let tree = repo.find_tree(tree_id)
+                .map_err(|e| format!("写入树失败: {}", e))?;
+            let oid = repo.commit(
+                None,
+                &signature,
+                &signature,
+                &format!("untracked files on {}: {}\n", branch, head_desc),
+                &tree,
+                &[],
+            ).map_err(|e| format!("创建贮藏提交失败: {}", e))?;
+            Some(repo.find_commit(oid).map_err(|e| format!("创建贮藏提交失败: {}", e))?)
+        }
+        None => None,
+    };
+
+    let stash_tree_id = stash_index.write_tree_to(repo)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+    let stash_tree = repo.find_tree(stash_tree_id)
+        .map_err(|e| format!("写入树失败: {}", e))?;
+
+    let stash_message = match message {
+        Some(msg) if !msg.trim().is_empty() => format!("On {}: {}", branch, msg.trim()),
+        _ => format!("WIP on {}: {}", branch, head_desc),
+    };
+
+    let mut parents = vec![head, &index_commit];
+    if let Some(ref untracked_commit) = untracked_commit {
+        parents.push(untracked_commit);
+    }
+    let stash_oid = repo.commit(
+        None,
+        &signature,
+        &signature,
+        &format!("{}\n", stash_message),
+        &stash_tree,
+        &parents,
+    ).map_err(|e| format!("创建贮藏提交失败: {}", e))?;
+
+    repo.reference_ensure_log(STASH_REF)
+        .map_err(|e| format!("更新贮藏引用失败: {}", e))?;
+    repo.reference(STASH_REF, stash_oid, true, &stash_message)
+        .map_err(|e| format!("更新贮藏引用失败: {}", e))?;
+
+    Ok(stash_oid)
+}
+
+/// 按选中的 hunk 拆分文件内容，返回 (贮藏版本, 留在工作区的版本)
+fn split_hunks(
+    old: &[u8],
+    new: &[u8],
+    selected: &[&HunkSelection],
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let patch = git2::Patch::from_buffers(old, None, new, None, None)
+        .map_err(|e| format!("创建 diff 失败: {}", e))?;
+    let old_lines: Vec<&[u8]> = old.split_inclusive(|b| *b == b'\n').collect();
+
+    let mut stashed = Vec::new();
+    let mut kept = Vec::new();
+    let mut old_pos = 0usize;
+    let mut matched = 0usize;
+
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_idx)
+            .map_err(|e| format!("读取 hunk 失败: {}", e))?;
+        let is_selected = selected.iter().any(|s| {
+            s.old_start == hunk.old_start()
+                && s.old_lines == hunk.old_lines()
+                && s.new_start == hunk.new_start()
+                && s.new_lines == hunk.new_lines()
+        });
+        if is_selected {
+            matched += 1;
+        }
+
+        // 纯新增的 hunk 插入在 old_start 之后
+        let hunk_begin = if hunk.old_lines() == 0 {
+            hunk.old_start() as usize
+        } else {
+            hunk.old_start() as usize - 1
+        };
+        for line in &old_lines[old_pos..hunk_begin] {
+            stashed.extend_from_slice(line);
+            kept.extend_from_slice(line);
+        }
+        old_pos = hunk_begin;
+
+        for line_idx in 0..line_count {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)
+                .map_err(|e| format!("读取 diff 行失败: {}", e))?;
+            match line.origin() {
+                ' ' => {
+                    stashed.extend_from_slice(line.content());
+                    kept.extend_from_slice(line.content());
+                    old_pos += 1;
+                }
+                '-' => {
+                    if is_selected {
+                        kept.extend_from_slice(line.content());
+                    } else {
+                        stashed.extend_from_slice(line.content());
+                    }
+                    old_pos += 1;
+                }
+                '+' => {
+                    if is_selected {
+                        stashed.extend_from_slice(line.content());
+                    } else {
+                        kept.extend_from_slice(line.content());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if matched != selected.len() {
+        return Err("选中的 hunk 与当前 diff 不一致，请刷新后重试".to_string());
+    }
+
+    for line in &old_lines[old_pos.min(old_lines.len())..] {
+        stashed.extend_from_slice(line);
+        kept.extend_from_slice(line);
+    }
+
+    Ok((stashed, kept))
+}
+
+fn workdir(repo: &Repository) -> Result<std::path::PathBuf, String> {
+    repo.workdir()
+        .map(|p| p.to_path_buf())
+        .ok_or_else(|| "裸仓库不支持贮藏".to_string())
+}
+
+fn write_index_tree(repo: &Repository) -> Result<git2::Tree<'_>, String> {
+    let tree_id = repo.index()
+        .and_then(|mut index| index.write_tree())
+        .map_err(|e| format!("写入索引树失败: {}", e))?;
+    repo.find_tree(tree_id)
+        .map_err(|e| format!("写入索引树失败: {}", e))
+}
+
+fn tree_to_index(tree: &git2::Tree) -> Result<Index, String> {
+    let mut index = Index::new()
+        .map_err(|e| format!("创建索引失败: {}", e))?;
+    index.read_tree(tree)
+        .map_err(|e| format!("读取树失败: {}", e))?;
+    Ok(index)
+}
+
+/// 把工作区文件写入索引，文件已删除时移除对应条目
+fn add_workdir_file(
+    repo: &Repository,
+    index: &mut Index,
+    workdir: &Path,
+    file_path: &str,
+) -> Result<(), String> {
+    let full_path = workdir.join(file_path);
+    let metadata = match fs::symlink_metadata(&full_path) {
+        Ok(metadata) => metadata,
+        Err(_) => {
+            let _ = index.remove_path(Path::new(file_path));
+            return Ok(());
+        }
+    };
+
+    let (blob, mode) = if metadata.file_type().is_symlink() {
+        let target = fs::read_link(&full_path)
+            .map_err(|e| format!("无法读取文件 {}: {}", file_path, e))?;
+        let blob = repo.blob(target.to_string_lossy().as_bytes())
+            .map_err(|e| format!("写入对象失败: {}", e))?;
+        (blob, 0o120000)
+    } else {
+        let blob = repo.blob_path(&full_path)
+            .map_err(|e| format!("写入对象失败: {}", e))?;
+        (blob, file_mode(&metadata))
+    };
+
+    index.add(&index_entry_for(file_path, blob, mode))
+        .map_err(|e| format!("更新索引失败: {}", e))
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    if metadata.permissions().mode() & 0o111 != 0 {
+        0o100755
+    } else {
+        0o100644
+    }
+}
+
+#[cfg(not(unix))]
+fn file_mode(_metadata: &fs::Metadata) -> u32 {
+    0o100644
+}
+
+fn remove_workdir_file(workdir: &Path, file_path: &str) -> Result<(), String> {
+    let full_path = workdir.join(file_path);
+    match fs::remove_file(&full_path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("无法删除文件 {}: {}", file_path, e)),
+    }
+
+    // 清理因此变空的目录
+    let mut dir = full_path.parent();
+    while let Some(d) = dir {
+        if d == workdir || fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+    Ok(())
+}
+
+fn index_entry_for(file_path: &str, id: Oid, mode: u32) -> IndexEntry {
+    IndexEntry {
+        ctime: IndexTime::new(0, 0),
+        mtime: IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size: 0,
+        id,
+        flags: 0,
+        flags_extended: 0,
+        path: file_path.as_bytes().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::get_file_diff;
+    use crate::git_ops::test_repo::TempRepo;
+
+    fn numbered(count: usize, eol: &str) -> String {
+        (1..=count).map(|i| format!("line {}{}", i, eol)).collect()
+    }
+
+    fn selection(file_path: &str, hunk: &DiffHunk) -> HunkSelection {
+        HunkSelection {
+            file_path: file_path.to_string(),
+            old_start: hunk.old_start,
+            old_lines: hunk.old_lines,
+            new_start: hunk.new_start,
+            new_lines: hunk.new_lines,
+        }
+    }
+
+    #[test]
+    fn split_hunks_separates_selected_changes() {
+        let old = numbered(20, "\n");
+        let new = old.replace("line 2\n", "changed 2\n").replace("line 19\n", "changed 19\nadded\n");
+        let patch = git2::Patch::from_buffers(old.as_bytes(), None, new.as_bytes(), None, None).unwrap();
+        assert_eq!(patch.num_hunks(), 2);
+        let (hunk, _) = patch.hunk(1).unwrap();
+        let second = HunkSelection {
+            file_path: "a.txt".to_string(),
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+        };
+
+        let (stashed, kept) = split_hunks(old.as_bytes(), new.as_bytes(), &[&second]).unwrap();
+        assert_eq!(String::from_utf8(stashed).unwrap(), old.replace("line 19\n", "changed 19\nadded\n"));
+        assert_eq!(String::from_utf8(kept).unwrap(), old.replace("line 2\n", "changed 2\n"));
+
+        let stale = HunkSelection { old_start: 5, ..second };
+        assert!(split_hunks(old.as_bytes(), new.as_bytes(), &[&stale]).is_err());
+    }
+
+    #[test]
+    fn partial_stash_round_trip() {
+        let repo = TempRepo::new("stash-hunks");
+        let original = numbered(30, "\n");
+        repo.write("a.txt", &original);
+        repo.commit("initial");
+        let modified = original.replace("line 2\n", "changed 2\n").replace("line 28\n", "changed 28\n");
+        repo.write("a.txt", &modified);
+
+        let diff = get_file_diff(repo.path(), "a.txt").unwrap();
+        assert_eq!(diff.hunks.len(), 2);
+        stash_push_hunks(repo.path(), Some("first hunk"), &[selection("a.txt", &diff.hunks[0])]).unwrap();
+
+        assert_eq!(repo.read("a.txt").unwrap(), original.replace("line 28\n", "changed 28\n"));
+        assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "");
+        assert_eq!(list_stashes(repo.path()).unwrap()[0].message, "On main: first hunk");
+
+        repo.git(&["checkout", "--", "a.txt"]);
+        stash_apply(repo.path(), 0, true, false).unwrap();
+        assert_eq!(repo.read("a.txt").unwrap(), original.replace("line 2\n", "changed 2\n"));
+        assert!(list_stashes(repo.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn partial_stash_applies_eol_filters() {
+        let repo = TempRepo::new("stash-crlf");
+        repo.git(&["config", "core.autocrlf", "true"]);
+        repo.write("a.txt", &numbered(30, "\r\n"));
+        repo.commit("initial");
+        let modified = numbered(30, "\r\n").replace("line 2\r\n", "changed 2\r\n").replace("line 28\r\n", "changed 28\r\n");
+        repo.write("a.txt", &modified);
+
+        let diff = get_file_diff(repo.path(), "a.txt").unwrap();
+        assert_eq!(diff.hunks.len(), 2);
+        stash_push_hunks(repo.path(), None, &[selection("a.txt", &diff.hunks[1])]).unwrap();
+
+        assert_eq!(
+            repo.read("a.txt").unwrap(),
+            numbered(30, "\r\n").replace("line 2\r\n", "changed 2\r\n")
+        );
+        assert_eq!(
+            repo.git(&["show", "stash@{0}:a.txt"]) + "\n",
+            numbered(30, "\n").replace("line 28\n", "changed 28\n")
+        );
+    }
+
+    #[test]
+    fn apply_restores_index_only_when_requested() {
+        let repo = TempRepo::new("stash-index");
+        repo.write("a.txt", "a\n");
+        repo.commit("initial");
+
+        for (restore_index, staged) in [(false, ""), (true, "a.txt")] {
+            repo.write("a.txt", &format!("staged {}\n", restore_index));
+            repo.git(&["add", "a.txt"]);
+            stash_push(repo.path(), None, &[], false).unwrap();
+            assert_eq!(repo.git(&["status", "--porcelain"]), "");
+
+            stash_apply(repo.path(), 0, true, restore_index).unwrap();
+            assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), staged);
+            assert_eq!(repo.read("a.txt").unwrap(), format!("staged {}\n", restore_index));
+            repo.git(&["reset", "-q", "--hard"]);
+        }
+    }
+}
@@ -0,0 +1,797 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use git2::{
+    AutotagOption, Direction, FetchOptions, FetchPrune, Oid, PushOptions, Remote, RemoteCallbacks,
+    Repository,
+};
+
+use super::credentials::{resolve_ssh_url, CredentialProvider, SshHost};
+use super::merge::merge;
+use super::{open, short_id};
+use super::rebase::rebase;
+use crate::models::*;
+
+/// 传输进度事件的最小发送间隔，避免大仓库传输时事件过多
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 远程操作中与前端交互的回调：传输进度、凭据输入（返回 None 表示用户取消）
+/// 和可选的取消标记
+pub struct RemoteHandlers<'a> {
+    pub progress: &'a dyn Fn(RemoteProgress),
+    pub prompt: &'a dyn Fn(CredentialPrompt) -> Option<CredentialAnswer>,
+    pub cancel: Option<&'a AtomicBool>,
+}
+
+impl RemoteHandlers<'_> {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// 已设置取消标记时返回 cancelled 错误
+    pub(crate) fn check_cancelled(&self) -> Result<(), RemoteError> {
+        match self.is_cancelled() {
+            true => Err(RemoteError {
+                kind: "cancelled".to_string(),
+                message: "操作已取消".to_string(),
+            }),
+            false => Ok(()),
+        }
+    }
+
+    /// 分类远程操作的错误，因取消标记中止时报告为 cancelled
+    pub(crate) fn remote_error(
+        &self,
+        provider: &CredentialProvider,
+        error: git2::Error,
+        context: &str,
+    ) -> RemoteError {
+        match self.check_cancelled() {
+            Err(cancelled) => cancelled,
+            Ok(()) => provider.remote_error(error, context),
+        }
+    }
+}
+
+/// 从远程仓库获取更新（`git fetch`）
+///
+/// `remote` 为空时使用当前分支的上游远程，其次为 origin。`prune` 为 false 时
+/// 仍遵循 `fetch.prune` 配置；`tags` 为 true 时获取全部标签。
+pub fn fetch(
+    path: &str,
+    remote: Option<&str>,
+    prune: bool,
+    tags: bool,
+    handlers: &RemoteHandlers,
+) -> Result<FetchResult, RemoteError> {
+    let repo = open(path)?;
+    let remote_name = match remote {
+        Some(remote) => remote.to_string(),
+        None => default_remote(&repo)?,
+    };
+    fetch_remote(&repo, &remote_name, prune, tags, handlers)
+}
+
+/// 获取上游分支的更新并整合到当前分支（`git pull`）
+///
+/// `strategy` 为空时依次按 `branch.<name>.rebase`、`pull.rebase`、`pull.ff` 配置决定整合方式；
+/// 均未配置且本地与上游已分叉时不做任何修改，返回 `divergent` 和双方领先的提交数，由调用方选择方式。
+/// 采用合并时 `pull.ff` 决定是否快进，采用变基时 `rebase.autoStash` 决定是否自动贮藏。
+pub fn pull(
+    path: &str,
+    strategy: Option<PullStrategy>,
+    handlers: &RemoteHandlers,
+) -> Result<PullResult, RemoteError> {
+    let repo = open(path)?;
+    let branch = current_branch(&repo)?;
+    let upstream = upstream_name(&repo, &branch)?;
+
+    let remote_name = repo.branch_upstream_remote(&branch)
+        .ok()
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+        .ok_or_else(|| "当前分支没有设置上游分支".to_string())?;
+    // 上游为本地分支时（remote = .）无需获取
+    if remote_name != "." {
+        fetch_remote(&repo, &remote_name, false, false, handlers)?;
+    }
+
+    let local_id = repo.refname_to_id(&branch)
+        .map_err(|e| format!("无法解析当前分支: {}", e))?;
+    let upstream_id = repo.refname_to_id(&upstream)
+        .map_err(|e| format!("无法解析上游分支 {}: {}", upstream, e))?;
+    let (ahead, behind) = repo.graph_ahead_behind(local_id, upstream_id)
+        .map_err(|e| format!("比较分支失败: {}", e))?;
+
+    let config = repo.config()
+        .map_err(|e| format!("读取配置失败: {}", e))?;
+    let strategy = pull_strategy(&repo, branch.trim_start_matches("refs/heads/"), strategy)?;
+    let no_ff = pull_ff(&config) == Some(PullFf::NoFf);
+
+    let mut result = PullResult {
+        status: "up_to_date".to_string(),
+        strategy,
+        upstream: upstream.clone(),
+        commit_id: None,
+        conflicts: Vec::new(),
+        ahead,
+        behind,
+        message: None,
+    };
+    if behind == 0 {
+        return Ok(result);
+    }
+
+    match strategy {
+        Some(PullStrategy::Rebase) if ahead > 0 => {
+            let autostash = config.get_bool("rebase.autoStash").unwrap_or(false);
+            let rebased = rebase(path, &upstream, None, autostash)?;
+            result.status = match rebased.status.as_str() {
+                "completed" => "rebased",
+                _ => "conflicted",
+            }
+            .to_string();
+            result.conflicts = rebased.conflicts;
+            result.message = rebased.message;
+            if result.status == "rebased" {
+                result.commit_id = open(path)?.head().ok()
+                    .and_then(|head| head.target())
+                    .map(|id| id.to_string());
+            }
+            Ok(result)
+        }
+        None | Some(PullStrategy::FfOnly) if ahead > 0 => {
+            result.status = "divergent".to_string();
+            result.message = Some(match strategy {
+                Some(_) => "本地分支与上游已分叉，无法快进".to_string(),
+                None => "本地分支与上游已分叉，请选择合并或变基".to_string(),
+            });
+            Ok(result)
+        }
+        _ => {
+            let policy = match strategy {
+                Some(PullStrategy::Merge) if no_ff => MergePolicy::NoFf,
+                Some(PullStrategy::Merge) => MergePolicy::Ff,
+                _ => MergePolicy::FfOnly,
+            };
+            let merged = merge(path, &upstream, Some(policy), None)?;
+            result.status = merged.status;
+            result.commit_id = merged.commit_id;
+            result.conflicts = merged.conflicts;
+            Ok(result)
+        }
+    }
+}
+
+/// 拉取分支 `branch`（短名）时实际采用的整合方式，调用方指定的方式优先于配置
+pub(super) fn pull_strategy(
+    repo: &Repository,
+    branch: &str,
+    strategy: Option<PullStrategy>,
+) -> Result<Option<PullStrategy>, String> {
+    if strategy.is_some() {
+        return Ok(strategy);
+    }
+    let config = repo.config()
+        .map_err(|e| format!("读取配置失败: {}", e))?;
+    Ok(configured_pull_strategy(&config, branch))
+}
+
+/// 按 git 的优先级读取配置中的整合方式，`branch.<name>.rebase` 优先于 `pull.rebase`
+///
+/// rebase 取值 `true`、`merges`、`interactive` 均按变基处理；未配置 rebase 时
+/// `pull.ff = only` 表示仅快进，`pull.ff` 为其它值表示合并，均未配置时返回 None。
+fn configured_pull_strategy(config: &git2::Config, branch: &str) -> Option<PullStrategy> {
+    let rebase = config.get_string(&format!("branch.{}.rebase", branch))
+        .or_else(|_| config.get_string("pull.rebase"))
+        .ok();
+    if let Some(value) = rebase {
+        return match parse_bool(&value) {
+            Some(false) => Some(PullStrategy::Merge),
+            _ => Some(PullStrategy::Rebase),
+        };
+    }
+
+    match pull_ff(config)? {
+        PullFf::Only => Some(PullStrategy::FfOnly),
+        PullFf::Ff | PullFf::NoFf => Some(PullStrategy::Merge),
+    }
+}
+
+/// `pull.ff` 配置的取值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PullFf {
+    Ff,
+    NoFf,
+    Only,
+}
+
+/// 读取 `pull.ff`：`only` 单独处理，其余按 git 的布尔值规则解析，无法识别的值按允许快进处理
+fn pull_ff(config: &git2::Config) -> Option<PullFf> {
+    let value = config.get_string("pull.ff").ok()?;
+    if value.trim().eq_ignore_ascii_case("only") {
+        return Some(PullFf::Only);
+    }
+    match parse_bool(&value) {
+        Some(false) => Some(PullFf::NoFf),
+        _ => Some(PullFf::Ff),
+    }
+}
+
+/// 按 git 的规则解析布尔配置值，不区分大小写，空值为 false
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" | "" => Some(false),
+        _ => None,
+    }
+}
+
+/// 推送分支到远程仓库（`git push`）
+///
+/// `branch` 为空时推送当前分支，目标为已配置的上游分支，否则为远程的同名分支。
+/// `ForcePush::WithLease` 仅在远程分支仍与本地记录的远程跟踪分支一致时才覆盖。
+pub fn push(
+    path: &str,
+    remote: Option<&str>,
+    branch: Option<&str>,
+    force: Option<ForcePush>,
+    set_upstream: bool,
+    tags: bool,
+    handlers: &RemoteHandlers,
+) -> Result<PushResult, RemoteError> {
+    let repo = open(path)?;
+    let (local_ref, remote_name, dst) = push_destination(&repo, remote, branch)?;
+    push_ref(&repo, &local_ref, &remote_name, &dst, force, set_upstream, tags, handlers)
+}
+
+/// 首次推送分支并设置上游分支（`git push -u <remote> <branch>[:<remote_branch>]`）
+///
+/// `branch` 为空时推送当前分支，`remote` 为空时使用分支的推送远程，
+/// `remote_branch` 为空时推送到远程的同名分支，忽略已有的上游配置。
+pub fn publish_branch(
+    path: &str,
+    branch: Option<&str>,
+    remote: Option<&str>,
+    remote_branch: Option<&str>,
+    handlers: &RemoteHandlers,
+) -> Result<PushResult, RemoteError> {
+    let repo = open(path)?;
+    let (local_ref, remote_name, _) = push_destination(&repo, remote, branch)?;
+    let dst = match remote_branch.map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(name) if name.starts_with("refs/heads/") => name.to_string(),
+        Some(name) => format!("refs/heads/{}", name),
+        None => local_ref.clone(),
+    };
+    push_ref(&repo, &local_ref, &remote_name, &dst, None, true, false, handlers)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_ref(
+    repo: &Repository,
+    local_ref: &str,
+    remote_name: &str,
+    dst: &str,
+    force: Option<ForcePush>,
+    set_upstream: bool,
+    tags: bool,
+    handlers: &RemoteHandlers,
+) -> Result<PushResult, RemoteError> {
+    let local_id = repo.refname_to_id(local_ref)
+        .map_err(|e| format!("无法找到分支 {}: {}", local_ref, e))?;
+    let remote = find_remote(repo, remote_name)?;
+
+    let prefix = if force.is_some() { "+" } else { "" };
+    let mut specs = vec![format!("{}{}:{}", prefix, local_ref, dst)];
+    if tags {
+        let tag_names = repo.tag_names(None)
+            .map_err(|e| format!("读取标签失败: {}", e))?;
+        for tag in tag_names.iter().flatten() {
+            specs.push(format!("refs/tags/{0}:refs/tags/{0}", tag));
+        }
+    }
+
+    let tracking = tracking_ref(&remote, dst);
+    // 没有远程跟踪分支时，要求远程分支也不存在
+    let lease = match force {
+        Some(ForcePush::WithLease) => Some(
+            tracking.as_deref()
+                .and_then(|name| repo.refname_to_id(name).ok())
+                .unwrap_or_else(Oid::zero),
+        ),
+        _ => None,
+    };
+
+    let url = remote.pushurl().or(remote.url())
+        .ok_or_else(|| format!("远程仓库 {} 没有配置地址", remote_name))?
+        .to_string();
+    let (mut connection, ssh_host) = connect_to(repo, remote, &url)?;
+    let anonymous = connection.name().is_none();
+    let mut provider = CredentialProvider::new(handlers.prompt, repo.config().ok(), ssh_host);
+
+    let mut refs = Vec::new();
+    let result = {
+        let mut callbacks = callbacks(handlers, &mut provider);
+        callbacks.push_negotiation(|updates| {
+            let Some(expected) = lease else { return Ok(()) };
+            for update in updates {
+                if update.dst_refname() == Some(dst) && update.src() != expected {
+                    return Err(git2::Error::from_str(&format!(
+                        "远程分支 {} 已被更新（{}），与本地记录的 {} 不一致，请先获取更新",
+                        dst,
+                        short_oid(repo, update.src()),
+                        short_oid(repo, expected)
+                    )));
+                }
+            }
+            Ok(())
+        });
+        callbacks.push_update_reference(|reference, message| {
+            (handlers.progress)(RemoteProgress::PushUpdate {
+                reference: reference.to_string(),
+                message: message.map(|s| s.to_string()),
+            });
+            refs.push(PushRefStatus {
+                reference: reference.to_string(),
+                status: if message.is_some() { "rejected" } else { "ok" }.to_string(),
+                message: message.map(|s| s.to_string()),
+            });
+            Ok(())
+        });
+
+        let mut opts = PushOptions::new();
+        opts.remote_callbacks(callbacks);
+        connection.push(&specs, Some(&mut opts))
+    };
+    result.map_err(|e| handlers.remote_error(&provider, e, "推送失败"))?;
+
+    let pushed = refs.iter().any(|r| r.reference == dst && r.status == "ok");
+    // 匿名连接不会更新远程跟踪分支，需要手动更新
+    if anonymous && pushed {
+        if let Some(tracking) = &tracking {
+            repo.reference(tracking, local_id, true, "update by push")
+                .map_err(|e| format!("更新远程跟踪分支失败: {}", e))?;
+        }
+    }
+
+    let upstream = if set_upstream && pushed {
+        set_branch_upstream(repo, local_ref, remote_name, dst)?;
+        tracking.map(|name| name.strip_prefix("refs/remotes/").unwrap_or(&name).to_string())
+    } else {
+        None
+    };
+
+    Ok(PushResult {
+        remote: remote_name.to_string(),
+        refs,
+        upstream,
+    })
+}
+
+pub(crate) fn fetch_remote(
+    repo: &Repository,
+    remote_name: &str,
+    prune: bool,
+    tags: bool,
+    handlers: &RemoteHandlers,
+) -> Result<FetchResult, RemoteError> {
+    let remote = find_remote(repo, remote_name)?;
+    let url = remote.url()
+        .ok_or_else(|| format!("远程仓库 {} 没有配置地址", remote_name))?
+        .to_string();
+    let refspecs: Vec<String> = remote.fetch_refspecs()
+        .map_err(|e| format!("读取 refspec 失败: {}", e))?
+        .iter()
+        .flatten()
+        .map(|s| s.to_string())
+        .collect();
+    let (mut connection, ssh_host) = connect_to(repo, remote, &url)?;
+    let mut provider = CredentialProvider::new(handlers.prompt, repo.config().ok(), ssh_host);
+
+    // 匿名连接没有配置的 refspec，需要显式传入
+    let (refspecs, reflog) = match connection.name() {
+        Some(_) => (Vec::new(), None),
+        None => (refspecs, Some(format!("fetch {}", remote_name))),
+    };
+
+    let mut updated = Vec::new();
+    let result = {
+        let mut callbacks = callbacks(handlers, &mut provider);
+        callbacks.update_tips(|name, old, new| {
+            updated.push(RefUpdate {
+                name: name.to_string(),
+                old_id: (!old.is_zero()).then(|| old.to_string()),
+                new_id: (!new.is_zero()).then(|| new.to_string()),
+            });
+            true
+        });
+
+        let mut opts = FetchOptions::new();
+        opts.remote_callbacks(callbacks);
+        if prune {
+            opts.prune(FetchPrune::On);
+        }
+        if tags {
+            opts.download_tags(AutotagOption::All);
+        }
+        connection.fetch(&refspecs, Some(&mut opts), reflog.as_deref())
+    };
+    result.map_err(|e| handlers.remote_error(&provider, e, "获取失败"))?;
+
+    let stats = connection.stats();
+    Ok(FetchResult {
+        remote: remote_name.to_string(),
+        updated,
+        received_objects: stats.received_objects(),
+        received_bytes: stats.received_bytes(),
+    })
+}
+
+fn find_remote<'r>(repo: &'r Repository, name: &str) -> Result<Remote<'r>, String> {
+    repo.find_remote(name)
+        .map_err(|e| format!("无法找到远程仓库 {}: {}", name, e))
+}
+
+/// 远程仓库当前存在的分支所对应的远程跟踪分支（基于 `git ls-remote`）
+pub(crate) fn remote_tracking_refs(
+    repo: &Repository,
+    remote_name: &str,
+    handlers: &RemoteHandlers,
+) -> Result<Vec<String>, RemoteError> {
+    let remote = find_remote(repo, remote_name)?;
+    let url = remote.url()
+        .ok_or_else(|| format!("远程仓库 {} 没有配置地址", remote_name))?
+        .to_string();
+    let (mut connection, ssh_host) = connect_to(repo, remote, &url)?;
+    let mut provider = CredentialProvider::new(handlers.prompt, repo.config().ok(), ssh_host);
+
+    let heads = {
+        let callbacks = callbacks(handlers, &mut provider);
+        connection.connect_auth(Direction::Fetch, Some(callbacks), None)
+            .and_then(|connected| {
+                let heads = connected.list()?;
+                Ok(heads.iter().map(|head| head.name().to_string()).collect::<Vec<_>>())
+            })
+    };
+    let heads = heads.map_err(|e| handlers.remote_error(&provider, e, "读取远程分支失败"))?;
+
+    let remote = find_remote(repo, remote_name)?;
+    Ok(heads.iter().filter_map(|head| tracking_ref(&remote, head)).collect())
+}
+
+/// 返回用于传输的连接；SSH 地址中的主机别名按 `~/.ssh/config` 解析后地址改变时，
+/// 改用解析后的地址建立匿名连接
+fn connect_to<'r>(
+    repo: &'r Repository,
+    remote: Remote<'r>,
+    url: &str,
+) -> Result<(Remote<'r>, SshHost), String> {
+    match resolve_ssh_url(url) {
+        Some((resolved, ssh_host)) if resolved != url => {
+            let connection = repo.remote_anonymous(&resolved)
+                .map_err(|e| format!("无法连接远程仓库 {}: {}", resolved, e))?;
+            Ok((connection, ssh_host))
+        }
+        Some((_, ssh_host)) => Ok((remote, ssh_host)),
+        None => Ok((remote, SshHost::default())),
+    }
+}
+
+/// 创建转发传输进度和提供凭据的回调，设置取消标记后在下一次进度回调时中止传输
+pub(crate) fn callbacks<'a, 'b: 'a>(
+    handlers: &'a RemoteHandlers<'b>,
+    provider: &'a mut CredentialProvider<'b>,
+) -> RemoteCallbacks<'a> {
+    let progress = handlers.progress;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        if handlers.is_cancelled() {
+            return Err(git2::Error::from_str("操作已取消"));
+        }
+        provider.credentials(url, username, allowed)
+    });
+
+    let mut last_transfer: Option<Instant> = None;
+    callbacks.transfer_progress(move |stats| {
+        let done = stats.received_objects() == stats.total_objects()
+            && stats.indexed_deltas() == stats.total_deltas();
+        if done || due(&mut last_transfer) {
+            progress(RemoteProgress::Transfer {
+                received_objects: stats.received_objects(),
+                total_objects: stats.total_objects(),
+                indexed_objects: stats.indexed_objects(),
+                indexed_deltas: stats.indexed_deltas(),
+                total_deltas: stats.total_deltas(),
+                received_bytes: stats.received_bytes(),
+            });
+        }
+        !handlers.is_cancelled()
+    });
+
+    callbacks.sideband_progress(move |data| {
+        let message = String::from_utf8_lossy(data).trim().to_string();
+        if !message.is_empty() {
+            progress(RemoteProgress::Sideband { message });
+        }
+        !handlers.is_cancelled()
+    });
+
+    let mut last_push: Option<Instant> = None;
+    callbacks.push_transfer_progress(move |current, total, bytes| {
+        if current == total || due(&mut last_push) {
+            progress(RemoteProgress::PushTransfer { current, total, bytes });
+        }
+    });
+
+    callbacks
+}
+
+pub(super) fn due(last: &mut Option<Instant>) -> bool {
+    let now = Instant::now();
+    match last {
+        Some(last) if now.duration_since(*last) < PROGRESS_INTERVAL => false,
+        _ => {
+            *last = Some(now);
+            true
+        }
+    }
+}
+
+/// 当前分支的完整引用名
+fn current_branch(repo: &Repository) -> Result<String, String> {
+    let head = repo.head()
+        .map_err(|e| format!("无法获取 HEAD: {}", e))?;
+    if !head.is_branch() {
+        return Err("当前处于分离 HEAD 状态，没有所在分支".to_string());
+    }
+    head.name()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "无法获取当前分支".to_string())
+}
+
+fn upstream_name(repo: &Repository, branch: &str) -> Result<String, String> {
+    repo.branch_upstream_name(branch)
+        .ok()
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+        .ok_or_else(|| "当前分支没有设置上游分支".to_string())
+}
+
+/// 默认远程：当前分支的上游远程，其次为 origin，仓库只有一个远程时使用该远程
+fn default_remote(repo: &Repository) -> Result<String, String> {
+    if let Ok(branch) = current_branch(repo) {
+        if let Some(remote) = repo.branch_upstream_remote(&branch).ok()
+            .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+            .filter(|remote| remote != ".")
+        {
+            return Ok(remote);
+        }
+    }
+
+    let remotes = repo.remotes()
+        .map_err(|e| format!("读取远程仓库失败: {}", e))?;
+    let names: Vec<&str> = remotes.iter().flatten().collect();
+    if names.contains(&"origin") {
+        return Ok("origin".to_string());
+    }
+    match names.as_slice() {
+        [only] => Ok(only.to_string()),
+        [] => Err("仓库没有配置远程仓库".to_string()),
+        _ => Err("请指定远程仓库".to_string()),
+    }
+}
+
+/// 推送目标远程：`branch.<name>.pushRemote`、`remote.pushDefault`，其次为默认远程
+fn push_remote(repo: &Repository, local_ref: &str) -> Result<String, String> {
+    let branch = local_ref.strip_prefix("refs/heads/").unwrap_or(local_ref);
+    if let Ok(config) = repo.config() {
+        let configured = config.get_string(&format!("branch.{}.pushRemote", branch))
+            .or_else(|_| config.get_string("remote.pushDefault"))
+            .or_else(|_| config.get_string(&format!("branch.{}.remote", branch)));
+        if let Ok(remote) = configured {
+            if remote != "." {
+                return Ok(remote);
+            }
+        }
+    }
+    default_remote(repo)
+}
+
+/// 推送的本地分支、目标远程和远程分支名；参数为空时使用当前分支及其推送远程
+pub(crate) fn push_destination(
+    repo: &Repository,
+    remote: Option<&str>,
+    branch: Option<&str>,
+) -> Result<(String, String, String), String> {
+    let local_ref = match branch {
+        Some(branch) if branch.starts_with("refs/heads/") => branch.to_string(),
+        Some(branch) => format!("refs/heads/{}", branch),
+        None => current_branch(repo)?,
+    };
+    let remote_name = match remote {
+        Some(remote) => remote.to_string(),
+        None => push_remote(repo, &local_ref)?,
+    };
+    let dst = merge_ref(repo, &local_ref, &remote_name).unwrap_or_else(|| local_ref.clone());
+    Ok((local_ref, remote_name, dst))
+}
+
+/// 分支在指定远程上配置的上游分支名（`branch.<name>.merge`）
+fn merge_ref(repo: &Repository, local_ref: &str, remote_name: &str) -> Option<String> {
+    let branch = local_ref.strip_prefix("refs/heads/")?;
+    let config = repo.config().ok()?;
+    let remote = config.get_string(&format!("branch.{}.remote", branch)).ok()?;
+    if remote != remote_name {
+        return None;
+    }
+    config.get_string(&format!("branch.{}.merge", branch)).ok()
+}
+
+/// 指定远程上的远程分支对应的远程跟踪分支
+pub(crate) fn tracking_ref_of(repo: &Repository, remote_name: &str, dst: &str) -> Option<String> {
+    let remote = repo.find_remote(remote_name).ok()?;
+    tracking_ref(&remote, dst)
+}
+
+/// 根据远程的 fetch refspec 计算远程分支对应的远程跟踪分支
+fn tracking_ref(remote: &Remote, dst: &str) -> Option<String> {
+    remote.refspecs()
+        .filter(|spec| spec.direction() == Direction::Fetch && spec.src_matches(dst))
+        .find_map(|spec| spec.transform(dst).ok()?.as_str().map(|s| s.to_string()))
+}
+
+fn set_branch_upstream(
+    repo: &Repository,
+    local_ref: &str,
+    remote_name: &str,
+    merge: &str,
+) -> Result<(), String> {
+    let branch = local_ref.strip_prefix("refs/heads/").unwrap_or(local_ref);
+    let mut config = repo.config()
+        .map_err(|e| format!("读取配置失败: {}", e))?;
+    config.set_str(&format!("branch.{}.remote", branch), remote_name)
+        .and_then(|_| config.set_str(&format!("branch.{}.merge", branch), merge))
+        .map_err(|e| format!("设置上游分支失败: {}", e))
+}
+
+/// 推送协商中用于提示的提交 id，远程上的新提交可能不在本地，此时无法缩写，给出完整 id
+fn short_oid(repo: &Repository, oid: Oid) -> String {
+    if oid.is_zero() {
+        return "不存在".to_string();
+    }
+    repo.find_object(oid, None)
+        .map(|object| short_id(&object))
+        .unwrap_or_else(|_| oid.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    fn config_with(repo: &TempRepo, entries: &[(&str, &str)]) -> git2::Config {
+        for (key, value) in entries {
+            repo.git(&["config", key, value]);
+        }
+        open(repo.path()).unwrap().config().unwrap().snapshot().unwrap()
+    }
+
+    fn quiet(_: RemoteProgress) {}
+
+    fn no_credentials(_: CredentialPrompt) -> Option<CredentialAnswer> {
+        None
+    }
+
+    fn handlers() -> RemoteHandlers<'static> {
+        RemoteHandlers { progress: &quiet, prompt: &no_credentials, cancel: None }
+    }
+
+    /// 裸仓库作为远程，返回已推送一个提交的两个克隆
+    fn remote_with_clones() -> (TempRepo, TempRepo, TempRepo) {
+        let remote = TempRepo::bare("sync-remote");
+        let first = TempRepo::clone_of(&remote, "sync-first");
+        first.write("a.txt", "a\n");
+        first.commit("initial");
+        first.git(&["push", "-q", "-u", "origin", "main"]);
+        let second = TempRepo::clone_of(&remote, "sync-second");
+        (remote, first, second)
+    }
+
+    #[test]
+    fn fetch_prunes_deleted_remote_branches() {
+        let (_remote, first, second) = remote_with_clones();
+        second.git(&["push", "-q", "origin", "main:topic"]);
+
+        fetch(first.path(), None, false, false, &handlers()).unwrap();
+        assert!(first.try_git(&["rev-parse", "--verify", "-q", "origin/topic"]));
+
+        second.git(&["push", "-q", "origin", ":topic"]);
+        fetch(first.path(), None, false, false, &handlers()).unwrap();
+        assert!(first.try_git(&["rev-parse", "--verify", "-q", "origin/topic"]));
+        fetch(first.path(), None, true, false, &handlers()).unwrap();
+        assert!(!first.try_git(&["rev-parse", "--verify", "-q", "origin/topic"]));
+    }
+
+    #[test]
+    fn pull_fast_forwards_or_reports_divergence() {
+        let (_remote, first, second) = remote_with_clones();
+        first.write("b.txt", "b\n");
+        let pushed = first.commit("from first");
+        first.git(&["push", "-q"]);
+
+        let result = pull(second.path(), None, &handlers()).unwrap();
+        assert_eq!((result.status.as_str(), result.ahead, result.behind), ("fast_forward", 0, 1));
+        assert_eq!(second.rev_parse("HEAD"), pushed);
+
+        first.write("c.txt", "c\n");
+        first.commit("second from first");
+        first.git(&["push", "-q"]);
+        second.write("d.txt", "d\n");
+        let local = second.commit("from second");
+
+        let result = pull(second.path(), None, &handlers()).unwrap();
+        assert_eq!((result.status.as_str(), result.ahead, result.behind), ("divergent", 1, 1));
+        assert!(result.message.is_some());
+        assert_eq!(second.rev_parse("HEAD"), local);
+    }
+
+    #[test]
+    fn push_sets_upstream() {
+        let (_remote, first, _second) = remote_with_clones();
+        first.git(&["checkout", "-q", "-b", "topic"]);
+        first.write("t.txt", "t\n");
+        let topic = first.commit("topic");
+
+        let result = push(first.path(), None, None, None, true, false, &handlers()).unwrap();
+        assert_eq!(result.refs[0].status, "ok");
+        assert_eq!(result.upstream.as_deref(), Some("origin/topic"));
+        assert_eq!(first.git(&["rev-parse", "--abbrev-ref", "topic@{upstream}"]), "origin/topic");
+        assert_eq!(first.rev_parse("origin/topic"), topic);
+    }
+
+    #[test]
+    fn lease_push_is_rejected_after_remote_moved() {
+        let (remote, first, second) = remote_with_clones();
+        second.write("b.txt", "b\n");
+        let moved = second.commit("from second");
+        second.git(&["push", "-q"]);
+
+        // first 的远程跟踪分支仍停留在 second 推送之前
+        first.write("a.txt", "rewritten\n");
+        first.git(&["commit", "-q", "-a", "--amend", "-m", "rewritten"]);
+        assert!(push(first.path(), None, None, Some(ForcePush::WithLease), false, false, &handlers()).is_err());
+        assert_eq!(remote.rev_parse("main"), moved);
+
+        fetch(first.path(), None, false, false, &handlers()).unwrap();
+        let result = push(first.path(), None, None, Some(ForcePush::WithLease), false, false, &handlers()).unwrap();
+        assert_eq!(result.refs[0].status, "ok");
+        assert_eq!(remote.rev_parse("main"), first.rev_parse("HEAD"));
+    }
+
+    #[test]
+    fn pull_ff_follows_git_boolean_rules() {
+        for (value, expected) in [
+            ("false", PullFf::NoFf),
+            ("No", PullFf::NoFf),
+            ("off", PullFf::NoFf),
+            ("0", PullFf::NoFf),
+            ("true", PullFf::Ff),
+            ("yes", PullFf::Ff),
+            ("only", PullFf::Only),
+            ("ONLY", PullFf::Only),
+        ] {
+            let repo = TempRepo::new("pull-ff");
+            let config = config_with(&repo, &[("pull.ff", value)]);
+            assert_eq!(pull_ff(&config), Some(expected), "pull.ff = {}", value);
+        }
+    }
+
+    #[test]
+    fn configured_strategy_prefers_branch_rebase() {
+        let repo = TempRepo::new("pull-strategy");
+        let config = config_with(&repo, &[("pull.ff", "only")]);
+        assert_eq!(configured_pull_strategy(&config, "main"), Some(PullStrategy::FfOnly));
+
+        let config = config_with(&repo, &[("pull.rebase", "true"), ("branch.main.rebase", "off")]);
+        assert_eq!(configured_pull_strategy(&config, "main"), Some(PullStrategy::Merge));
+        assert_eq!(configured_pull_strategy(&config, "other"), Some(PullStrategy::Rebase));
+    }
+}
@@ -0,0 +1,128 @@
+//! 测试用的临时仓库，提交历史通过 git 命令行构造，与被测代码的实现相互独立
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 临时目录中的仓库，离开作用域时删除
+pub(crate) struct TempRepo {
+    dir: PathBuf,
+}
+
+impl TempRepo {
+    /// 创建默认分支为 main 的空仓库
+    pub fn new(name: &str) -> Self {
+        let repo = Self::empty_dir(name);
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo.configure();
+        repo
+    }
+
+    /// 创建空的裸仓库，用作远程仓库
+    pub fn bare(name: &str) -> Self {
+        let repo = Self::empty_dir(name);
+        repo.git(&["init", "-q", "--bare", "-b", "main"]);
+        repo
+    }
+
+    /// 克隆 `remote` 得到的仓库
+    pub fn clone_of(remote: &TempRepo, name: &str) -> Self {
+        let repo = Self::empty_dir(name);
+        repo.git(&["clone", "-q", &remote.url(), "."]);
+        repo.configure();
+        repo
+    }
+
+    /// 只创建空目录，不初始化仓库
+    pub fn empty_dir(name: &str) -> Self {
+        let dir = std::env::temp_dir()
+            .join("prism-tests")
+            .join(format!("{}-{}", name, uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path(&self) -> &str {
+        self.dir.to_str().unwrap()
+    }
+
+    pub fn url(&self) -> String {
+        format!("file://{}", self.path())
+    }
+
+    /// 执行 git 命令并返回标准输出（去掉首尾空白），命令失败时 panic
+    pub fn git(&self, args: &[&str]) -> String {
+        self.git_with_env(args, &[])
+    }
+
+    /// 带额外环境变量执行 git 命令，如指定提交时间的 `GIT_AUTHOR_DATE`
+    pub fn git_with_env(&self, args: &[&str], env: &[(&str, &str)]) -> String {
+        let output = self.command(args).envs(env.iter().copied()).output().unwrap();
+        assert!(
+            output.status.success(),
+            "git {} 失败: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// 执行预期可能失败的 git 命令，返回是否成功
+    pub fn try_git(&self, args: &[&str]) -> bool {
+        self.command(args).output().unwrap().status.success()
+    }
+
+    pub fn write(&self, file: &str, content: &str) {
+        let path = self.dir.join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, content).unwrap();
+    }
+
+    /// 暂存全部改动并提交，返回新提交的 id
+    pub fn commit(&self, message: &str) -> String {
+        self.git(&["add", "-A"]);
+        self.git(&["commit", "-q", "--allow-empty", "-m", message]);
+        self.rev_parse("HEAD")
+    }
+
+    /// 读取工作区文件，文件不存在时返回 None
+    pub fn read(&self, file: &str) -> Option<String> {
+        fs::read_to_string(self.dir.join(file)).ok()
+    }
+
+    pub fn rev_parse(&self, rev: &str) -> String {
+        self.git(&["rev-parse", rev])
+    }
+
+    fn configure(&self) {
+        self.git(&["config", "user.name", "Tester"]);
+        self.git(&["config", "user.email", "tester@example.com"]);
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new("git");
+        command
+            .current_dir(&self.dir)
+            .args(args)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_AUTHOR_NAME", "Tester")
+            .env("GIT_AUTHOR_EMAIL", "tester@example.com")
+            .env("GIT_COMMITTER_NAME", "Tester")
+            .env("GIT_COMMITTER_EMAIL", "tester@example.com")
+            .env("GIT_EDITOR", "true");
+        command
+    }
+}
+
+impl Drop for TempRepo {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,228 @@
+use std::io::Read;
+use std::path::Path;
+
+use git2::{ObjectType, Odb, Oid, Repository, Tree};
+
+use super::open;
+use crate::models::*;
+
+/// 未指定上限时读取文件内容的最大字节数
+pub const DEFAULT_BLOB_LIMIT: usize = 1024 * 1024;
+
+/// 判断二进制文件时检查的开头字节数，与 git 相同
+const BINARY_CHECK_LEN: usize = 8000;
+
+const MODE_SYMLINK: i32 = 0o120000;
+const MODE_SUBMODULE: i32 = 0o160000;
+
+/// 列出 `rev` 中目录 `dir`（为空时为根目录）下的一层内容，目录在前，各自按名称排序
+///
+/// 只读取对象头获取文件大小，不会解压文件内容。
+pub fn list_tree(path: &str, rev: &str, dir: Option<&str>) -> Result<Vec<TreeEntryInfo>, String> {
+    let repo = open(path)?;
+    let root = rev_tree(&repo, rev)?;
+    let dir = dir.map(|dir| dir.trim_matches('/')).filter(|dir| !dir.is_empty());
+    let tree = match dir {
+        Some(dir) => root.get_path(Path::new(dir))
+            .and_then(|entry| entry.to_object(&repo))
+            .and_then(|object| object.peel_to_tree())
+            .map_err(|e| format!("{} 中没有目录 {}: {}", rev, dir, e))?,
+        None => root,
+    };
+    let odb = repo.odb()
+        .map_err(|e| format!("无法打开对象库: {}", e))?;
+
+    let mut entries = Vec::new();
+    for entry in tree.iter() {
+        let name = String::from_utf8_lossy(entry.name_bytes()).to_string();
+        let mode = entry.filemode();
+        let kind = match (entry.kind(), mode) {
+            (Some(ObjectType::Tree), _) => "tree",
+            (_, MODE_SUBMODULE) => "submodule",
+            (_, MODE_SYMLINK) => "symlink",
+            _ => "blob",
+        };
+        let size = match kind {
+            "blob" | "symlink" => odb.read_header(entry.id()).ok().map(|(size, _)| size as u64),
+            _ => None,
+        };
+
+        entries.push(TreeEntryInfo {
+            path: match dir {
+                Some(dir) => format!("{}/{}", dir, name),
+                None => name.clone(),
+            },
+            name,
+            kind: kind.to_string(),
+            mode: format!("{:06o}", mode),
+            id: entry.id().to_string(),
+            size,
+        });
+    }
+
+    entries.sort_by(|a, b| (a.kind != "tree").cmp(&(b.kind != "tree")).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+/// 读取 `rev` 中文件的内容，最多返回 `limit` 字节（默认 1 MiB）
+///
+/// 先读取对象头获取大小，超过上限的文件只读取开头部分，不会把整个文件读入内存。
+/// 按 git 的规则（前 8000 字节含 NUL 字节）判断二进制文件，二进制文件不返回内容；
+/// 文本按 UTF-8 解码，无效的字节替换为 U+FFFD。
+pub fn read_blob(path: &str, rev: &str, file_path: &str, limit: Option<usize>) -> Result<BlobContent, String> {
+    let repo = open(path)?;
+    let file_path = file_path.trim_matches('/');
+    let entry = rev_tree(&repo, rev)?
+        .get_path(Path::new(file_path))
+        .map_err(|e| format!("{} 中没有文件 {}: {}", rev, file_path, e))?;
+    if entry.kind() != Some(ObjectType::Blob) {
+        return Err(format!("{} 不是文件", file_path));
+    }
+    let id = entry.id();
+    let odb = repo.odb()
+        .map_err(|e| format!("无法打开对象库: {}", e))?;
+    let (size, _) = odb.read_header(id)
+        .map_err(|e| format!("读取文件 {} 失败: {}", file_path, e))?;
+
+    let limit = limit.unwrap_or(DEFAULT_BLOB_LIMIT);
+    let truncated = size > limit;
+    let object;
+    let prefix;
+    let data = if truncated {
+        prefix = read_prefix(&odb, id, limit.max(BINARY_CHECK_LEN))
+            .map_err(|e| format!("读取文件 {} 失败: {}", file_path, e))?;
+        &prefix[..]
+    } else {
+        object = odb.read(id)
+            .map_err(|e| format!("读取文件 {} 失败: {}", file_path, e))?;
+        object.data()
+    };
+    let binary = data[..data.len().min(BINARY_CHECK_LEN)].contains(&0);
+    let content = (!binary).then(|| match truncated {
+        true => decode_prefix(&data[..limit]),
+        false => String::from_utf8_lossy(data).to_string(),
+    });
+
+    Ok(BlobContent {
+        path: file_path.to_string(),
+        id: id.to_string(),
+        size: size as u64,
+        binary,
+        truncated,
+        content,
+    })
+}
+
+/// 读取对象开头最多 `len` 字节
+///
+/// 打包的对象不支持流式读取，此时读取整个对象后截取开头部分。
+fn read_prefix(odb: &Odb, id: Oid, len: usize) -> Result<Vec<u8>, String> {
+    match odb.reader(id) {
+        Ok((mut reader, size, _)) => {
+            // 流式读取总是报告读满了缓冲区，读取长度不能超过对象大小
+            let mut prefix = vec![0; len.min(size)];
+            reader.read_exact(&mut prefix).map_err(|e| e.to_string())?;
+            Ok(prefix)
+        }
+        Err(_) => {
+            let object = odb.read(id).map_err(|e| e.message().to_string())?;
+            let data = object.data();
+            Ok(data[..data.len().min(len)].to_vec())
+        }
+    }
+}
+
+fn rev_tree<'r>(repo: &'r Repository, rev: &str) -> Result<Tree<'r>, String> {
+    repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|e| format!("无法解析版本 {}: {}", rev, e))
+}
+
+/// 解码截断后的文本，截断处不完整的多字节字符直接丢弃，不显示为替换字符
+fn decode_prefix(bytes: &[u8]) -> String {
+    let end = match std::str::from_utf8(bytes) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => bytes.len(),
+    };
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_ops::test_repo::TempRepo;
+
+    fn blob_repo() -> TempRepo {
+        let repo = TempRepo::new("tree");
+        repo.write("README.md", "héllo\n");
+        repo.write("src/main.rs", "fn main() {}\n");
+        repo.write("bin.dat", "a\0b");
+        // NUL 出现在前 8000 字节之后时仍按文本处理
+        repo.write("late-nul.txt", &format!("{}\0", "x".repeat(BINARY_CHECK_LEN)));
+        repo.commit("initial");
+        repo.git(&["tag", "v1"]);
+        repo.write("README.md", "changed\n");
+        repo.commit("change readme");
+        repo
+    }
+
+    #[test]
+    fn lists_directories_first() {
+        let repo = blob_repo();
+
+        let root = list_tree(repo.path(), "v1", None).unwrap();
+        let entries: Vec<_> = root.iter()
+            .map(|e| (e.name.as_str(), e.kind.as_str(), e.size))
+            .collect();
+        assert_eq!(entries, vec![
+            ("src", "tree", None),
+            ("README.md", "blob", Some(7)),
+            ("bin.dat", "blob", Some(3)),
+            ("late-nul.txt", "blob", Some(BINARY_CHECK_LEN as u64 + 1)),
+        ]);
+
+        let src = list_tree(repo.path(), "v1", Some("/src/")).unwrap();
+        assert_eq!(src[0].path, "src/main.rs");
+        assert!(list_tree(repo.path(), "v1", Some("README.md")).is_err());
+    }
+
+    #[test]
+    fn reads_text_and_binary_blobs() {
+        let repo = blob_repo();
+
+        let readme = read_blob(repo.path(), "v1", "README.md", None).unwrap();
+        assert_eq!((readme.content.as_deref(), readme.binary, readme.truncated, readme.size), (Some("héllo\n"), false, false, 7));
+        let current = read_blob(repo.path(), "HEAD", "/README.md", None).unwrap();
+        assert_eq!(current.content.as_deref(), Some("changed\n"));
+
+        let binary = read_blob(repo.path(), "v1", "bin.dat", None).unwrap();
+        assert!(binary.binary);
+        assert_eq!(binary.content, None);
+        let late = read_blob(repo.path(), "v1", "late-nul.txt", None).unwrap();
+        assert!(!late.binary);
+
+        assert!(read_blob(repo.path(), "v1", "src", None).is_err());
+        assert!(read_blob(repo.path(), "v1", "missing.txt", None).is_err());
+    }
+
+    #[test]
+    fn truncates_large_blobs() {
+        let repo = blob_repo();
+
+        // 截断处不完整的多字节字符被丢弃
+        let readme = read_blob(repo.path(), "v1", "README.md", Some(2)).unwrap();
+        assert_eq!((readme.content.as_deref(), readme.truncated, readme.size), (Some("h"), true, 7));
+        // 截断时仍检查前 8000 字节判断二进制
+        let binary = read_blob(repo.path(), "v1", "bin.dat", Some(1)).unwrap();
+        assert!(binary.binary && binary.truncated);
+        let late = read_blob(repo.path(), "v1", "late-nul.txt", Some(4)).unwrap();
+        assert_eq!((late.content.as_deref(), late.binary, late.size), (Some("xxxx"), false, BINARY_CHECK_LEN as u64 + 1));
+
+        // 打包后的对象不支持流式读取，结果应当相同
+        repo.git(&["gc", "-q"]);
+        let packed = read_blob(repo.path(), "v1", "README.md", Some(2)).unwrap();
+        assert_eq!((packed.content, packed.truncated, packed.size), (readme.content, true, 7));
+        let packed = read_blob(repo.path(), "v1", "late-nul.txt", Some(4)).unwrap();
+        assert_eq!(packed.content.as_deref(), Some("xxxx"));
+    }
+}
@@ -8,9 +8,14 @@ mod models;
 
 use cli::find_git_root;
 use config::{AppConfig, RepositoryConfig};
+use git_ops::history::HistoryWalk;
+use git_ops::sync::RemoteHandlers;
 use models::*;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager, State};
 
 // Unix-only imports for IPC
@@ -25,8 +30,25 @@ use std::thread;
 struct AppState {
     config: Mutex<AppConfig>,
     initial_repo_path: Mutex<Option<String>>,
+    // 等待前端回答的凭据请求
+    credential_requests: Mutex<HashMap<String, mpsc::Sender<Option<CredentialAnswer>>>>,
+    // 进行中的克隆的取消标记
+    clone_cancellations: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    // 进行中的内容搜索的取消标记
+    pickaxe_cancellations: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    // 提交历史的遍历会话，窗口关闭或同一窗口重新加载同一仓库的历史时移除
+    history_sessions: Mutex<HashMap<String, Arc<Mutex<HistorySession>>>>,
 }
 
+struct HistorySession {
+    window: String,
+    path: String,
+    walk: HistoryWalk,
+}
+
+/// 等待用户输入凭据的最长时间，超时视为取消
+const CREDENTIAL_PROMPT_TIMEOUT: Duration = Duration::from_secs(300);
+
 /// Get the socket path for IPC (Unix only)
 #[cfg(unix)]
 fn get_socket_path() -> PathBuf {
@@ -143,6 +165,123 @@ async fn get_commits(
         .map_err(|e| format!("Task error: {}", e))?
 }
 
+// Tauri 命令：获取一页提交及提交图布局，state 为上一页返回的布局状态
+#[tauri::command]
+async fn get_commit_graph(
+    path: String,
+    limit: usize,
+    offset: usize,
+    state: Option<GraphState>,
+) -> Result<CommitGraphPage, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::graph::get_commit_graph(&path, limit, offset, state)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：按游标获取一页提交历史及提交图布局
+// cursor 为空时按 options 开始新的遍历会话，替换本窗口中同一仓库的旧会话
+#[tauri::command]
+async fn get_history_page(
+    window: tauri::Window,
+    path: String,
+    cursor: Option<String>,
+    limit: usize,
+    options: Option<HistoryOptions>,
+    state: State<'_, AppState>,
+) -> Result<HistoryPage, String> {
+    let (id, offset, session) = match cursor {
+        Some(cursor) => {
+            let (id, offset) = git_ops::history::decode_cursor(&cursor)?;
+            let session = state.history_sessions.lock().map_err(|e| e.to_string())?
+                .get(id)
+                .cloned()
+                .ok_or_else(|| "游标已失效，请重新加载提交历史".to_string())?;
+            (id.to_string(), offset, session)
+        }
+        None => {
+            let walk_path = path.clone();
+            let options = options.unwrap_or_default();
+            let walk = tokio::task::spawn_blocking(move || HistoryWalk::start(&walk_path, &options))
+                .await
+                .map_err(|e| format!("Task error: {}", e))??;
+            let session = Arc::new(Mutex::new(HistorySession {
+                window: window.label().to_string(),
+                path: path.clone(),
+                walk,
+            }));
+            let id = uuid::Uuid::new_v4().to_string();
+
+            let mut sessions = state.history_sessions.lock().map_err(|e| e.to_string())?;
+            sessions.retain(|_, existing| {
+                existing.lock()
+                    .map(|s| s.window != window.label() || s.path != path)
+                    .unwrap_or(false)
+            });
+            sessions.insert(id.clone(), session.clone());
+            (id, 0, session)
+        }
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let mut session = session.lock().map_err(|e| e.to_string())?;
+        if session.path != path {
+            return Err("游标不属于该仓库".to_string());
+        }
+        let page = session.walk.page(offset, limit)?;
+        Ok(HistoryPage {
+            next_cursor: page.next.map(|next| git_ops::history::encode_cursor(&id, next)),
+            total: page.total,
+            complete: page.complete,
+            session: id,
+            commits: page.commits,
+            rows: page.rows,
+        })
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：解析版本表达式，session 为提交历史的遍历会话，用于定位提交在该历史中的位置
+#[tauri::command]
+async fn resolve_revision(
+    path: String,
+    spec: String,
+    session: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<RevisionResolution, String> {
+    let session = match session {
+        Some(id) => Some(
+            state.history_sessions.lock().map_err(|e| e.to_string())?
+                .get(&id)
+                .cloned()
+                .ok_or_else(|| "提交历史会话已失效，请重新加载提交历史".to_string())?,
+        ),
+        None => None,
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let Some(session) = session else {
+            return git_ops::revision::resolve(&path, &spec, None);
+        };
+        let session = session.lock().map_err(|e| e.to_string())?;
+        if session.path != path {
+            return Err("提交历史会话不属于该仓库".to_string());
+        }
+        git_ops::revision::resolve(&path, &spec, Some(&session.walk))
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：结束提交历史的遍历会话
+#[tauri::command]
+fn close_history_session(session: String, state: State<AppState>) -> Result<bool, String> {
+    let mut sessions = state.history_sessions.lock().map_err(|e| e.to_string())?;
+    Ok(sessions.remove(&session).is_some())
+}
+
 // Tauri 命令：获取文件状态
 #[tauri::command]
 async fn get_status(path: String) -> Result<FileStatusResponse, String> {
@@ -159,6 +298,793 @@ async fn get_diff(path: String, file_path: String) -> Result<DiffResponse, Strin
         .map_err(|e| format!("Task error: {}", e))?
 }
 
+// Tauri 命令：获取修改过文件的提交，跟随重命名
+#[tauri::command]
+async fn get_file_history(
+    path: String,
+    file_path: String,
+    rev: Option<String>,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<FileHistoryEntry>, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::file_history::file_history(&path, &file_path, rev.as_deref(), limit, offset)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：获取提交中单个文件的 diff
+#[tauri::command]
+async fn get_commit_file_diff(
+    path: String,
+    commit_id: String,
+    file_path: String,
+    old_path: Option<String>,
+) -> Result<DiffResponse, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::file_history::commit_file_diff(&path, &commit_id, &file_path, old_path.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：列出某个版本中目录下的内容，dir 为空时为根目录
+#[tauri::command]
+async fn list_tree(path: String, rev: String, dir: Option<String>) -> Result<Vec<TreeEntryInfo>, String> {
+    tokio::task::spawn_blocking(move || git_ops::tree::list_tree(&path, &rev, dir.as_deref()))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：读取某个版本中的文件内容，limit 为返回的最大字节数
+#[tauri::command]
+async fn read_blob(
+    path: String,
+    rev: String,
+    file_path: String,
+    limit: Option<usize>,
+) -> Result<BlobContent, String> {
+    tokio::task::spawn_blocking(move || git_ops::tree::read_blob(&path, &rev, &file_path, limit))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：逐行追溯文件内容的来源，rev 为空时追溯工作区中的文件
+#[tauri::command]
+async fn blame_file(
+    path: String,
+    file_path: String,
+    rev: Option<String>,
+    options: Option<BlameFileOptions>,
+) -> Result<BlameResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        git_ops::blame::blame_file(&path, &file_path, rev.as_deref(), &options)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：在历史中搜索内容发生变化的提交，id 由前端生成，用于取消
+// 结果通过 `pickaxe-results` 事件分批发送，全部完成后返回统计
+#[tauri::command]
+async fn search_history_content(
+    window: tauri::Window,
+    id: String,
+    path: String,
+    options: PickaxeOptions,
+    state: State<'_, AppState>,
+) -> Result<PickaxeSummary, String> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    state.pickaxe_cancellations.lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .insert(id.clone(), cancel.clone());
+
+    let event_id = id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let on_matches = |matches: Vec<PickaxeMatch>| {
+            let _ = window.emit_to(window.label(), "pickaxe-results", PickaxeResultsEvent {
+                id: event_id.clone(),
+                matches,
+            });
+        };
+        git_ops::pickaxe::search(&path, &options, &cancel, &on_matches)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e));
+
+    if let Ok(mut cancellations) = state.pickaxe_cancellations.lock() {
+        cancellations.remove(&id);
+    }
+    result?
+}
+
+// Tauri 命令：取消进行中的内容搜索
+#[tauri::command]
+fn cancel_history_search(id: String, state: State<AppState>) -> Result<bool, String> {
+    let cancellations = state.pickaxe_cancellations.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    match cancellations.get(&id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// Tauri 命令：贮藏修改（paths 为空时贮藏整个工作区）
+#[tauri::command]
+async fn stash_push(
+    path: String,
+    message: Option<String>,
+    paths: Vec<String>,
+    include_untracked: bool,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::stash::stash_push(&path, message.as_deref(), &paths, include_untracked)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：只贮藏选中的 hunk
+#[tauri::command]
+async fn stash_push_hunks(
+    path: String,
+    message: Option<String>,
+    hunks: Vec<HunkSelection>,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::stash::stash_push_hunks(&path, message.as_deref(), &hunks)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：获取贮藏列表
+#[tauri::command]
+async fn get_stashes(path: String) -> Result<Vec<StashEntry>, String> {
+    tokio::task::spawn_blocking(move || git_ops::stash::list_stashes(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：应用贮藏
+#[tauri::command]
+async fn stash_apply(
+    path: String,
+    index: usize,
+    pop: bool,
+    restore_index: Option<bool>,
+) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::stash::stash_apply(&path, index, pop, restore_index.unwrap_or(false))
+    })
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：删除贮藏
+#[tauri::command]
+async fn stash_drop(path: String, index: usize) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || git_ops::stash::stash_drop(&path, index))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：合并指定版本到当前分支
+#[tauri::command]
+async fn merge_branch(
+    path: String,
+    source: String,
+    policy: Option<MergePolicy>,
+    message: Option<String>,
+) -> Result<MergeResult, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::merge::merge(&path, &source, policy, message.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：解决冲突后继续合并
+#[tauri::command]
+async fn merge_continue(path: String, message: Option<String>) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || git_ops::merge::merge_continue(&path, message.as_deref()))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：放弃合并
+#[tauri::command]
+async fn merge_abort(path: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || git_ops::merge::merge_abort(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+/// 仓库的受保护分支规则
+fn protected_patterns(state: &AppState, path: &str) -> Result<Vec<String>, String> {
+    Ok(state.config.lock().map_err(|e| e.to_string())?.protected_branches(path))
+}
+
+/// 操作会改写受保护分支的历史且未经确认时拒绝执行，返回 kind 为 protected 的错误
+///
+/// 检查需要读取仓库，应在阻塞任务中调用。
+fn ensure_protected_confirmed(
+    patterns: &[String],
+    confirmed: Option<bool>,
+    check: impl FnOnce(&[String]) -> Result<Option<String>, String>,
+) -> Result<(), RewriteError> {
+    if patterns.is_empty() || confirmed.unwrap_or(false) {
+        return Ok(());
+    }
+    match check(patterns)? {
+        Some(branch) => Err(RewriteError {
+            kind: "protected".to_string(),
+            message: format!("分支 {} 受保护，此操作会改写其历史，需要确认后才能继续", branch),
+            branch: Some(branch),
+        }),
+        None => Ok(()),
+    }
+}
+
+// Tauri 命令：变基当前分支
+#[tauri::command]
+async fn rebase_branch(
+    path: String,
+    upstream: String,
+    onto: Option<String>,
+    autostash: bool,
+    confirm_protected: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<RebaseResult, RewriteError> {
+    let patterns = protected_patterns(&state, &path)?;
+    tokio::task::spawn_blocking(move || {
+        ensure_protected_confirmed(&patterns, confirm_protected, |patterns| {
+            git_ops::protection::check_rebase(&path, &upstream, onto.as_deref(), patterns)
+        })?;
+        Ok(git_ops::rebase::rebase(&path, &upstream, onto.as_deref(), autostash)?)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：生成交互式变基计划
+#[tauri::command]
+async fn get_rebase_plan(
+    path: String,
+    upstream: String,
+    autosquash: Option<bool>,
+) -> Result<Vec<RebaseTodoItem>, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::interactive_rebase::plan(&path, &upstream, autosquash)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：按计划执行交互式变基
+#[tauri::command]
+async fn interactive_rebase(
+    path: String,
+    upstream: String,
+    onto: Option<String>,
+    plan: Vec<RebaseTodoItem>,
+    autostash: bool,
+    confirm_protected: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<RebaseResult, RewriteError> {
+    let patterns = protected_patterns(&state, &path)?;
+    tokio::task::spawn_blocking(move || {
+        ensure_protected_confirmed(&patterns, confirm_protected, |patterns| {
+            git_ops::protection::check_interactive_rebase(&path, &upstream, onto.as_deref(), patterns)
+        })?;
+        Ok(git_ops::interactive_rebase::start(&path, &upstream, onto.as_deref(), plan, autostash)?)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：解决冲突后继续变基
+#[tauri::command]
+async fn rebase_continue(path: String) -> Result<RebaseResult, String> {
+    tokio::task::spawn_blocking(move || git_ops::rebase::rebase_continue(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：跳过当前提交并继续变基
+#[tauri::command]
+async fn rebase_skip(path: String) -> Result<RebaseResult, String> {
+    tokio::task::spawn_blocking(move || git_ops::rebase::rebase_skip(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：放弃变基
+#[tauri::command]
+async fn rebase_abort(path: String) -> Result<Option<String>, String> {
+    tokio::task::spawn_blocking(move || git_ops::rebase::rebase_abort(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：拣选提交
+#[tauri::command]
+async fn cherry_pick(
+    path: String,
+    commits: Vec<String>,
+    mainline: Option<u32>,
+    no_commit: bool,
+) -> Result<SequenceResult, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::sequencer::cherry_pick(&path, &commits, mainline, no_commit)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：撤销提交
+#[tauri::command]
+async fn revert_commits(
+    path: String,
+    commits: Vec<String>,
+    mainline: Option<u32>,
+    no_commit: bool,
+) -> Result<SequenceResult, String> {
+    tokio::task::spawn_blocking(move || {
+        git_ops::sequencer::revert(&path, &commits, mainline, no_commit)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：解决冲突后继续拣选或撤销
+#[tauri::command]
+async fn sequence_continue(path: String) -> Result<SequenceResult, String> {
+    tokio::task::spawn_blocking(move || git_ops::sequencer::sequence_continue(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：放弃拣选或撤销
+#[tauri::command]
+async fn sequence_abort(path: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || git_ops::sequencer::sequence_abort(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：重置当前分支（hard 模式会先创建备份）
+#[tauri::command]
+async fn reset_to_commit(
+    path: String,
+    target: String,
+    mode: ResetMode,
+    confirm_protected: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ResetResult, RewriteError> {
+    let patterns = protected_patterns(&state, &path)?;
+    tokio::task::spawn_blocking(move || {
+        ensure_protected_confirmed(&patterns, confirm_protected, |patterns| {
+            git_ops::protection::check_reset(&path, &target, patterns)
+        })?;
+        Ok(git_ops::reset::reset(&path, &target, mode)?)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：获取重置备份列表
+#[tauri::command]
+async fn get_backups(path: String) -> Result<Vec<BackupEntry>, String> {
+    tokio::task::spawn_blocking(move || git_ops::reset::list_backups(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：恢复重置备份
+#[tauri::command]
+async fn restore_backup(
+    path: String,
+    name: String,
+    confirm_protected: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ResetResult, RewriteError> {
+    let patterns = protected_patterns(&state, &path)?;
+    tokio::task::spawn_blocking(move || {
+        ensure_protected_confirmed(&patterns, confirm_protected, |patterns| {
+            git_ops::protection::check_backup_restore(&path, &name, patterns)
+        })?;
+        Ok(git_ops::reset::restore_backup(&path, &name)?)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：删除重置备份
+#[tauri::command]
+async fn delete_backup(path: String, name: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || git_ops::reset::delete_backup(&path, &name))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：获取引用日志
+#[tauri::command]
+async fn get_reflog(path: String, reference: Option<String>) -> Result<Vec<ReflogEntry>, String> {
+    tokio::task::spawn_blocking(move || git_ops::reflog::get_reflog(&path, reference.as_deref()))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：将引用恢复到引用日志中的某个位置
+#[tauri::command]
+async fn restore_reflog_entry(
+    path: String,
+    reference: Option<String>,
+    index: usize,
+    confirm_protected: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ResetResult, RewriteError> {
+    let patterns = protected_patterns(&state, &path)?;
+    tokio::task::spawn_blocking(move || {
+        ensure_protected_confirmed(&patterns, confirm_protected, |patterns| {
+            git_ops::protection::check_reflog_restore(&path, reference.as_deref(), index, patterns)
+        })?;
+        Ok(git_ops::reflog::restore_reflog_entry(&path, reference.as_deref(), index)?)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+/// 将远程传输进度以 `remote-progress` 事件发送给发起操作的窗口
+fn remote_progress_emitter(
+    window: tauri::Window,
+    path: String,
+    operation: &'static str,
+) -> impl Fn(RemoteProgress) {
+    move |progress| {
+        let _ = window.emit_to(window.label(), "remote-progress", RemoteProgressEvent {
+            path: path.clone(),
+            operation: operation.to_string(),
+            progress,
+        });
+    }
+}
+
+/// 需要凭据时向发起操作的窗口发送 `credential-request` 事件，
+/// 并等待前端通过 `answer_credential_request` 回答
+fn credential_prompter(
+    window: tauri::Window,
+    path: String,
+) -> impl Fn(CredentialPrompt) -> Option<CredentialAnswer> {
+    move |prompt| {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (sender, receiver) = mpsc::channel();
+        let state = window.state::<AppState>();
+        state.credential_requests.lock().ok()?.insert(id.clone(), sender);
+
+        let _ = window.emit_to(window.label(), "credential-request", CredentialRequestEvent {
+            id: id.clone(),
+            path: path.clone(),
+            prompt,
+        });
+        let answer = receiver.recv_timeout(CREDENTIAL_PROMPT_TIMEOUT).ok().flatten();
+
+        if let Ok(mut requests) = state.credential_requests.lock() {
+            requests.remove(&id);
+        }
+        answer
+    }
+}
+
+// Tauri 命令：回答凭据请求，answer 为空表示取消
+#[tauri::command]
+fn answer_credential_request(
+    id: String,
+    answer: Option<CredentialAnswer>,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let sender = state.credential_requests.lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .remove(&id)
+        .ok_or_else(|| "凭据请求已失效".to_string())?;
+    sender.send(answer)
+        .map_err(|_| "凭据请求已失效".to_string())
+}
+
+// Tauri 命令：从远程仓库获取更新
+#[tauri::command]
+async fn fetch_remote(
+    window: tauri::Window,
+    path: String,
+    remote: Option<String>,
+    prune: bool,
+    tags: bool,
+) -> Result<FetchResult, RemoteError> {
+    tokio::task::spawn_blocking(move || {
+        let progress = remote_progress_emitter(window.clone(), path.clone(), "fetch");
+        let prompt = credential_prompter(window, path.clone());
+        let handlers = RemoteHandlers { progress: &progress, prompt: &prompt, cancel: None };
+        git_ops::sync::fetch(&path, remote.as_deref(), prune, tags, &handlers)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：拉取上游分支并按配置或指定方式整合
+#[tauri::command]
+async fn pull_remote(
+    window: tauri::Window,
+    path: String,
+    strategy: Option<PullStrategy>,
+    confirm_protected: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<PullResult, RemoteError> {
+    let patterns = protected_patterns(&state, &path)?;
+    tokio::task::spawn_blocking(move || {
+        ensure_protected_confirmed(&patterns, confirm_protected, |patterns| {
+            git_ops::protection::check_pull(&path, strategy, patterns)
+        })?;
+        let progress = remote_progress_emitter(window.clone(), path.clone(), "pull");
+        let prompt = credential_prompter(window, path.clone());
+        let handlers = RemoteHandlers { progress: &progress, prompt: &prompt, cancel: None };
+        git_ops::sync::pull(&path, strategy, &handlers)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：推送分支到远程仓库
+#[tauri::command]
+async fn push_remote(
+    window: tauri::Window,
+    path: String,
+    remote: Option<String>,
+    branch: Option<String>,
+    force: Option<ForcePush>,
+    set_upstream: bool,
+    tags: bool,
+    confirm_protected: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<PushResult, RemoteError> {
+    let patterns = protected_patterns(&state, &path)?;
+    tokio::task::spawn_blocking(move || {
+        ensure_protected_confirmed(&patterns, confirm_protected, |patterns| {
+            git_ops::protection::check_force_push(&path, remote.as_deref(), branch.as_deref(), force, patterns)
+        })?;
+        let progress = remote_progress_emitter(window.clone(), path.clone(), "push");
+        let prompt = credential_prompter(window, path.clone());
+        let handlers = RemoteHandlers { progress: &progress, prompt: &prompt, cancel: None };
+        git_ops::sync::push(
+            &path,
+            remote.as_deref(),
+            branch.as_deref(),
+            force,
+            set_upstream,
+            tags,
+            &handlers,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：推送新分支并设置上游分支
+#[tauri::command]
+async fn publish_branch(
+    window: tauri::Window,
+    path: String,
+    branch: Option<String>,
+    remote: Option<String>,
+    remote_branch: Option<String>,
+) -> Result<PushResult, RemoteError> {
+    tokio::task::spawn_blocking(move || {
+        let progress = remote_progress_emitter(window.clone(), path.clone(), "push");
+        let prompt = credential_prompter(window, path.clone());
+        let handlers = RemoteHandlers { progress: &progress, prompt: &prompt, cancel: None };
+        git_ops::sync::publish_branch(
+            &path,
+            branch.as_deref(),
+            remote.as_deref(),
+            remote_branch.as_deref(),
+            &handlers,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：获取分支列表
+#[tauri::command]
+async fn get_branches(path: String) -> Result<Vec<BranchInfo>, String> {
+    tokio::task::spawn_blocking(move || git_ops::branch::list_branches(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：设置或修改分支的上游分支
+#[tauri::command]
+async fn set_branch_upstream(path: String, branch: String, upstream: String) -> Result<BranchInfo, String> {
+    tokio::task::spawn_blocking(move || git_ops::branch::set_upstream(&path, &branch, &upstream))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：取消分支的上游分支
+#[tauri::command]
+async fn unset_branch_upstream(path: String, branch: String) -> Result<BranchInfo, String> {
+    tokio::task::spawn_blocking(move || git_ops::branch::unset_upstream(&path, &branch))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：查找已合并、上游已删除和远程已不存在的分支，受保护的分支不会列出
+#[tauri::command]
+async fn scan_branch_cleanup(
+    window: tauri::Window,
+    path: String,
+    base: String,
+    check_remotes: bool,
+    state: State<'_, AppState>,
+) -> Result<CleanupScan, RemoteError> {
+    let patterns = state.config.lock().map_err(|e| e.to_string())?.protected_branches(&path);
+    let mut scan = tokio::task::spawn_blocking(move || {
+        // 只列出远程分支，没有传输进度
+        let progress = |_: RemoteProgress| {};
+        let prompt = credential_prompter(window, path.clone());
+        let handlers = RemoteHandlers { progress: &progress, prompt: &prompt, cancel: None };
+        git_ops::cleanup::scan(&path, &base, check_remotes, &handlers)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))??;
+
+    scan.candidates.retain(|c| c.is_remote || !git_ops::protection::is_protected(&patterns, &c.name));
+    Ok(scan)
+}
+
+// Tauri 命令：批量删除分支并记入恢复日志，受保护的分支不会删除
+#[tauri::command]
+async fn delete_branches(
+    path: String,
+    names: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<CleanupResult, String> {
+    let patterns = state.config.lock().map_err(|e| e.to_string())?.protected_branches(&path);
+    let (protected, names): (Vec<String>, Vec<String>) = names.into_iter().partition(|name| {
+        name.strip_prefix("refs/heads/")
+            .is_some_and(|branch| git_ops::protection::is_protected(&patterns, branch))
+    });
+
+    let mut result = tokio::task::spawn_blocking(move || git_ops::cleanup::delete_branches(&path, &names))
+        .await
+        .map_err(|e| format!("Task error: {}", e))??;
+    result.failures.extend(protected.into_iter().map(|full_name| CleanupFailure {
+        message: format!("分支 {} 受保护", full_name.trim_start_matches("refs/heads/")),
+        full_name,
+    }));
+    Ok(result)
+}
+
+// Tauri 命令：获取已删除分支的恢复日志
+#[tauri::command]
+async fn get_deleted_branches(path: String) -> Result<Vec<DeletedBranch>, String> {
+    tokio::task::spawn_blocking(move || git_ops::cleanup::list_deleted(&path))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：从恢复日志重新创建分支
+#[tauri::command]
+async fn restore_deleted_branch(path: String, index: usize, name: Option<String>) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || git_ops::cleanup::restore_deleted(&path, index, name.as_deref()))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Tauri 命令：获取远程仓库列表
+#[tauri::command]
+fn get_remotes(path: String) -> Result<Vec<RemoteInfo>, String> {
+    git_ops::remote::list_remotes(&path)
+}
+
+// Tauri 命令：添加远程仓库
+#[tauri::command]
+fn add_remote(
+    path: String,
+    name: String,
+    url: String,
+    fetch_refspec: Option<String>,
+) -> Result<RemoteInfo, String> {
+    git_ops::remote::add_remote(&path, &name, &url, fetch_refspec.as_deref())
+}
+
+// Tauri 命令：重命名远程仓库
+#[tauri::command]
+fn rename_remote(path: String, name: String, new_name: String) -> Result<Vec<String>, String> {
+    git_ops::remote::rename_remote(&path, &name, &new_name)
+}
+
+// Tauri 命令：删除远程仓库
+#[tauri::command]
+fn remove_remote(path: String, name: String) -> Result<(), String> {
+    git_ops::remote::remove_remote(&path, &name)
+}
+
+// Tauri 命令：修改远程仓库地址
+#[tauri::command]
+fn set_remote_url(
+    path: String,
+    name: String,
+    url: Option<String>,
+    push: bool,
+) -> Result<RemoteInfo, String> {
+    git_ops::remote::set_remote_url(&path, &name, url.as_deref(), push)
+}
+
+// Tauri 命令：修改远程仓库的 fetch refspec
+#[tauri::command]
+fn set_remote_fetch_refspecs(
+    path: String,
+    name: String,
+    refspecs: Vec<String>,
+) -> Result<RemoteInfo, String> {
+    git_ops::remote::set_fetch_refspecs(&path, &name, &refspecs)
+}
+
+// Tauri 命令：克隆仓库并添加到仓库列表，id 由前端生成，用于取消
+#[tauri::command]
+async fn clone_repository(
+    window: tauri::Window,
+    id: String,
+    url: String,
+    destination: String,
+    options: CloneOptions,
+    state: State<'_, AppState>,
+) -> Result<RepositoryConfig, RemoteError> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    state.clone_cancellations.lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .insert(id.clone(), cancel.clone());
+
+    let events = window.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let progress = remote_progress_emitter(window.clone(), destination.clone(), "clone");
+        let prompt = credential_prompter(window, destination.clone());
+        let handlers = RemoteHandlers { progress: &progress, prompt: &prompt, cancel: Some(&cancel) };
+        git_ops::clone::clone_repository(&url, &destination, &options, &handlers)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e));
+
+    if let Ok(mut cancellations) = state.clone_cancellations.lock() {
+        cancellations.remove(&id);
+    }
+    let path = result??;
+
+    let repo = add_repository(path, None, state)?;
+    let _ = events.emit("repo-list-refresh", ());
+    Ok(repo)
+}
+
+// Tauri 命令：取消进行中的克隆
+#[tauri::command]
+fn cancel_clone(id: String, state: State<AppState>) -> Result<bool, String> {
+    let cancellations = state.clone_cancellations.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    match cancellations.get(&id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
 // Tauri 命令：查找 Git 仓库根目录
 #[tauri::command]
 fn find_repo_root(path: String) -> Result<String, String> {
@@ -224,6 +1150,20 @@ fn remove_repository(id: String, state: State<AppState>) -> Result<bool, String>
     Ok(removed)
 }
 
+// Tauri 命令：设置仓库的受保护分支
+#[tauri::command]
+fn set_protected_branches(
+    id: String,
+    branches: Vec<String>,
+    state: State<AppState>,
+) -> Result<RepositoryConfig, String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+    let repo = config.set_protected_branches(&id, branches)
+        .ok_or_else(|| "Repository not found".to_string())?;
+    config.save()?;
+    Ok(repo)
+}
+
 // Tauri 命令：获取初始仓库路径
 #[tauri::command]
 fn get_initial_repo_path(state: State<AppState>) -> Option<String> {
@@ -251,6 +1191,10 @@ fn main() {
     let state = AppState {
         config: Mutex::new(config),
         initial_repo_path: Mutex::new(initial_repo_path),
+        credential_requests: Mutex::new(HashMap::new()),
+        clone_cancellations: Mutex::new(HashMap::new()),
+        pickaxe_cancellations: Mutex::new(HashMap::new()),
+        history_sessions: Mutex::new(HashMap::new()),
     };
 
     tauri::Builder::default()
@@ -261,18 +1205,83 @@ fn main() {
             start_ipc_server(app.handle().clone());
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                if let Ok(mut sessions) = window.state::<AppState>().history_sessions.lock() {
+                    sessions.retain(|_, session| {
+                        session.lock().map(|s| s.window != window.label()).unwrap_or(false)
+                    });
+                }
+            }
+        })
         .manage(state)
         .invoke_handler(tauri::generate_handler![
             open_repo,
             get_commits,
+            get_commit_graph,
+            get_history_page,
+            close_history_session,
+            resolve_revision,
             get_status,
             get_diff,
+            get_file_history,
+            get_commit_file_diff,
+            list_tree,
+            read_blob,
+            blame_file,
+            search_history_content,
+            cancel_history_search,
+            stash_push,
+            stash_push_hunks,
+            get_stashes,
+            stash_apply,
+            stash_drop,
+            merge_branch,
+            merge_continue,
+            merge_abort,
+            rebase_branch,
+            get_rebase_plan,
+            interactive_rebase,
+            rebase_continue,
+            rebase_skip,
+            rebase_abort,
+            cherry_pick,
+            revert_commits,
+            sequence_continue,
+            sequence_abort,
+            reset_to_commit,
+            get_backups,
+            restore_backup,
+            delete_backup,
+            get_reflog,
+            restore_reflog_entry,
+            fetch_remote,
+            pull_remote,
+            push_remote,
+            publish_branch,
+            get_branches,
+            set_branch_upstream,
+            unset_branch_upstream,
+            scan_branch_cleanup,
+            delete_branches,
+            get_deleted_branches,
+            restore_deleted_branch,
+            answer_credential_request,
+            get_remotes,
+            add_remote,
+            rename_remote,
+            remove_remote,
+            set_remote_url,
+            set_remote_fetch_refspecs,
+            clone_repository,
+            cancel_clone,
             find_repo_root,
             check_is_git_repo,
             init_repository,
             get_repository_list,
             add_repository,
             remove_repository,
+            set_protected_branches,
             get_initial_repo_path,
         ])
         .run(tauri::generate_context!())
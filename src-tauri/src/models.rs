@@ -6,17 +6,45 @@ pub struct RepoInfo {
     pub name: String,
     pub current_branch: String,
     pub is_bare: bool,
+    pub upstream: UpstreamStatus,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct CommitInfo {
     pub id: String,
-    pub short_id: String,
+    pub short_id: String,          // 在仓库中唯一的缩写 id，长度遵循 core.abbrev
     pub message: String,
+    pub summary: String,           // 说明的第一段，合并为一行
+    pub body: String,              // 第一段之后的内容
+    pub encoding: Option<String>,  // 提交中声明的说明编码，未声明时为 UTF-8
     pub author_name: String,
     pub author_email: String,
-    pub timestamp: i64,
+    pub author_timestamp: i64,
+    pub author_offset: i32,        // 作者时区相对 UTC 的偏移（分钟）
+    pub committer_name: String,
+    pub committer_email: String,
+    pub timestamp: i64,            // 提交者时间
+    pub committer_offset: i32,
     pub parent_ids: Vec<String>,
+    pub trailers: Vec<CommitTrailer>,
+    pub refs: Vec<RefLabel>,
+}
+
+/// 提交说明末尾的 trailer，如 `Signed-off-by`、`Co-authored-by`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommitTrailer {
+    pub key: String,
+    pub value: String,
+}
+
+/// 指向提交的引用，用于在历史中标注分支和标签
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RefLabel {
+    pub name: String,
+    pub full_name: String,
+    pub kind: String, // "head", "branch", "remote", "tag"
+    pub is_current: bool, // HEAD 指向的分支
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,6 +58,9 @@ pub struct FileInfo {
 pub struct FileStatusResponse {
     pub unstaged: Vec<FileInfo>,
     pub staged: Vec<FileInfo>,
+    pub conflicted: Vec<FileInfo>,
+    pub state: String, // "clean", "merge", "rebase", "cherry_pick", "revert", ...
+    pub rebase: Option<RebaseProgress>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -54,3 +85,556 @@ pub struct DiffResponse {
     pub file_path: String,
     pub hunks: Vec<DiffHunk>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StashEntry {
+    pub index: usize,
+    pub id: String,
+    pub message: String,
+}
+
+/// 通过 DiffResponse 中的 hunk 头定位要操作的 hunk
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HunkSelection {
+    pub file_path: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    FfOnly,
+    Ff,
+    NoFf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeResult {
+    pub status: String, // "up_to_date", "fast_forward", "merged", "conflicted"
+    pub commit_id: Option<String>,
+    pub conflicts: Vec<String>,
+}
+
+/// 进行中的变基进度，读取自 `.git/rebase-merge` 或 `.git/rebase-apply`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RebaseProgress {
+    pub current: usize,
+    pub total: usize,
+    pub head_name: String,
+    pub onto: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RebaseResult {
+    pub status: String, // "completed", "conflicted", "stopped", "exec_failed"
+    pub progress: Option<RebaseProgress>,
+    pub conflicts: Vec<String>,
+    pub message: Option<String>,
+    pub dropped: Vec<String>, // 本次执行中因改动已包含在新基点中而跳过的提交
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Drop,
+    Exec,
+}
+
+/// 交互式变基计划中的一项，顺序即执行顺序
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RebaseTodoItem {
+    pub action: RebaseAction,
+    pub commit_id: Option<String>, // exec 为空
+    pub summary: String,
+    pub message: Option<String>,   // reword / squash 以及 amend! 产生的 fixup 使用的新提交信息
+    pub command: Option<String>,   // exec 执行的命令
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SequenceResult {
+    pub status: String, // "completed", "conflicted"
+    pub commit_ids: Vec<String>, // 本次新建的提交
+    pub skipped: Vec<String>, // 修改已存在于 HEAD、没有创建提交的提交
+    pub current: Option<String>, // 发生冲突的提交
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetMode {
+    Soft,
+    Mixed,
+    Hard,
+}
+
+/// hard reset 之前保存的 HEAD、索引和工作区快照
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupEntry {
+    pub name: String,
+    pub id: String,
+    pub previous_head: String,
+    pub branch: Option<String>,
+    pub message: String,
+    pub timestamp: i64,
+    pub has_changes: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetResult {
+    pub head: String,
+    pub backup: Option<BackupEntry>,
+}
+
+/// 可能改写分支历史的操作（变基、重置、恢复）失败的原因
+///
+/// `protected` 表示操作会改写受保护分支，需要用户确认后带上 confirm_protected 重试。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RewriteError {
+    pub kind: String,           // protected | other
+    pub message: String,
+    pub branch: Option<String>, // kind 为 protected 时需要确认的分支
+}
+
+impl From<String> for RewriteError {
+    fn from(message: String) -> Self {
+        RewriteError {
+            kind: "other".to_string(),
+            message,
+            branch: None,
+        }
+    }
+}
+
+/// 引用日志中的一条记录，`index` 为 0 时表示最新一次移动（即 `HEAD@{0}`）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReflogEntry {
+    pub index: usize,
+    pub old_id: String,
+    pub new_id: String,
+    pub message: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub timestamp: i64,
+}
+
+/// 远程传输进度，通过 `remote-progress` 事件发送给发起操作的窗口
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RemoteProgress {
+    Transfer {
+        received_objects: usize,
+        total_objects: usize,
+        indexed_objects: usize,
+        indexed_deltas: usize,
+        total_deltas: usize,
+        received_bytes: usize,
+    },
+    Sideband {
+        message: String,
+    },
+    PushTransfer {
+        current: usize,
+        total: usize,
+        bytes: usize,
+    },
+    PushUpdate {
+        reference: String,
+        message: Option<String>,
+    },
+    Checkout {
+        completed: usize,
+        total: usize,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteProgressEvent {
+    pub path: String,
+    pub operation: String, // fetch | pull | push | clone
+    pub progress: RemoteProgress,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForcePush {
+    WithLease,
+    Force,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefUpdate {
+    pub name: String,
+    pub old_id: Option<String>,
+    pub new_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FetchResult {
+    pub remote: String,
+    pub updated: Vec<RefUpdate>,
+    pub received_objects: usize,
+    pub received_bytes: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PushRefStatus {
+    pub reference: String,
+    pub status: String, // ok | rejected
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushResult {
+    pub remote: String,
+    pub refs: Vec<PushRefStatus>,
+    pub upstream: Option<String>,
+}
+
+/// 远程操作需要用户输入凭据时发给前端的请求
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialPrompt {
+    pub kind: String, // user_pass | passphrase
+    pub url: String,
+    pub username: Option<String>,
+    pub key_path: Option<String>,
+    pub attempt: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialRequestEvent {
+    pub id: String,
+    pub path: String,
+    pub prompt: CredentialPrompt,
+}
+
+/// 用户输入的凭据；passphrase 类型只使用 password
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialAnswer {
+    pub username: Option<String>,
+    pub password: String,
+}
+
+/// 远程操作失败的原因，身份验证失败与网络错误分开报告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteError {
+    pub kind: String, // auth | certificate | network | cancelled | protected | other
+    pub message: String,
+}
+
+impl From<String> for RemoteError {
+    fn from(message: String) -> Self {
+        RemoteError {
+            kind: "other".to_string(),
+            message,
+        }
+    }
+}
+
+impl From<RewriteError> for RemoteError {
+    fn from(error: RewriteError) -> Self {
+        RemoteError {
+            kind: error.kind,
+            message: error.message,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteInfo {
+    pub name: String,
+    pub url: Option<String>,
+    pub push_url: Option<String>,
+    pub fetch_refspecs: Vec<String>,
+    pub push_refspecs: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CloneOptions {
+    pub branch: Option<String>,
+    pub depth: Option<u32>,
+    pub recursive: bool,
+    pub bare: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PullStrategy {
+    Merge,
+    Rebase,
+    FfOnly,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PullResult {
+    pub status: String, // up_to_date | fast_forward | merged | rebased | conflicted | divergent
+    pub strategy: Option<PullStrategy>,
+    pub upstream: String,
+    pub commit_id: Option<String>,
+    pub conflicts: Vec<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    pub message: Option<String>,
+}
+
+/// 本地分支与上游分支的跟踪状态
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpstreamStatus {
+    pub status: String, // "none", "gone", "tracking"
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BranchInfo {
+    pub name: String,
+    pub full_name: String,
+    pub is_remote: bool,
+    pub is_head: bool,
+    pub commit_id: Option<String>,
+    pub upstream: Option<UpstreamStatus>, // 远程跟踪分支为 None
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CleanupCandidate {
+    pub name: String,
+    pub full_name: String,
+    pub is_remote: bool,
+    pub reasons: Vec<String>, // "merged", "gone", "stale_remote"
+    pub commit_id: String,
+    pub summary: String,
+    pub author_name: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CleanupScan {
+    pub base: String,
+    pub candidates: Vec<CleanupCandidate>,
+    pub skipped_remotes: Vec<String>, // 无法连接、未检查过期远程跟踪分支的远程
+}
+
+/// 恢复日志中记录的已删除分支
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeletedBranch {
+    pub index: usize,
+    pub full_name: String,
+    pub commit_id: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CleanupFailure {
+    pub full_name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CleanupResult {
+    pub deleted: Vec<String>,
+    pub failures: Vec<CleanupFailure>,
+}
+
+/// 提交图中一行内的线段，列号从 0 开始
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GraphEdge {
+    // "pass": 从上到下穿过本行；"in": 从上方同列连到提交；"fork": 从上方其它列汇入提交（分叉点）；
+    // "out": 从提交连到下方同列的第一个父提交；"merge": 从提交连到下方其它列的父提交
+    pub kind: String,
+    pub from: usize,
+    pub to: usize,
+    pub color: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GraphRow {
+    pub id: String,
+    pub column: usize,
+    pub color: usize,
+    pub width: usize,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GraphLane {
+    pub commit_id: String,
+    pub color: usize,
+}
+
+/// 布局到某一行之后的状态：每一列正在等待的提交，传给下一页继续布局
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GraphState {
+    pub lanes: Vec<Option<GraphLane>>,
+    pub next_color: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommitGraphPage {
+    pub commits: Vec<CommitInfo>,
+    pub rows: Vec<GraphRow>,
+    pub state: GraphState,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub session: String,
+    pub commits: Vec<CommitInfo>,
+    pub rows: Vec<GraphRow>,
+    pub next_cursor: Option<String>, // 已到末尾时为 None
+    pub total: usize,  // 目前已找到的提交数，遍历未完成时还会增加
+    pub complete: bool, // 遍历是否已经完成
+}
+
+/// 文件历史中的一条记录
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileHistoryEntry {
+    pub commit: CommitInfo,
+    pub path: String,             // 该提交中文件的路径
+    pub old_path: Option<String>, // 本次提交重命名前的路径
+    pub status: String,           // "added", "modified", "deleted", "renamed"
+}
+
+/// 版本表达式解析出的对象
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolvedObject {
+    pub id: String,
+    pub kind: String,              // "commit", "tree", "blob", "tag"
+    pub summary: String,           // 提交说明首行或标签名，其它对象为空
+    pub commit: Option<CommitInfo>,
+    pub position: Option<usize>,   // 提交在提交历史中的位置（从 0 开始），不在历史中时为 None
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevisionResolution {
+    pub spec: String,
+    pub ambiguous: bool, // 缩写的提交 id 对应多个对象，candidates 中是全部候选
+    pub candidates: Vec<ResolvedObject>,
+}
+
+/// 某个版本中目录下的一项
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TreeEntryInfo {
+    pub name: String,
+    pub path: String,       // 相对仓库根目录的完整路径
+    pub kind: String,       // "tree", "blob", "symlink", "submodule"
+    pub mode: String,       // 八进制文件模式，如 "100644"
+    pub id: String,         // 对象 id，子模块为其指向的提交
+    pub size: Option<u64>,  // 文件大小，目录和子模块为 None
+}
+
+/// 某个版本中文件的内容
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlobContent {
+    pub path: String,
+    pub id: String,
+    pub size: u64,
+    pub binary: bool,
+    pub truncated: bool,         // 超过大小上限，只返回了开头部分
+    pub content: Option<String>, // 二进制文件为 None
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BlameFileOptions {
+    pub ignore_whitespace: bool,
+    pub ignore_revs: Vec<String>,     // 额外忽略的提交
+    pub skip_ignore_revs_file: bool,  // 不读取 blame.ignoreRevsFile / .git-blame-ignore-revs
+}
+
+/// 一段连续且来自同一提交的行
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BlameRange {
+    pub start_line: usize,          // 在所查看版本中的起始行（从 1 开始）
+    pub lines: usize,
+    pub commit_id: Option<String>,  // 工作区中尚未提交的行为 None
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: i64,
+    pub summary: String,
+    pub orig_path: String,          // 该提交中文件的路径
+    pub orig_start_line: usize,     // 在该提交的版本中的起始行
+    pub parent_id: Option<String>,  // 查看这些行之前的版本时使用的版本（第一个父提交）
+    pub boundary: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlameResult {
+    pub path: String,
+    pub rev: Option<String>,      // 为 None 时是工作区
+    pub ranges: Vec<BlameRange>,
+    pub ignored_revs: Vec<String>, // 生效的忽略提交
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PickaxeKind {
+    #[default]
+    String, // git log -S：字符串出现次数发生变化
+    Regex,  // git log -G：新增或删除的行匹配正则
+}
+
+/// 在历史中搜索内容变化的条件
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PickaxeOptions {
+    pub kind: PickaxeKind,
+    pub pattern: String,
+    pub ignore_case: bool,
+    pub paths: Vec<String>,         // 只比较这些路径，为空时比较全部文件
+    pub rev: Option<String>,        // 起点，默认 HEAD
+    pub max_count: Option<usize>,   // 找到这么多提交后停止
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PickaxeMatch {
+    pub commit: CommitInfo,
+    pub paths: Vec<String>, // 匹配的文件
+}
+
+/// `pickaxe-results` 事件：一批新找到的提交，发送给发起搜索的窗口
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PickaxeResultsEvent {
+    pub id: String,
+    pub matches: Vec<PickaxeMatch>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PickaxeSummary {
+    pub scanned: usize,
+    pub matched: usize,
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeFilter {
+    NoMerges,
+    MergesOnly,
+}
+
+/// 提交历史的遍历起点和过滤条件；起点全部为空时从 HEAD 开始
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct HistoryOptions {
+    pub branches: bool,
+    pub remotes: bool,
+    pub tags: bool,
+    pub refs: Vec<String>, // 指定的引用或版本，非空时不再自动包含 HEAD
+    pub author: Option<String>,    // 匹配 "姓名 <邮箱>" 的正则，不区分大小写
+    pub committer: Option<String>, // 同上，匹配提交者
+    pub since: Option<i64>,        // 提交时间下限（Unix 秒，含）
+    pub until: Option<i64>,        // 提交时间上限（Unix 秒，含）
+    pub message: Option<String>,   // 匹配完整提交说明的正则，不区分大小写
+    pub first_parent: bool,
+    pub merges: Option<MergeFilter>,
+    pub simplify_by_decoration: bool, // 只保留有引用指向的提交
+}
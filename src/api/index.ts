@@ -6,6 +6,8 @@ export interface RepositoryConfig {
   path: string;
   added_at: string;
   last_opened_at: string | null;
+  // Branch name patterns, `*` matches any characters
+  protected_branches: string[];
 }
 
 export interface RepoInfo {
@@ -13,16 +15,123 @@ export interface RepoInfo {
   name: string;
   current_branch: string;
   is_bare: boolean;
+  upstream: UpstreamStatus;
+}
+
+export interface UpstreamStatus {
+  status: 'none' | 'gone' | 'tracking';
+  upstream: string | null;
+  ahead: number;
+  behind: number;
+}
+
+export interface BranchInfo {
+  name: string;
+  full_name: string;
+  is_remote: boolean;
+  is_head: boolean;
+  commit_id: string | null;
+  // null for remote-tracking branches
+  upstream: UpstreamStatus | null;
 }
 
 export interface CommitInfo {
   id: string;
+  // Unique within the repository, length follows core.abbrev
   short_id: string;
   message: string;
+  // First paragraph joined into one line
+  summary: string;
+  body: string;
+  // Declared message encoding; null means UTF-8
+  encoding: string | null;
   author_name: string;
   author_email: string;
+  author_timestamp: number;
+  // Offsets from UTC in minutes, for showing times in the original timezone
+  author_offset: number;
+  committer_name: string;
+  committer_email: string;
+  // Committer time
   timestamp: number;
+  committer_offset: number;
   parent_ids: string[];
+  trailers: CommitTrailer[];
+  refs: RefLabel[];
+}
+
+// e.g. Signed-off-by, Co-authored-by
+export interface CommitTrailer {
+  key: string;
+  value: string;
+}
+
+export interface RefLabel {
+  name: string;
+  full_name: string;
+  kind: 'head' | 'branch' | 'remote' | 'tag';
+  // The branch HEAD points at, or HEAD itself when detached
+  is_current: boolean;
+}
+
+// Starting points of a history walk; HEAD is included unless `refs` is given
+export type MergeFilter = 'no_merges' | 'merges_only';
+
+// Author / committer / message patterns are case-insensitive regexes; author and committer
+// match against "Name <email>". since / until are committer times in Unix seconds (inclusive).
+export interface HistoryOptions {
+  branches?: boolean;
+  remotes?: boolean;
+  tags?: boolean;
+  refs?: string[];
+  author?: string;
+  committer?: string;
+  since?: number;
+  until?: number;
+  message?: string;
+  first_parent?: boolean;
+  merges?: MergeFilter;
+  simplify_by_decoration?: boolean;
+}
+
+// Edge kinds: 'pass' runs through the row; 'in' / 'fork' enter the commit from the same / another column;
+// 'out' / 'merge' leave the commit to its first / other parents
+export interface GraphEdge {
+  kind: 'pass' | 'in' | 'fork' | 'out' | 'merge';
+  from: number;
+  to: number;
+  color: number;
+}
+
+export interface GraphRow {
+  id: string;
+  column: number;
+  color: number;
+  width: number;
+  edges: GraphEdge[];
+}
+
+// Opaque layout state; pass the one returned by the previous page to continue the graph
+export interface GraphState {
+  lanes: ({ commit_id: string; color: number } | null)[];
+  next_color: number;
+}
+
+export interface CommitGraphPage {
+  commits: CommitInfo[];
+  rows: GraphRow[];
+  state: GraphState;
+}
+
+export interface HistoryPage {
+  session: string;
+  commits: CommitInfo[];
+  rows: GraphRow[];
+  // null once the last page has been returned
+  next_cursor: string | null;
+  // Commits matched so far; grows until complete is true
+  total: number;
+  complete: boolean;
 }
 
 export interface FileInfo {
@@ -34,6 +143,127 @@ export interface FileInfo {
 export interface FileStatusResponse {
   staged: FileInfo[];
   unstaged: FileInfo[];
+  conflicted: FileInfo[];
+  state: string;
+  rebase: RebaseProgress | null;
+}
+
+export interface RebaseProgress {
+  current: number;
+  total: number;
+  head_name: string;
+  onto: string;
+}
+
+export interface FileHistoryEntry {
+  commit: CommitInfo;
+  // Path of the file in this commit
+  path: string;
+  // Set when this commit renamed the file from old_path
+  old_path: string | null;
+  status: 'added' | 'modified' | 'deleted' | 'renamed';
+}
+
+export interface ResolvedObject {
+  id: string;
+  kind: 'commit' | 'tree' | 'blob' | 'tag';
+  // Commit summary or tag name; empty for trees and blobs
+  summary: string;
+  commit: CommitInfo | null;
+  // Offset of the commit in the history (the given session, or the default history from HEAD)
+  position: number | null;
+}
+
+export interface RevisionResolution {
+  spec: string;
+  // An abbreviated id matched several objects; candidates lists all of them
+  ambiguous: boolean;
+  candidates: ResolvedObject[];
+}
+
+export interface TreeEntryInfo {
+  name: string;
+  // Full path from the repository root
+  path: string;
+  kind: 'tree' | 'blob' | 'symlink' | 'submodule';
+  // Octal file mode, e.g. "100644"
+  mode: string;
+  // Object id; the checked-out commit for submodules
+  id: string;
+  // null for directories and submodules
+  size: number | null;
+}
+
+export interface BlobContent {
+  path: string;
+  id: string;
+  size: number;
+  binary: boolean;
+  // Only the first `limit` bytes were returned
+  truncated: boolean;
+  // null for binary files
+  content: string | null;
+}
+
+export interface BlameFileOptions {
+  ignore_whitespace?: boolean;
+  // Extra commits to skip, on top of blame.ignoreRevsFile / .git-blame-ignore-revs
+  ignore_revs?: string[];
+  skip_ignore_revs_file?: boolean;
+}
+
+// A run of lines coming from the same commit. To blame the parent of a line's commit,
+// call blameFile again with orig_path at parent_id.
+export interface BlameRange {
+  start_line: number;
+  lines: number;
+  // null for uncommitted lines in the working copy
+  commit_id: string | null;
+  author_name: string;
+  author_email: string;
+  timestamp: number;
+  summary: string;
+  orig_path: string;
+  orig_start_line: number;
+  parent_id: string | null;
+  boundary: boolean;
+}
+
+export interface BlameResult {
+  path: string;
+  rev: string | null;
+  ranges: BlameRange[];
+  ignored_revs: string[];
+}
+
+// 'string' finds commits changing the number of occurrences (git log -S);
+// 'regex' finds commits whose added or removed lines match (git log -G)
+export type PickaxeKind = 'string' | 'regex';
+
+export interface PickaxeOptions {
+  kind?: PickaxeKind;
+  pattern: string;
+  ignore_case?: boolean;
+  paths?: string[];
+  rev?: string;
+  max_count?: number;
+}
+
+export interface PickaxeMatch {
+  commit: CommitInfo;
+  paths: string[];
+}
+
+// Payload of the `pickaxe-results` event, sent in batches to the window that started the search
+export interface PickaxeResultsEvent {
+  id: string;
+  matches: PickaxeMatch[];
+}
+
+export interface PickaxeSummary {
+  scanned: number;
+  matched: number;
+  cancelled: boolean;
 }
 
 export interface DiffResponse {
@@ -43,6 +273,226 @@ export interface DiffResponse {
   hunks: any[];
 }
 
+export interface StashEntry {
+  index: number;
+  id: string;
+  message: string;
+}
+
+export interface HunkSelection {
+  file_path: string;
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+}
+
+export type MergePolicy = 'ff_only' | 'ff' | 'no_ff';
+
+export interface MergeResult {
+  status: 'up_to_date' | 'fast_forward' | 'merged' | 'conflicted';
+  commit_id: string | null;
+  conflicts: string[];
+}
+
+export type RebaseAction = 'pick' | 'reword' | 'edit' | 'squash' | 'fixup' | 'drop' | 'exec';
+
+export interface RebaseTodoItem {
+  action: RebaseAction;
+  commit_id: string | null;
+  summary: string;
+  message: string | null;
+  command: string | null;
+}
+
+export interface RebaseResult {
+  status: 'completed' | 'conflicted' | 'stopped' | 'exec_failed';
+  progress: RebaseProgress | null;
+  conflicts: string[];
+  message: string | null;
+  // Commits skipped in this call because their changes were already in the new base
+  dropped: string[];
+}
+
+export interface SequenceResult {
+  status: 'completed' | 'conflicted';
+  commit_ids: string[];
+  // Commits whose changes were already in HEAD, so no commit was created
+  skipped: string[];
+  current: string | null;
+  conflicts: string[];
+}
+
+export type ResetMode = 'soft' | 'mixed' | 'hard';
+
+export interface BackupEntry {
+  name: string;
+  id: string;
+  previous_head: string;
+  branch: string | null;
+  message: string;
+  timestamp: number;
+  has_changes: boolean;
+}
+
+export interface ResetResult {
+  head: string;
+  backup: BackupEntry | null;
+}
+
+export interface ReflogEntry {
+  index: number;
+  old_id: string;
+  new_id: string;
+  message: string;
+  committer_name: string;
+  committer_email: string;
+  timestamp: number;
+}
+
+export type RemoteProgress =
+  | {
+      kind: 'transfer';
+      received_objects: number;
+      total_objects: number;
+      indexed_objects: number;
+      indexed_deltas: number;
+      total_deltas: number;
+      received_bytes: number;
+    }
+  | { kind: 'sideband'; message: string }
+  | { kind: 'push_transfer'; current: number; total: number; bytes: number }
+  | { kind: 'push_update'; reference: string; message: string | null }
+  | { kind: 'checkout'; completed: number; total: number };
+
+// Payload of the `remote-progress` event, sent only to the window that started the operation
+export interface RemoteProgressEvent {
+  path: string;
+  operation: 'fetch' | 'pull' | 'push' | 'clone';
+  progress: RemoteProgress;
+}
+
+export type ForcePush = 'with_lease' | 'force';
+
+export interface RefUpdate {
+  name: string;
+  old_id: string | null;
+  new_id: string | null;
+}
+
+export interface FetchResult {
+  remote: string;
+  updated: RefUpdate[];
+  received_objects: number;
+  received_bytes: number;
+}
+
+export interface PushRefStatus {
+  reference: string;
+  status: 'ok' | 'rejected';
+  message: string | null;
+}
+
+export interface PushResult {
+  remote: string;
+  refs: PushRefStatus[];
+  upstream: string | null;
+}
+
+export interface CredentialPrompt {
+  kind: 'user_pass' | 'passphrase';
+  url: string;
+  username: string | null;
+  key_path: string | null;
+  attempt: number;
+}
+
+// Payload of the `credential-request` event; answer it with answerCredentialRequest
+export interface CredentialRequestEvent {
+  id: string;
+  path: string;
+  prompt: CredentialPrompt;
+}
+
+export interface CredentialAnswer {
+  username?: string | null;
+  password: string;
+}
+
+export type PullStrategy = 'merge' | 'rebase' | 'ff_only';
+
+export interface PullResult {
+  status: 'up_to_date' | 'fast_forward' | 'merged' | 'rebased' | 'conflicted' | 'divergent';
+  strategy: PullStrategy | null;
+  upstream: string;
+  commit_id: string | null;
+  conflicts: string[];
+  ahead: number;
+  behind: number;
+  message: string | null;
+}
+
+export interface CleanupCandidate {
+  name: string;
+  full_name: string;
+  is_remote: boolean;
+  reasons: ('merged' | 'gone' | 'stale_remote')[];
+  commit_id: string;
+  summary: string;
+  author_name: string;
+  timestamp: number;
+}
+
+export interface CleanupScan {
+  base: string;
+  candidates: CleanupCandidate[];
+  // Remotes that could not be contacted, so their stale refs were not checked
+  skipped_remotes: string[];
+}
+
+export interface DeletedBranch {
+  index: number;
+  full_name: string;
+  commit_id: string;
+  timestamp: number;
+}
+
+export interface CleanupResult {
+  deleted: string[];
+  failures: { full_name: string; message: string }[];
+}
+
+// Error thrown by fetchRemote / pullRemote / pushRemote
+// 'protected' means the operation rewrites a protected branch; retry with confirmProtected
+export interface RemoteError {
+  kind: 'auth' | 'certificate' | 'network' | 'cancelled' | 'protected' | 'other';
+  message: string;
+}
+
+// Error thrown by rebase / reset / restore operations that may rewrite branch history
+// 'protected' carries the branch that needs confirmation; retry with confirmProtected
+export interface RewriteError {
+  kind: 'protected' | 'other';
+  message: string;
+  branch: string | null;
+}
+
+export interface RemoteInfo {
+  name: string;
+  url: string | null;
+  push_url: string | null;
+  fetch_refspecs: string[];
+  push_refspecs: string[];
+}
+
+export interface CloneOptions {
+  branch?: string | null;
+  // Shallow clones are rejected for local paths and file:// URLs
+  depth?: number | null;
+  recursive?: boolean;
+  bare?: boolean;
+}
+
 // Repository list operations
 export async function getRepositoryList(): Promise<RepositoryConfig[]> {
   return invoke<RepositoryConfig[]>('get_repository_list');
@@ -56,6 +506,13 @@ export async function removeRepository(id: string): Promise<boolean> {
   return invoke<boolean>('remove_repository', { id });
 }
 
+export async function setProtectedBranches(
+  id: string,
+  branches: string[]
+): Promise<RepositoryConfig> {
+  return invoke<RepositoryConfig>('set_protected_branches', { id, branches });
+}
+
 // Repository operations
 export async function openRepo(path: string): Promise<RepoInfo> {
   return invoke<RepoInfo>('open_repo', { path });
@@ -69,6 +526,43 @@ export async function getCommits(
   return invoke<CommitInfo[]>('get_commits', { path, limit, offset });
 }
 
+// Omit the cursor to start a new walk with `options`; pass next_cursor of the previous page to continue
+export async function getHistoryPage(
+  path: string,
+  limit: number,
+  cursor?: string,
+  options?: HistoryOptions
+): Promise<HistoryPage> {
+  return invoke<HistoryPage>('get_history_page', {
+    path,
+    cursor: cursor ?? null,
+    limit,
+    options: options ?? null,
+  });
+}
+
+export async function closeHistorySession(session: string): Promise<boolean> {
+  return invoke<boolean>('close_history_session', { session });
+}
+
+// Accepts anything `git rev-parse` does; pass the history session to get positions within it
+export async function resolveRevision(
+  path: string,
+  spec: string,
+  session?: string
+): Promise<RevisionResolution> {
+  return invoke<RevisionResolution>('resolve_revision', { path, spec, session: session ?? null });
+}
+
+export async function getCommitGraph(
+  path: string,
+  limit: number,
+  offset: number,
+  state?: GraphState
+): Promise<CommitGraphPage> {
+  return invoke<CommitGraphPage>('get_commit_graph', { path, limit, offset, state: state ?? null });
+}
+
 export async function getStatus(path: string): Promise<FileStatusResponse> {
   return invoke<FileStatusResponse>('get_status', { path });
 }
@@ -77,7 +571,463 @@ export async function getDiff(path: string, filePath: string): Promise<DiffRespo
   return invoke<DiffResponse>('get_diff', { path, file_path: filePath });
 }
 
-// Git utilities
+// Commits touching a file, following renames like `git log --follow`
+export async function getFileHistory(
+  path: string,
+  filePath: string,
+  limit: number,
+  offset = 0,
+  rev?: string
+): Promise<FileHistoryEntry[]> {
+  return invoke<FileHistoryEntry[]>('get_file_history', {
+    path,
+    filePath,
+    rev: rev ?? null,
+    limit,
+    offset,
+  });
+}
+
+// Pass the entry's old_path for rename commits so the diff compares both paths
+export async function getCommitFileDiff(
+  path: string,
+  commitId: string,
+  filePath: string,
+  oldPath?: string | null
+): Promise<DiffResponse> {
+  return invoke<DiffResponse>('get_commit_file_diff', {
+    path,
+    commitId,
+    filePath,
+    oldPath: oldPath ?? null,
+  });
+}
+
+// Revision browsing
+export async function listTree(path: string, rev: string, dir?: string): Promise<TreeEntryInfo[]> {
+  return invoke<TreeEntryInfo[]>('list_tree', { path, rev, dir: dir ?? null });
+}
+
+// Reads at most `limit` bytes (1 MiB by default)
+export async function readBlob(
+  path: string,
+  rev: string,
+  filePath: string,
+  limit?: number
+): Promise<BlobContent> {
+  return invoke<BlobContent>('read_blob', { path, rev, filePath, limit: limit ?? null });
+}
+
+// Omit rev to blame the working copy
+export async function blameFile(
+  path: string,
+  filePath: string,
+  rev?: string | null,
+  options?: BlameFileOptions
+): Promise<BlameResult> {
+  return invoke<BlameResult>('blame_file', {
+    path,
+    filePath,
+    rev: rev ?? null,
+    options: options ?? null,
+  });
+}
+
+// `id` is chosen by the caller, tags the `pickaxe-results` events and is used to cancel the search.
+// Resolves once the search finishes or is cancelled.
+export async function searchHistoryContent(
+  id: string,
+  path: string,
+  options: PickaxeOptions
+): Promise<PickaxeSummary> {
+  return invoke<PickaxeSummary>('search_history_content', { id, path, options });
+}
+
+export async function cancelHistorySearch(id: string): Promise<boolean> {
+  return invoke<boolean>('cancel_history_search', { id });
+}
+
+// Stash operations
+export async function stashPush(
+  path: string,
+  options: { message?: string; paths?: string[]; includeUntracked?: boolean } = {}
+): Promise<string> {
+  return invoke<string>('stash_push', {
+    path,
+    message: options.message ?? null,
+    paths: options.paths ?? [],
+    includeUntracked: options.includeUntracked ?? false,
+  });
+}
+
+export async function stashPushHunks(
+  path: string,
+  hunks: HunkSelection[],
+  message?: string
+): Promise<string> {
+  return invoke<string>('stash_push_hunks', { path, hunks, message: message ?? null });
+}
+
+export async function getStashes(path: string): Promise<StashEntry[]> {
+  return invoke<StashEntry[]>('get_stashes', { path });
+}
+
+// restoreIndex also restores staged changes (git stash apply --index)
+export async function stashApply(
+  path: string,
+  index: number,
+  pop = false,
+  restoreIndex = false
+): Promise<void> {
+  return invoke<void>('stash_apply', { path, index, pop, restoreIndex });
+}
+
+export async function stashDrop(path: string, index: number): Promise<void> {
+  return invoke<void>('stash_drop', { path, index });
+}
+
+// Merge operations
+export async function mergeBranch(
+  path: string,
+  source: string,
+  policy?: MergePolicy,
+  message?: string
+): Promise<MergeResult> {
+  return invoke<MergeResult>('merge_branch', {
+    path,
+    source,
+    policy: policy ?? null,
+    message: message ?? null,
+  });
+}
+
+export async function mergeContinue(path: string, message?: string): Promise<string> {
+  return invoke<string>('merge_continue', { path, message: message ?? null });
+}
+
+export async function mergeAbort(path: string): Promise<void> {
+  return invoke<void>('merge_abort', { path });
+}
+
+// Rebase operations
+export async function rebaseBranch(
+  path: string,
+  upstream: string,
+  options: { onto?: string; autostash?: boolean; confirmProtected?: boolean } = {}
+): Promise<RebaseResult> {
+  return invoke<RebaseResult>('rebase_branch', {
+    path,
+    upstream,
+    onto: options.onto ?? null,
+    autostash: options.autostash ?? false,
+    confirmProtected: options.confirmProtected ?? null,
+  });
+}
+
+export async function getRebasePlan(
+  path: string,
+  upstream: string,
+  autosquash?: boolean
+): Promise<RebaseTodoItem[]> {
+  return invoke<RebaseTodoItem[]>('get_rebase_plan', { path, upstream, autosquash: autosquash ?? null });
+}
+
+export async function interactiveRebase(
+  path: string,
+  upstream: string,
+  plan: RebaseTodoItem[],
+  options: { onto?: string; autostash?: boolean; confirmProtected?: boolean } = {}
+): Promise<RebaseResult> {
+  return invoke<RebaseResult>('interactive_rebase', {
+    path,
+    upstream,
+    onto: options.onto ?? null,
+    plan,
+    autostash: options.autostash ?? false,
+    confirmProtected: options.confirmProtected ?? null,
+  });
+}
+
+export async function rebaseContinue(path: string): Promise<RebaseResult> {
+  return invoke<RebaseResult>('rebase_continue', { path });
+}
+
+export async function rebaseSkip(path: string): Promise<RebaseResult> {
+  return invoke<RebaseResult>('rebase_skip', { path });
+}
+
+export async function rebaseAbort(path: string): Promise<string | null> {
+  return invoke<string | null>('rebase_abort', { path });
+}
+
+// Cherry-pick / revert operations
+export async function cherryPick(
+  path: string,
+  commits: string[],
+  options: { mainline?: number; noCommit?: boolean } = {}
+): Promise<SequenceResult> {
+  return invoke<SequenceResult>('cherry_pick', {
+    path,
+    commits,
+    mainline: options.mainline ?? null,
+    noCommit: options.noCommit ?? false,
+  });
+}
+
+export async function revertCommits(
+  path: string,
+  commits: string[],
+  options: { mainline?: number; noCommit?: boolean } = {}
+): Promise<SequenceResult> {
+  return invoke<SequenceResult>('revert_commits', {
+    path,
+    commits,
+    mainline: options.mainline ?? null,
+    noCommit: options.noCommit ?? false,
+  });
+}
+
+export async function sequenceContinue(path: string): Promise<SequenceResult> {
+  return invoke<SequenceResult>('sequence_continue', { path });
+}
+
+export async function sequenceAbort(path: string): Promise<void> {
+  return invoke<void>('sequence_abort', { path });
+}
+
+// Reset operations
+export async function resetToCommit(
+  path: string,
+  target: string,
+  mode: ResetMode,
+  confirmProtected?: boolean
+): Promise<ResetResult> {
+  return invoke<ResetResult>('reset_to_commit', {
+    path,
+    target,
+    mode,
+    confirmProtected: confirmProtected ?? null,
+  });
+}
+
+export async function getBackups(path: string): Promise<BackupEntry[]> {
+  return invoke<BackupEntry[]>('get_backups', { path });
+}
+
+export async function restoreBackup(
+  path: string,
+  name: string,
+  confirmProtected?: boolean
+): Promise<ResetResult> {
+  return invoke<ResetResult>('restore_backup', {
+    path,
+    name,
+    confirmProtected: confirmProtected ?? null,
+  });
+}
+
+export async function deleteBackup(path: string, name: string): Promise<void> {
+  return invoke<void>('delete_backup', { path, name });
+}
+
+// Reflog operations
+export async function getReflog(path: string, reference?: string): Promise<ReflogEntry[]> {
+  return invoke<ReflogEntry[]>('get_reflog', { path, reference: reference ?? null });
+}
+
+export async function restoreReflogEntry(
+  path: string,
+  index: number,
+  reference?: string,
+  confirmProtected?: boolean
+): Promise<ResetResult> {
+  return invoke<ResetResult>('restore_reflog_entry', {
+    path,
+    reference: reference ?? null,
+    index,
+    confirmProtected: confirmProtected ?? null,
+  });
+}
+
+// Remote sync operations
+export async function fetchRemote(
+  path: string,
+  options: { remote?: string; prune?: boolean; tags?: boolean } = {}
+): Promise<FetchResult> {
+  return invoke<FetchResult>('fetch_remote', {
+    path,
+    remote: options.remote ?? null,
+    prune: options.prune ?? false,
+    tags: options.tags ?? false,
+  });
+}
+
+export async function pullRemote(
+  path: string,
+  strategy?: PullStrategy,
+  confirmProtected?: boolean
+): Promise<PullResult> {
+  return invoke<PullResult>('pull_remote', {
+    path,
+    strategy,
+    confirmProtected: confirmProtected ?? null,
+  });
+}
+
+export async function pushRemote(
+  path: string,
+  options: {
+    remote?: string;
+    branch?: string;
+    // true forces with lease against the last fetched remote-tracking ref
+    force?: boolean | ForcePush;
+    setUpstream?: boolean;
+    tags?: boolean;
+    confirmProtected?: boolean;
+  } = {}
+): Promise<PushResult> {
+  const force = options.force === true ? 'with_lease' : options.force || null;
+  return invoke<PushResult>('push_remote', {
+    path,
+    remote: options.remote ?? null,
+    branch: options.branch ?? null,
+    force,
+    setUpstream: options.setUpstream ?? false,
+    tags: options.tags ?? false,
+    confirmProtected: options.confirmProtected ?? null,
+  });
+}
+
+export async function publishBranch(
+  path: string,
+  options: { branch?: string; remote?: string; remoteBranch?: string } = {}
+): Promise<PushResult> {
+  return invoke<PushResult>('publish_branch', {
+    path,
+    branch: options.branch ?? null,
+    remote: options.remote ?? null,
+    remoteBranch: options.remoteBranch ?? null,
+  });
+}
+
+// Pass null to cancel the request
+export async function answerCredentialRequest(
+  id: string,
+  answer: CredentialAnswer | null
+): Promise<void> {
+  return invoke<void>('answer_credential_request', { id, answer });
+}
+
+// Remote management
+export async function getRemotes(path: string): Promise<RemoteInfo[]> {
+  return invoke<RemoteInfo[]>('get_remotes', { path });
+}
+
+export async function addRemote(
+  path: string,
+  name: string,
+  url: string,
+  fetchRefspec?: string
+): Promise<RemoteInfo> {
+  return invoke<RemoteInfo>('add_remote', {
+    path,
+    name,
+    url,
+    fetchRefspec: fetchRefspec ?? null,
+  });
+}
+
+// Returns fetch refspecs that could not be renamed automatically
+export async function renameRemote(
+  path: string,
+  name: string,
+  newName: string
+): Promise<string[]> {
+  return invoke<string[]>('rename_remote', { path, name, newName });
+}
+
+export async function removeRemote(path: string, name: string): Promise<void> {
+  return invoke<void>('remove_remote', { path, name });
+}
+
+// With push = true, a null url clears the push URL
+export async function setRemoteUrl(
+  path: string,
+  name: string,
+  url: string | null,
+  push = false
+): Promise<RemoteInfo> {
+  return invoke<RemoteInfo>('set_remote_url', { path, name, url, push });
+}
+
+export async function setRemoteFetchRefspecs(
+  path: string,
+  name: string,
+  refspecs: string[]
+): Promise<RemoteInfo> {
+  return invoke<RemoteInfo>('set_remote_fetch_refspecs', { path, name, refspecs });
+}
+
+// Clone operations
+// `id` is chosen by the caller and used to cancel the clone; progress events carry the destination as `path`
+export async function cloneRepository(
+  id: string,
+  url: string,
+  destination: string,
+  options: CloneOptions = {}
+): Promise<RepositoryConfig> {
+  return invoke<RepositoryConfig>('clone_repository', { id, url, destination, options });
+}
+
+export async function cancelClone(id: string): Promise<boolean> {
+  return invoke<boolean>('cancel_clone', { id });
+}
+
+// Branch operations
+export async function getBranches(path: string): Promise<BranchInfo[]> {
+  return invoke<BranchInfo[]>('get_branches', { path });
+}
+
+export async function setBranchUpstream(
+  path: string,
+  branch: string,
+  upstream: string
+): Promise<BranchInfo> {
+  return invoke<BranchInfo>('set_branch_upstream', { path, branch, upstream });
+}
+
+export async function unsetBranchUpstream(path: string, branch: string): Promise<BranchInfo> {
+  return invoke<BranchInfo>('unset_branch_upstream', { path, branch });
+}
+
+// Branch cleanup operations
+export async function scanBranchCleanup(
+  path: string,
+  base: string,
+  checkRemotes = false
+): Promise<CleanupScan> {
+  return invoke<CleanupScan>('scan_branch_cleanup', { path, base, checkRemotes });
+}
+
+// Takes full ref names; each deleted tip is recorded in the recovery log
+export async function deleteBranches(path: string, names: string[]): Promise<CleanupResult> {
+  return invoke<CleanupResult>('delete_branches', { path, names });
+}
+
+export async function getDeletedBranches(path: string): Promise<DeletedBranch[]> {
+  return invoke<DeletedBranch[]>('get_deleted_branches', { path });
+}
+
+// Returns the restored full ref name
+export async function restoreDeletedBranch(
+  path: string,
+  index: number,
+  name?: string
+): Promise<string> {
+  return invoke<string>('restore_deleted_branch', { path, index, name: name ?? null });
+}
+
+
 export async function findRepoRoot(path: string): Promise<string> {
   return invoke<string>('find_repo_root', { path });
 }